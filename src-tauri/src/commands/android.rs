@@ -1,40 +1,33 @@
-use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
 use tokio;
 use tauri::Emitter;
 use arboard::{Clipboard, ImageData};
 use image::GenericImageView;
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use crate::utils::new_command;
+use crate::toolchain::Tool;
+use super::settings::EmulatorLaunchParams;
+use crate::logs::crash::{CrashCapture, CrashCaptureOptions};
+use crate::logs::logcat::LogcatParser;
+use crate::logs::{self, LogFilter, LogFilterOptions, SessionId, StopReason};
+use crate::screenrecord::{self, parse_size, RecordingOptions, RecordingResult, ScreenRecorder};
+use crate::db::captures::CaptureKind;
+use super::captures::{self, CaptureDevice};
+use super::packages::{
+    has_extension, run_checked, run_streaming, InstallOptions, InstallOutcome, InstallProgress, InstallStage,
+    PackageAction, PackageInfo, TempDir,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use super::device::{
+    BootProgress, BootResult, BootStage, Device, DeviceStatus, EmulatorBackend, Platform,
+    DEFAULT_BOOT_TIMEOUT_SECS,
+};
 
-static LOGCAT_BUFFER: Mutex<Vec<String>> = Mutex::new(Vec::new());
-static LOGCAT_RUNNING: Mutex<bool> = Mutex::new(false);
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AndroidEmulator {
-    pub id: String,
-    pub name: String,
-    pub device_type: String,
-    pub os_version: String,
-    pub status: String,
-}
 
 #[tauri::command]
-pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
-    let emulator_exe = if cfg!(target_os = "windows") {
-        "emulator.exe"
-    } else {
-        "emulator"
-    };
-    
-    let emulator_path = std::path::Path::new(&android_home)
-        .join("emulator")
-        .join(emulator_exe);
+pub async fn list_android_emulators() -> Result<Vec<Device>, String> {
+    let android_home = crate::toolchain::android_home()?;
+    let emulator_path = crate::toolchain::resolve(Tool::Emulator)?;
     
     let output = new_command(&emulator_path)
         .arg("-list-avds")
@@ -53,29 +46,22 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
     for line in avd_list.lines() {
         let name = line.trim();
         if !name.is_empty() {
-            emulators.push(AndroidEmulator {
+            emulators.push(Device {
                 id: name.to_string(),
                 name: name.to_string(),
                 device_type: name.to_string(),
                 os_version: "".to_string(),
-                status: "stopped".to_string(),
+                status: DeviceStatus::Stopped,
+                platform: Platform::Android,
             });
         }
     }
 
     // Check running emulators using adb devices
-    let adb_exe = if cfg!(target_os = "windows") {
-        "adb.exe"
-    } else {
-        "adb"
-    };
-    
-    let adb_path = std::path::Path::new(&android_home)
-        .join("platform-tools")
-        .join(adb_exe);
+    let adb_path = crate::toolchain::resolve(Tool::Adb);
     
-    if adb_path.exists() {
-        if let Ok(adb_output) = new_command(&adb_path).arg("devices").output() {
+    if let Ok(adb_path) = &adb_path {
+        if let Ok(adb_output) = new_command(adb_path).arg("devices").output() {
             let devices = String::from_utf8_lossy(&adb_output.stdout);
             
             // 创建一个映射来存储设备序列号到 AVD 名称的关系
@@ -86,7 +72,7 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
                     let parts: Vec<&str> = line.split_whitespace().collect();
                     if let Some(serial) = parts.first() {
                         // Query the AVD name for this emulator
-                        if let Ok(avd_output) = new_command(&adb_path)
+                        if let Ok(avd_output) = new_command(adb_path)
                             .args(["-s", serial, "emu", "avd", "name"])
                             .output() 
                         {
@@ -104,7 +90,7 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
             for emu in &mut emulators {
                 for (serial, avd_name) in &serial_to_avd {
                     if emu.name == *avd_name {
-                        emu.status = "running".to_string();
+                        emu.status = DeviceStatus::Running;
                         emu.id = serial.clone();
                         break;
                     }
@@ -123,20 +109,23 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
             for emu in &mut emulators {
-                if emu.status == "stopped" {
+                if emu.status == DeviceStatus::Stopped {
                     // 检查是否有包含该模拟器名称的进程
                     if output_str.contains(&format!("-avd {}", emu.name)) || 
                        output_str.contains(&format!("-avd \"{}\"", emu.name)) {
-                        emu.status = "running".to_string();
+                        emu.status = DeviceStatus::Running;
                         // 尝试通过 adb 获取设备序列号
-                        if let Ok(adb_output) = new_command(&adb_path).arg("devices").output() {
+                        let Ok(adb_path) = &adb_path else {
+                            continue;
+                        };
+                        if let Ok(adb_output) = new_command(adb_path).arg("devices").output() {
                             let devices = String::from_utf8_lossy(&adb_output.stdout);
                             for line in devices.lines() {
                                 if line.contains("emulator-") && line.contains("device") {
                                     let parts: Vec<&str> = line.split_whitespace().collect();
                                     if let Some(serial) = parts.first() {
                                         // 验证这个设备是否对应当前模拟器
-                                        if let Ok(avd_output) = new_command(&adb_path)
+                                        if let Ok(avd_output) = new_command(adb_path)
                                             .args(["-s", serial, "emu", "avd", "name"])
                                             .output() 
                                         {
@@ -164,19 +153,22 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
             for emu in &mut emulators {
-                if emu.status == "stopped" {
+                if emu.status == DeviceStatus::Stopped {
                     // 检查是否有包含该模拟器名称的进程
                     if output_str.contains(&format!("-avd {}", emu.name)) {
-                        emu.status = "running".to_string();
+                        emu.status = DeviceStatus::Running;
                         // 尝试通过 adb 获取设备序列号
-                        if let Ok(adb_output) = new_command(&adb_path).arg("devices").output() {
+                        let Ok(adb_path) = &adb_path else {
+                            continue;
+                        };
+                        if let Ok(adb_output) = new_command(adb_path).arg("devices").output() {
                             let devices = String::from_utf8_lossy(&adb_output.stdout);
                             for line in devices.lines() {
                                 if line.contains("emulator-") && line.contains("device") {
                                     let parts: Vec<&str> = line.split_whitespace().collect();
                                     if let Some(serial) = parts.first() {
                                         // 验证这个设备是否对应当前模拟器
-                                        if let Ok(avd_output) = new_command(&adb_path)
+                                        if let Ok(avd_output) = new_command(adb_path)
                                             .args(["-s", serial, "emu", "avd", "name"])
                                             .output() 
                                         {
@@ -199,25 +191,260 @@ pub async fn list_android_emulators() -> Result<Vec<AndroidEmulator>, String> {
     Ok(emulators)
 }
 
-#[tauri::command]
-pub async fn start_android_emulator(id: String, app: tauri::AppHandle) -> Result<(), String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
-    let emulator_exe = if cfg!(target_os = "windows") {
-        "emulator.exe"
-    } else {
-        "emulator"
+/// 当前 adb 中所有模拟器的序列号（含尚未 online 的）
+fn emulator_serials(adb_path: &std::path::Path) -> Vec<String> {
+    let Ok(output) = new_command(adb_path).arg("devices").output() else {
+        return Vec::new();
     };
-    
-    let emulator_path = std::path::Path::new(&android_home)
-        .join("emulator")
-        .join(emulator_exe);
-    
-    if !emulator_path.exists() {
-        return Err(format!("Emulator not found at: {:?}. Please check your Android SDK path in Settings.", emulator_path));
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|serial| serial.starts_with("emulator-"))
+        .map(|serial| serial.to_string())
+        .collect()
+}
+
+/// 通过模拟器控制台查询序列号对应的 AVD 名称
+fn avd_name_of(adb_path: &std::path::Path, serial: &str) -> Option<String> {
+    let output = new_command(adb_path)
+        .args(["-s", serial, "emu", "avd", "name"])
+        .output()
+        .ok()?;
+    // 输出形如 "Pixel_7_API_34\r\nOK"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn getprop(adb_path: &std::path::Path, serial: &str, prop: &str) -> Option<String> {
+    let output = new_command(adb_path)
+        .args(["-s", serial, "shell", "getprop", prop])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 截图历史中记录的设备型号、系统版本和屏幕分辨率
+fn capture_device(adb_path: &std::path::Path, serial: &str) -> CaptureDevice {
+    let os_version = match (
+        getprop(adb_path, serial, "ro.build.version.release"),
+        getprop(adb_path, serial, "ro.build.version.sdk"),
+    ) {
+        (Some(release), Some(sdk)) if !release.is_empty() => Some(format!("Android {} (API {})", release, sdk)),
+        _ => None,
+    };
+    // `wm size` 输出 "Physical size: 1080x2400"，修改过分辨率时另有 "Override size: ..."，以最后一行为准
+    let screen_size = new_command(adb_path)
+        .args(["-s", serial, "shell", "wm", "size"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_once(": ").and_then(|(_, size)| parse_size(size)))
+                .next_back()
+        });
+    CaptureDevice {
+        model: getprop(adb_path, serial, "ro.product.model"),
+        os_version,
+        screen_size,
+    }
+}
+
+/// 系统启动完成且开机动画已结束
+fn is_boot_completed(adb_path: &std::path::Path, serial: &str) -> bool {
+    getprop(adb_path, serial, "sys.boot_completed").as_deref() == Some("1")
+        && getprop(adb_path, serial, "init.svc.bootanim").as_deref() != Some("running")
+}
+
+const GPU_MODES: [&str; 7] = [
+    "auto",
+    "host",
+    "swiftshader_indirect",
+    "angle_indirect",
+    "guest",
+    "off",
+    "auto-no-window",
+];
+const NETSPEED_PRESETS: [&str; 9] = ["gsm", "hscsd", "gprs", "edge", "umts", "hsdpa", "lte", "evdo", "full"];
+const NETDELAY_PRESETS: [&str; 5] = ["gsm", "gprs", "edge", "umts", "none"];
+
+/// "a:b" 形式的两个数字，或单个数字
+fn is_numeric_range(value: &str) -> bool {
+    value.split(':').count() <= 2 && value.split(':').all(|part| part.parse::<u32>().is_ok())
+}
+
+/// 主机名，如 `dns.google`；每段由字母、数字和 '-' 组成，不以 '-' 开头或结尾
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// 把启动参数转换为 emulator 命令行参数，同时校验取值
+pub fn launch_args(params: &EmulatorLaunchParams) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+
+    if params.no_window {
+        args.push("-no-window".to_string());
     }
+    if params.wipe_data {
+        args.push("-wipe-data".to_string());
+    }
+    if params.no_snapshot {
+        args.push("-no-snapshot".to_string());
+    }
+    if params.read_only {
+        args.push("-read-only".to_string());
+    }
+
+    let dns_server = params.dns_server.trim();
+    if !dns_server.is_empty() {
+        for server in dns_server.split(',').map(str::trim) {
+            if server.parse::<std::net::IpAddr>().is_err() && !is_hostname(server) {
+                return Err(format!("Invalid DNS server address: {}", server));
+            }
+        }
+        args.push("-dns-server".to_string());
+        args.push(dns_server.to_string());
+    }
+
+    let longitude = params.gps_longitude.trim();
+    let latitude = params.gps_latitude.trim();
+    match (longitude.is_empty(), latitude.is_empty()) {
+        (true, true) => {}
+        (false, false) => {
+            let lon: f64 = longitude
+                .parse()
+                .map_err(|_| format!("Invalid GPS longitude: {}", longitude))?;
+            let lat: f64 = latitude
+                .parse()
+                .map_err(|_| format!("Invalid GPS latitude: {}", latitude))?;
+            if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+                return Err(format!("GPS position out of range: {},{}", longitude, latitude));
+            }
+            args.push("-gps".to_string());
+            args.push(format!("{},{}", longitude, latitude));
+        }
+        _ => return Err("Both GPS longitude and latitude are required".to_string()),
+    }
+
+    if let Some(memory) = params.memory {
+        if memory < 128 {
+            return Err(format!("Memory must be at least 128 MB, got {}", memory));
+        }
+        args.push("-memory".to_string());
+        args.push(memory.to_string());
+    }
+
+    let http_proxy = params.http_proxy.trim();
+    if !http_proxy.is_empty() {
+        args.push("-http-proxy".to_string());
+        args.push(http_proxy.to_string());
+    }
+
+    if let Some(cores) = params.cores {
+        if cores < 1 {
+            return Err(format!("CPU cores must be at least 1, got {}", cores));
+        }
+        args.push("-cores".to_string());
+        args.push(cores.to_string());
+    }
+
+    let gpu = params.gpu.trim();
+    if !gpu.is_empty() {
+        if !GPU_MODES.contains(&gpu) {
+            return Err(format!("Unknown GPU mode: {} (expected one of {})", gpu, GPU_MODES.join(", ")));
+        }
+        args.push("-gpu".to_string());
+        args.push(gpu.to_string());
+    }
+
+    let camera = params.camera_back.trim();
+    if !camera.is_empty() {
+        let valid = matches!(camera, "emulated" | "virtualscene" | "none")
+            || camera
+                .strip_prefix("webcam")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        if !valid {
+            return Err(format!(
+                "Invalid back camera: {} (expected emulated, virtualscene, webcamN or none)",
+                camera
+            ));
+        }
+        args.push("-camera-back".to_string());
+        args.push(camera.to_string());
+    }
+
+    let netspeed = params.netspeed.trim();
+    if !netspeed.is_empty() {
+        if !NETSPEED_PRESETS.contains(&netspeed) && !is_numeric_range(netspeed) {
+            return Err(format!("Invalid network speed: {}", netspeed));
+        }
+        args.push("-netspeed".to_string());
+        args.push(netspeed.to_string());
+    }
+
+    let netdelay = params.netdelay.trim();
+    if !netdelay.is_empty() {
+        if !NETDELAY_PRESETS.contains(&netdelay) && !is_numeric_range(netdelay) {
+            return Err(format!("Invalid network delay: {}", netdelay));
+        }
+        args.push("-netdelay".to_string());
+        args.push(netdelay.to_string());
+    }
+
+    if let Some(port) = params.port {
+        // 控制台端口必须是 5554-5682 之间的偶数
+        if !(5554..=5682).contains(&port) || port % 2 != 0 {
+            return Err(format!("Console port must be an even number between 5554 and 5682, got {}", port));
+        }
+        args.push("-port".to_string());
+        args.push(port.to_string());
+    }
+
+    let features: Vec<&str> = params
+        .features
+        .iter()
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+        .collect();
+    if !features.is_empty() {
+        if let Some(bad) = features
+            .iter()
+            .find(|f| !f.trim_start_matches('-').chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            return Err(format!("Invalid feature name: {}", bad));
+        }
+        args.push("-feature".to_string());
+        args.push(features.join(","));
+    }
+
+    args.extend(crate::utils::split_args(&params.extra_args)?);
+
+    Ok(args)
+}
+
+#[tauri::command]
+pub async fn start_android_emulator(
+    id: String,
+    boot_timeout_secs: Option<u64>,
+    app: tauri::AppHandle,
+) -> Result<BootResult, String> {
+    let android_home = crate::toolchain::android_home()?;
+    let emulator_path = crate::toolchain::resolve(Tool::Emulator)?;
     
     // Load launch parameters
     let params = crate::commands::settings::get_emulator_launch_params_sync(&id, "android");
@@ -228,29 +455,11 @@ pub async fn start_android_emulator(id: String, app: tauri::AppHandle) -> Result
         .arg(&id)
         .env("ANDROID_HOME", &android_home)
         .env("ANDROID_SDK_ROOT", &android_home)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped());
-    
-    // Apply launch parameters
-    if params.no_window {
-        cmd.arg("-no-window");
-    }
-    
-    if !params.dns_server.is_empty() {
-        cmd.arg("-dns-server").arg(&params.dns_server);
-    }
-    
-    if !params.gps_longitude.is_empty() && !params.gps_latitude.is_empty() {
-        cmd.arg("-gps").arg(format!("{},{}", params.gps_longitude, params.gps_latitude));
-    }
-    
-    if let Some(memory) = params.memory {
-        cmd.arg("-memory").arg(memory.to_string());
-    }
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
     
-    if !params.http_proxy.is_empty() {
-        cmd.arg("-http-proxy").arg(&params.http_proxy);
-    }
+    // 参数有误时在启动前直接报错
+    cmd.args(launch_args(&params)?);
     
     // 直接打印 Command 对象，确保日志与实际执行的命令一致
     let cmd_str = format!("{:?}", cmd);
@@ -264,83 +473,99 @@ pub async fn start_android_emulator(id: String, app: tauri::AppHandle) -> Result
     
     println!("启动 Android 模拟器命令: {}", cmd_str);
     
+    let started = std::time::Instant::now();
+    let timeout = std::time::Duration::from_secs(boot_timeout_secs.unwrap_or(DEFAULT_BOOT_TIMEOUT_SECS));
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+
     let mut child = cmd.spawn()
         .map_err(|e| format!("Failed to start emulator: {}", e))?;
+    let stderr = crate::utils::capture_stderr(&mut child);
 
-    // Wait for initial startup and check for immediate errors
-    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-    
-    // Check if process crashed immediately
-    match child.try_wait() {
-        Ok(Some(status)) => {
-            let output = child.wait_with_output()
-                .map_err(|e| format!("Failed to read output: {}", e))?;
-            
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            
-            if !status.success() {
-                let error_msg = if !stderr.is_empty() {
-                    stderr.to_string()
-                } else if !stdout.is_empty() {
-                    stdout.to_string()
+    let mut serial: Option<String> = None;
+    let mut last_stage = None;
+
+    loop {
+        let elapsed = started.elapsed();
+        let stage = if serial.is_some() { BootStage::Booting } else { BootStage::WaitingForDevice };
+        if last_stage != Some(stage) {
+            last_stage = Some(stage);
+            BootProgress {
+                platform: Platform::Android,
+                id: id.clone(),
+                serial: serial.clone(),
+                stage,
+                elapsed_ms: elapsed.as_millis() as u64,
+            }
+            .emit(&app);
+        }
+
+        // 进程退出说明启动失败（例如同名 AVD 已在运行、镜像缺失）
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                // 给读取线程一点时间收集最后的输出
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                let output = stderr.lock().unwrap().trim().to_string();
+                return Err(if !output.is_empty() {
+                    output
                 } else {
                     format!("Emulator exited with status: {}", status)
-                };
-                return Err(error_msg);
+                });
             }
+            Ok(None) => {}
+            Err(e) => return Err(format!("Failed to check process status: {}", e)),
         }
-        Ok(None) => {
-            // Process is running, wait for emulator to be ready
-            for _ in 0..30 { // Wait up to 30 seconds
-                tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                
-                // Check if emulator is ready by listing running emulators
-                let adb_path = std::path::Path::new(&android_home)
-                    .join("platform-tools")
-                    .join(if cfg!(target_os = "windows") { "adb.exe" } else { "adb" });
-                
-                if let Ok(output) = new_command(&adb_path)
-                    .args(&["devices"])
-                    .output() {
-                    let devices_output = String::from_utf8_lossy(&output.stdout);
-                    if devices_output.contains(&id) || devices_output.lines().any(|line| line.contains("device") && !line.contains("List")) {
-                        break; // Emulator is ready
+
+        if elapsed > timeout {
+            // 超时后结束模拟器，避免留下无人管理的进程；qemu 可能不随启动器退出，先通过控制台关闭
+            if let Some(s) = &serial {
+                let _ = new_command(&adb_path).args(["-s", s, "emu", "kill"]).output();
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "Emulator '{}' did not finish booting within {} seconds",
+                id,
+                timeout.as_secs()
+            ));
+        }
+
+        match &serial {
+            None => {
+                serial = emulator_serials(&adb_path)
+                    .into_iter()
+                    .find(|s| avd_name_of(&adb_path, s).as_deref() == Some(id.as_str()));
+            }
+            Some(s) => {
+                if is_boot_completed(&adb_path, s) {
+                    let elapsed_ms = started.elapsed().as_millis() as u64;
+                    BootProgress {
+                        platform: Platform::Android,
+                        id: id.clone(),
+                        serial: Some(s.clone()),
+                        stage: BootStage::Completed,
+                        elapsed_ms,
                     }
-                }
-                
-                // Check if process is still running
-                if let Ok(Some(_)) = child.try_wait() {
-                    return Err("Emulator process terminated unexpectedly".to_string());
+                    .emit(&app);
+
+                    return Ok(BootResult {
+                        id,
+                        serial: s.clone(),
+                        boot_duration_ms: elapsed_ms,
+                    });
                 }
             }
         }
-        Err(e) => {
-            return Err(format!("Failed to check process status: {}", e));
-        }
-    }
 
-    Ok(())
+        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+    }
 }
 
 #[tauri::command]
 pub async fn stop_android_emulator(id: String) -> Result<(), String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
     // Check if force kill is enabled
     let force_kill = crate::commands::settings::get_android_force_kill();
     
-    let adb_exe = if cfg!(target_os = "windows") {
-        "adb.exe"
-    } else {
-        "adb"
-    };
-    
-    let adb_path = std::path::Path::new(&android_home)
-        .join("platform-tools")
-        .join(adb_exe);
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
     
     // 首先获取当前设备列表
     let devices_output = new_command(&adb_path)
@@ -605,6 +830,9 @@ pub async fn stop_android_emulator(id: String) -> Result<(), String> {
         println!("Normal shutdown successful");
     }
     
+    // 模拟器已关闭，结束其上的 logcat
+    logs::stop_device(&serial);
+    
     // Wait a moment for the emulator to shut down
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
     
@@ -613,21 +841,7 @@ pub async fn stop_android_emulator(id: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn delete_android_emulator(id: String) -> Result<(), String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
-    let avdmanager_exe = if cfg!(target_os = "windows") {
-        "avdmanager.bat"
-    } else {
-        "avdmanager"
-    };
-    
-    let avdmanager_path = std::path::Path::new(&android_home)
-        .join("cmdline-tools")
-        .join("latest")
-        .join("bin")
-        .join(avdmanager_exe);
+    let avdmanager_path = crate::toolchain::resolve(Tool::Avdmanager)?;
     
     let output = new_command(&avdmanager_path)
         .args(&["delete", "avd", "-n", &id])
@@ -652,10 +866,6 @@ pub async fn delete_android_emulator(id: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn wipe_android_data(id: String, app: tauri::AppHandle) -> Result<(), String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
     // Get AVD directory path
     let avd_home = std::env::var("ANDROID_AVD_HOME")
         .or_else(|_| std::env::var("HOME").map(|h| format!("{}/.android/avd", h)))
@@ -785,19 +995,7 @@ pub async fn wipe_android_data(id: String, app: tauri::AppHandle) -> Result<(),
 
 #[tauri::command]
 pub async fn screenshot_android(id: String) -> Result<String, String> {
-    // Get ANDROID_HOME from settings or environment
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())?;
-    
-    let adb_exe = if cfg!(target_os = "windows") {
-        "adb.exe"
-    } else {
-        "adb"
-    };
-    
-    let adb_path = std::path::Path::new(&android_home)
-        .join("platform-tools")
-        .join(adb_exe);
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
     
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
     let filename = format!("screenshot_{}_{}.png", id, timestamp);
@@ -822,35 +1020,222 @@ pub async fn screenshot_android(id: String) -> Result<String, String> {
     std::fs::write(&path, &output.stdout)
         .map_err(|e| format!("Failed to save screenshot: {}", e))?;
 
-    Ok(path.to_string_lossy().to_string())
+    let path = path.to_string_lossy().to_string();
+    captures::record(CaptureKind::Screenshot, Platform::Android, &id, &capture_device(&adb_path, &id), &path);
+    Ok(path)
+}
+
+/// `adb shell screenrecord` 录屏，单段最长 3 分钟
+struct AndroidScreenRecorder {
+    adb_path: std::path::PathBuf,
+    /// 当前段 screenrecord 在设备上的进程号
+    segment_pid: std::sync::Mutex<Option<u32>>,
+}
+
+/// 读取设备端 shell 输出的第一行进程号；逐字节读取，不会多读后续输出
+fn read_shell_pid(child: &mut std::process::Child) -> Option<u32> {
+    use std::io::Read;
+    let mut stdout = child.stdout.take()?;
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while stdout.read(&mut byte).ok()? == 1 && byte[0] != b'\n' {
+        line.push(byte[0]);
+    }
+    child.stdout = Some(stdout);
+    String::from_utf8_lossy(&line).trim().parse().ok()
+}
+
+impl ScreenRecorder for AndroidScreenRecorder {
+    fn platform(&self) -> Platform {
+        Platform::Android
+    }
+
+    fn max_segment_secs(&self) -> u64 {
+        180
+    }
+
+    fn remote_path(&self, name: &str) -> String {
+        format!("/sdcard/{}", name)
+    }
+
+    fn spawn(&self, device_id: &str, remote: &str, options: &RecordingOptions, secs: u64) -> Result<std::process::Child, String> {
+        // 设备端 shell 先输出自己的进程号再 exec 为 screenrecord，停止时只结束这一个进程
+        let mut script = format!("echo $$; exec screenrecord --time-limit {}", secs);
+        if let Some(bit_rate) = options.bit_rate {
+            script.push_str(&format!(" --bit-rate {}", bit_rate));
+        }
+        if let Some(size) = options.size.as_deref() {
+            script.push_str(&format!(" --size {}", size.trim()));
+        }
+        script.push(' ');
+        script.push_str(remote);
+
+        let mut child = new_command(&self.adb_path)
+            .args(["-s", device_id, "shell", &script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start screen recording: {}", e))?;
+        match read_shell_pid(&mut child) {
+            Some(pid) => {
+                *self.segment_pid.lock().unwrap() = Some(pid);
+                Ok(child)
+            }
+            None => {
+                let output = child.wait_with_output();
+                let stderr = output.map(|o| String::from_utf8_lossy(&o.stderr).trim().to_string());
+                Err(format!("Failed to start screen recording: {}", stderr.unwrap_or_default()))
+            }
+        }
+    }
+
+    fn interrupt(&self, device_id: &str, _child: &mut std::process::Child) {
+        // screenrecord 收到 SIGINT 后写完 MP4 再退出；只通知本段的进程，不影响其他录屏
+        let Some(pid) = self.segment_pid.lock().unwrap().take() else {
+            return;
+        };
+        let _ = new_command(&self.adb_path)
+            .args(["-s", device_id, "shell", "kill", "-INT", &pid.to_string()])
+            .output();
+    }
+
+    fn pull(&self, device_id: &str, remote: &str, local: &std::path::Path) -> Result<(), String> {
+        pull_file(device_id, remote, local)
+    }
+
+    fn remove(&self, device_id: &str, remote: &str) {
+        let _ = new_command(&self.adb_path)
+            .args(["-s", device_id, "shell", "rm", "-f", remote])
+            .output();
+    }
+
+    fn describe(&self, device_id: &str) -> CaptureDevice {
+        capture_device(&self.adb_path, device_id)
+    }
 }
 
+/// 开始录屏，超过 3 分钟自动分段；设置了总时长时到时自动结束并推送 `screen-recording-finished`
 #[tauri::command]
-pub async fn start_logcat(device_id: String, time_filter: Option<String>) -> Result<(), String> {
-    // 先停止之前的 logcat
-    stop_logcat().await?;
-    
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured".to_string())?;
-    
-    let adb_exe = if cfg!(target_os = "windows") {
-        "adb.exe"
-    } else {
-        "adb"
+pub async fn start_recording_android(id: String, options: Option<RecordingOptions>, app: tauri::AppHandle) -> Result<(), String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+    let recorder = AndroidScreenRecorder {
+        adb_path,
+        segment_pid: std::sync::Mutex::new(None),
     };
+    screenrecord::start(recorder, &id, options.unwrap_or_default(), app)
+}
+
+/// 停止录屏，返回拉取到截图目录中的视频文件
+#[tauri::command]
+pub async fn stop_recording_android(id: String) -> Result<RecordingResult, String> {
+    tokio::task::spawn_blocking(move || screenrecord::stop(&id))
+        .await
+        .map_err(|e| format!("Failed to stop screen recording: {}", e))?
+}
+
+async fn read_recent_logcat(id: &str, lines: usize) -> Result<Vec<String>, String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
     
-    let adb_path = std::path::Path::new(&android_home)
-        .join("platform-tools")
-        .join(adb_exe);
+    // -d 输出当前缓冲区后退出，-t 只取最后 N 行
+    let output = new_command(&adb_path)
+        .args(&["-s", id, "logcat", "-d", "-t", &lines.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to read logcat: {}", e))?;
     
-    // 清空缓冲区
-    {
-        let mut buffer = LOGCAT_BUFFER.lock().unwrap();
-        buffer.clear();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to read logcat: {}", stderr));
+    }
+    
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+pub struct AndroidBackend;
+
+impl EmulatorBackend for AndroidBackend {
+    fn platform(&self) -> Platform {
+        Platform::Android
+    }
+
+    async fn list(&self) -> Result<Vec<Device>, String> {
+        list_android_emulators().await
+    }
+
+    async fn start(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String> {
+        start_android_emulator(id.to_string(), None, app.clone()).await.map(|_| ())
+    }
+
+    async fn stop(&self, id: &str) -> Result<(), String> {
+        stop_android_emulator(id.to_string()).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), String> {
+        delete_android_emulator(id.to_string()).await
+    }
+
+    async fn wipe(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String> {
+        wipe_android_data(id.to_string(), app.clone()).await
+    }
+
+    async fn screenshot(&self, id: &str) -> Result<String, String> {
+        screenshot_android(id.to_string()).await
+    }
+
+    async fn logs(&self, id: &str, lines: usize) -> Result<Vec<String>, String> {
+        read_recent_logcat(id, lines).await
+    }
+
+    async fn install(
+        &self,
+        id: &str,
+        paths: &[String],
+        options: &InstallOptions,
+        app: &tauri::AppHandle,
+    ) -> Result<Vec<InstallOutcome>, String> {
+        install_packages(id, paths, options, app).await
+    }
+
+    async fn packages(&self, id: &str) -> Result<Vec<PackageInfo>, String> {
+        get_device_packages(id.to_string()).await
+    }
+
+    async fn package_details(&self, id: &str, package: &str) -> Result<PackageInfo, String> {
+        let (id, package) = (id.to_string(), package.to_string());
+        tokio::task::spawn_blocking(move || package_details(&id, &package))
+            .await
+            .map_err(|e| format!("Failed to read package info: {}", e))?
+    }
+
+    async fn package_action(&self, id: &str, package: &str, action: &PackageAction) -> Result<(), String> {
+        let (id, package, action) = (id.to_string(), package.to_string(), action.clone());
+        tokio::task::spawn_blocking(move || run_package_action(&id, &package, &action))
+            .await
+            .map_err(|e| format!("Package action failed: {}", e))?
+    }
+}
+
+#[tauri::command]
+pub async fn start_logcat(
+    device_id: String,
+    time_filter: Option<String>,
+    filter: Option<LogFilterOptions>,
+    crash: Option<CrashCaptureOptions>,
+    app: tauri::AppHandle,
+) -> Result<SessionId, String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+    let filter = Arc::new(LogFilter::new(&filter.unwrap_or_default())?);
+    let crash_capture = CrashCapture::new(crash, pull_file)?;
+    
+    // 按包名过滤时先查一次进程号，避免开头的日志被全部过滤
+    if let Some(package) = filter.package() {
+        filter.set_pids(package_pids(&adb_path, &device_id, package).await);
     }
     
     // 构建 logcat 命令参数
-    let mut args = vec!["-s".to_string(), device_id.clone(), "logcat".to_string(), "-v".to_string(), "color".to_string()];
+    let mut args = vec!["-s".to_string(), device_id.clone(), "logcat".to_string(), "-v".to_string(), "threadtime".to_string()];
     
     // 添加时间过滤参数
     // time_filter 格式：
@@ -864,7 +1249,8 @@ pub async fn start_logcat(device_id: String, time_filter: Option<String>) -> Res
                 let now = chrono::Local::now();
                 let since = now - chrono::Duration::minutes(minutes);
                 let time_str = since.format("%m-%d %H:%M:%S.000").to_string();
-                args.push("-t".to_string());
+                // -T 输出该时间之后的日志并继续跟踪（-t 会在输出后退出）
+                args.push("-T".to_string());
                 args.push(time_str);
             }
         } else if filter.starts_with("since:") {
@@ -874,7 +1260,7 @@ pub async fn start_logcat(device_id: String, time_filter: Option<String>) -> Res
                 if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(time_str) {
                     let local_dt = dt.with_timezone(&chrono::Local);
                     let time_str = local_dt.format("%m-%d %H:%M:%S.000").to_string();
-                    args.push("-t".to_string());
+                    args.push("-T".to_string());
                     args.push(time_str);
                 }
             }
@@ -895,79 +1281,74 @@ pub async fn start_logcat(device_id: String, time_filter: Option<String>) -> Res
         cmd.creation_flags(CREATE_NO_WINDOW);
     }
 
-    let mut child = cmd
+    let child = cmd
         .args(&args_refs)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| format!("Failed to start logcat: {}", e))?;
     
-    // 获取 stdout
-    let stdout = child.stdout.take()
-        .ok_or_else(|| "Failed to capture stdout".to_string())?;
-    
-    // 标记 logcat 正在运行
-    {
-        let mut running = LOGCAT_RUNNING.lock().unwrap();
-        *running = true;
-    }
-    
-    // 在后台线程中持续读取日志
-    tokio::spawn(async move {
-        let reader = TokioBufReader::new(stdout);
-        let mut lines = reader.lines();
-        
-        while let Ok(Some(line)) = lines.next_line().await {
-            // 检查是否应该停止
-            {
-                let running = LOGCAT_RUNNING.lock().unwrap();
-                if !*running {
-                    break;
-                }
-            }
-            
-            let mut buffer = LOGCAT_BUFFER.lock().unwrap();
-            
-            // 限制缓冲区大小
-            if buffer.len() >= 1000 {
-                buffer.remove(0);
+    // 会话持有 logcat 进程，日志通过 log-lines 事件批量推送
+    let session = logs::register(Platform::Android, &device_id, filter.clone());
+    let session_id = session.id;
+    logs::spawn_process(session.clone(), child, LogcatParser::new(filter.clone()), crash_capture, app)?;
+    
+    // 应用重启或子进程退出后进程号会变化，定时刷新
+    if let Some(package) = filter.package().map(str::to_string) {
+        tokio::spawn(async move {
+            while session.is_running() {
+                tokio::time::sleep(logs::PID_REFRESH_INTERVAL).await;
+                filter.set_pids(package_pids(&adb_path, &device_id, &package).await);
             }
-            
-            buffer.push(line);
-        }
-    });
+        });
+    }
     
+    Ok(session_id)
+}
+
+/// 从设备拉取文件（tombstone 等），非 root 设备上可能没有读取权限
+fn pull_file(serial: &str, remote: &str, local: &std::path::Path) -> Result<(), String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+    let output = new_command(&adb_path)
+        .args(["-s", serial, "pull", remote])
+        .arg(local)
+        .output()
+        .map_err(|e| format!("Failed to pull {}: {}", remote, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to pull {}: {}", remote, stderr.trim()));
+    }
     Ok(())
 }
 
-#[tauri::command]
-pub async fn get_logcat_logs(_device_id: String) -> Result<Vec<String>, String> {
-    let mut buffer = LOGCAT_BUFFER.lock().unwrap();
-    let logs = buffer.clone();
-    buffer.clear();
-    Ok(logs)
+/// 应用正在运行的全部进程（主进程及 `:remote` 等子进程）；应用未运行时为空。
+/// 两次刷新之间新启动的进程由 [`LogcatParser`] 从 ActivityManager 的启动日志中补充
+async fn package_pids(adb_path: &std::path::Path, serial: &str, package: &str) -> HashSet<u32> {
+    let mut cmd = tokio::process::Command::from(new_command(adb_path));
+    cmd.args(["-s", serial, "shell", "ps", "-A", "-o", "PID,NAME"]);
+    let Ok(output) = cmd.output().await else {
+        return HashSet::new();
+    };
+    logs::parse_package_pids(&String::from_utf8_lossy(&output.stdout), package)
 }
 
+/// 停止 logcat：指定 session_id 只停该会话，指定 device_id 停该设备的全部会话，都不指定则全部停止
 #[tauri::command]
-pub async fn stop_logcat() -> Result<(), String> {
-    // 标记停止
-    {
-        let mut running = LOGCAT_RUNNING.lock().unwrap();
-        *running = false;
-    }
-    
-    // 清空缓冲区
-    {
-        let mut buffer = LOGCAT_BUFFER.lock().unwrap();
-        buffer.clear();
+pub async fn stop_logcat(device_id: Option<String>, session_id: Option<SessionId>) -> Result<(), String> {
+    if let Some(id) = session_id {
+        logs::stop(id, StopReason::Stopped);
+        return Ok(());
     }
     
-    // 注意：由于我们使用 tokio::process::Command，进程会在任务结束时自动清理
-    // 我们通过设置 LOGCAT_RUNNING = false 来让读取循环退出
-    
+    logs::stop_where(StopReason::Stopped, |session| {
+        session.platform == Platform::Android
+            && device_id.as_deref().is_none_or(|d| session.device_id == d)
+    });
     Ok(())
 }
 
+
 #[tauri::command]
 pub async fn copy_image_to_clipboard(path: String) -> Result<(), String> {
     // 读取图片文件
@@ -1003,32 +1384,21 @@ pub async fn write_log_file(path: String, content: String) -> Result<(), String>
     Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PackageInfo {
-    pub name: String,
-    pub is_system: bool,
-}
-
 #[tauri::command]
 pub async fn get_device_packages(device_id: String) -> Result<Vec<PackageInfo>, String> {
-    let android_home = crate::commands::settings::get_android_home()
-        .ok_or_else(|| "Android SDK path not configured".to_string())?;
-    
-    let adb_exe = if cfg!(target_os = "windows") {
-        "adb.exe"
-    } else {
-        "adb"
-    };
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
     
-    let adb_path = std::path::Path::new(&android_home)
-        .join("platform-tools")
-        .join(adb_exe);
-    
-    // 获取所有包名
-    let output = new_command(&adb_path)
-        .args(&["-s", &device_id, "shell", "pm", "list", "packages"])
+    // 获取所有包名；-f 附带 APK 路径，-U 附带 uid（Android 8 以下不支持时退回无参数）
+    let mut output = new_command(&adb_path)
+        .args(["-s", &device_id, "shell", "pm", "list", "packages", "-f", "-U"])
         .output()
         .map_err(|e| format!("Failed to list packages: {}", e))?;
+    if !String::from_utf8_lossy(&output.stdout).contains("package:") {
+        output = new_command(&adb_path)
+            .args(["-s", &device_id, "shell", "pm", "list", "packages"])
+            .output()
+            .map_err(|e| format!("Failed to list packages: {}", e))?;
+    }
     
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1040,7 +1410,7 @@ pub async fn get_device_packages(device_id: String) -> Result<Vec<PackageInfo>,
     
     // 获取系统包列表
     let system_output = new_command(&adb_path)
-        .args(&["-s", &device_id, "shell", "pm", "list", "packages", "-s"])
+        .args(["-s", &device_id, "shell", "pm", "list", "packages", "-s"])
         .output()
         .map_err(|e| format!("Failed to list system packages: {}", e))?;
     
@@ -1055,14 +1425,9 @@ pub async fn get_device_packages(device_id: String) -> Result<Vec<PackageInfo>,
     
     // 解析所有包名
     for line in packages_output.lines() {
-        if let Some(package_name) = line.strip_prefix("package:") {
-            let package_name = package_name.trim().to_string();
-            let is_system = system_packages.contains(&package_name);
-            
-            packages.push(PackageInfo {
-                name: package_name,
-                is_system,
-            });
+        if let Some(mut package) = parse_package_line(line) {
+            package.is_system = system_packages.contains(&package.name);
+            packages.push(package);
         }
     }
     
@@ -1077,3 +1442,827 @@ pub async fn get_device_packages(device_id: String) -> Result<Vec<PackageInfo>,
     
     Ok(packages)
 }
+
+/// `package:/data/app/~~x/com.example-y/base.apk=com.example uid:10123`，-f / -U 部分可能不存在
+fn parse_package_line(line: &str) -> Option<PackageInfo> {
+    let rest = line.trim().strip_prefix("package:")?;
+    let (rest, uid) = match rest.split_once(" uid:") {
+        Some((rest, uid)) => (rest, uid.trim().parse().ok()),
+        None => (rest, None),
+    };
+    // 路径中可能含有 '='，包名不会
+    let (apk_path, name) = match rest.rsplit_once('=') {
+        Some((path, name)) => (Some(path.to_string()), name),
+        None => (None, rest),
+    };
+    let mut package = PackageInfo::new(name.trim(), false);
+    package.apk_path = apk_path;
+    package.uid = uid;
+    Some(package)
+}
+
+/// `dumpsys package <pkg>` 中该包自己的段落（排除 "Hidden system packages" 等重复段落）
+fn package_section<'a>(dump: &'a str, package: &str) -> Option<Vec<&'a str>> {
+    let header = format!("Package [{}]", package);
+    let mut lines = dump.lines().skip_while(|line| !line.trim_start().starts_with(&header));
+    let first = lines.next()?;
+    let indent = first.len() - first.trim_start().len();
+    Some(
+        lines
+            .take_while(|line| line.trim().is_empty() || line.len() - line.trim_start().len() > indent)
+            .collect(),
+    )
+}
+
+/// 段落内 `key=value` 字段的值及其后的内容，同一行可有多个字段
+fn dump_value<'a>(section: &[&'a str], key: &str) -> Option<&'a str> {
+    let prefix = format!("{}=", key);
+    section.iter().find_map(|line| {
+        let field = line.split_whitespace().find(|field| field.starts_with(&prefix))?;
+        let start = line.find(field)? + prefix.len();
+        Some(line[start..].trim())
+    })
+}
+
+/// 单个 `key=value` 字段，值到空格为止
+fn dump_field<'a>(section: &[&'a str], key: &str) -> Option<&'a str> {
+    dump_value(section, key)?.split_whitespace().next()
+}
+
+/// 段落中某个标题下缩进更深的行
+fn dump_block<'a>(section: &[&'a str], title: &str) -> Vec<&'a str> {
+    let mut result = Vec::new();
+    let mut iter = section.iter().skip_while(|line| line.trim() != title);
+    let Some(header) = iter.next() else {
+        return result;
+    };
+    let indent = header.len() - header.trim_start().len();
+    for line in iter {
+        if line.trim().is_empty() || line.len() - line.trim_start().len() <= indent {
+            break;
+        }
+        result.push(line.trim());
+    }
+    result
+}
+
+/// `dumpsys diskstats` 中应用的数据大小
+fn app_data_size(adb_path: &std::path::Path, serial: &str, package: &str) -> Option<u64> {
+    let output = new_command(adb_path)
+        .args(["-s", serial, "shell", "dumpsys", "diskstats"])
+        .output()
+        .ok()?;
+    parse_data_size(&String::from_utf8_lossy(&output.stdout), package)
+}
+
+/// diskstats 中包名和数据大小是两个按位置对应的 JSON 数组
+fn parse_data_size(diskstats: &str, package: &str) -> Option<u64> {
+    let array = |prefix: &str| -> Option<serde_json::Value> {
+        let line = diskstats.lines().find_map(|line| line.strip_prefix(prefix))?;
+        serde_json::from_str(line.trim()).ok()
+    };
+    let names = array("Package Names:")?;
+    let sizes = array("App Data Sizes:")?;
+    let index = names.as_array()?.iter().position(|n| n.as_str() == Some(package))?;
+    sizes.as_array()?.get(index)?.as_u64()
+}
+
+fn package_details(serial: &str, package: &str) -> Result<PackageInfo, String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+    let output = new_command(&adb_path)
+        .args(["-s", serial, "shell", "dumpsys", "package", package])
+        .output()
+        .map_err(|e| format!("Failed to read package info: {}", e))?;
+    let mut info = parse_package_details(&String::from_utf8_lossy(&output.stdout), package)
+        .ok_or_else(|| format!("Package {} not found", package))?;
+    info.data_size = app_data_size(&adb_path, serial, package);
+    Ok(info)
+}
+
+/// 解析 `dumpsys package <pkg>` 的输出，数据大小需另外读取
+fn parse_package_details(dump: &str, package: &str) -> Option<PackageInfo> {
+    let section = package_section(dump, package)?;
+
+    let mut info = PackageInfo::new(package, false);
+    // versionName 和时间可能含空格，取到行尾
+    info.version_name = dump_value(&section, "versionName").map(str::to_string);
+    info.version_code = dump_field(&section, "versionCode").and_then(|v| v.parse().ok());
+    info.min_sdk = dump_field(&section, "minSdk").and_then(|v| v.parse().ok());
+    info.target_sdk = dump_field(&section, "targetSdk").and_then(|v| v.parse().ok());
+    info.first_install_time = dump_value(&section, "firstInstallTime").map(str::to_string);
+    info.last_update_time = dump_value(&section, "lastUpdateTime").map(str::to_string);
+    info.uid = dump_field(&section, "userId").and_then(|v| v.parse().ok());
+    // codePath 是安装目录，base.apk 位于其中
+    info.apk_path = dump_field(&section, "codePath").map(|path| {
+        if path.ends_with(".apk") {
+            path.to_string()
+        } else {
+            format!("{}/base.apk", path)
+        }
+    });
+    // pkgFlags=[ SYSTEM HAS_CODE ALLOW_CLEAR_USER_DATA ]
+    info.is_system = dump_value(&section, "pkgFlags").is_some_and(|flags| flags.contains(" SYSTEM "));
+
+    // 新版本中权限名后可能带有 ": restricted=true"
+    info.requested_permissions = dump_block(&section, "requested permissions:")
+        .into_iter()
+        .map(|line| line.split(':').next().unwrap_or(line).trim().to_string())
+        .collect();
+    let mut granted: Vec<String> = ["install permissions:", "runtime permissions:"]
+        .iter()
+        .flat_map(|title| dump_block(&section, title))
+        .filter(|line| line.contains("granted=true"))
+        .map(|line| line.split(':').next().unwrap_or(line).trim().to_string())
+        .collect();
+    granted.sort();
+    granted.dedup();
+    info.granted_permissions = granted;
+    Some(info)
+}
+
+/// 常见安装失败码的说明
+fn describe_install_error(code: &str) -> Option<&'static str> {
+    let message = match code {
+        "INSTALL_FAILED_VERSION_DOWNGRADE" => "A newer version of this app is already installed. Allow downgrade or uninstall it first",
+        "INSTALL_FAILED_UPDATE_INCOMPATIBLE" | "INSTALL_PARSE_FAILED_INCONSISTENT_CERTIFICATES" => {
+            "The package is signed with a different key than the installed app. Uninstall the existing app first"
+        }
+        "INSTALL_PARSE_FAILED_NO_CERTIFICATES" => "The package is not signed",
+        "INSTALL_FAILED_ALREADY_EXISTS" => "The app is already installed. Enable reinstall to replace it",
+        "INSTALL_FAILED_INSUFFICIENT_STORAGE" => "Not enough storage on the device",
+        "INSTALL_FAILED_OLDER_SDK" => "The app requires a newer Android version than the device runs",
+        "INSTALL_FAILED_NO_MATCHING_ABIS" => "The app has no native libraries for the device CPU architecture",
+        "INSTALL_FAILED_MISSING_SPLIT" => "A required split APK is missing. Install all splits of the app together",
+        "INSTALL_FAILED_INVALID_APK" | "INSTALL_PARSE_FAILED_NOT_APK" => "The file is not a valid APK",
+        "INSTALL_FAILED_DUPLICATE_PERMISSION" => "The app declares a permission already defined by another installed app",
+        "INSTALL_FAILED_CONFLICTING_PROVIDER" => "A content provider authority conflicts with another installed app",
+        "INSTALL_FAILED_SHARED_USER_INCOMPATIBLE" => "The app's sharedUserId is incompatible with an installed app",
+        "INSTALL_FAILED_USER_RESTRICTED" => "Installation was blocked on the device. Check \"Install via USB\" in developer options",
+        "INSTALL_FAILED_VERIFICATION_FAILURE" => "Package verification failed on the device",
+        "INSTALL_FAILED_TEST_ONLY" => "The app is a test-only build. Enable \"allow test packages\" to install it",
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// 从 adb install 输出中取出失败码和可读说明
+fn install_error(output: &str) -> (Option<String>, String) {
+    let code = regex::Regex::new(r"INSTALL_[A-Z_]+")
+        .ok()
+        .and_then(|re| re.find(output).map(|m| m.as_str().to_string()));
+    let raw = output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| line.contains("Failure") || line.starts_with("adb: "))
+        .or_else(|| output.lines().map(str::trim).rfind(|line| !line.is_empty()))
+        .unwrap_or("Installation failed")
+        .to_string();
+    let message = match code.as_deref().and_then(describe_install_error) {
+        Some(description) => format!("{} ({})", description, raw),
+        None => raw,
+    };
+    (code, message)
+}
+
+/// 挑选 APK 集中的拆分包所需的设备配置
+#[derive(Debug, Default)]
+struct DeviceSpec {
+    /// 设备支持的 ABI，按偏好排序，写法同拆分包名（`arm64_v8a`）
+    abis: Vec<String>,
+    /// 屏幕密度 (dpi)
+    density: Option<u32>,
+    /// 系统语言，如 `zh`
+    language: Option<String>,
+}
+
+impl DeviceSpec {
+    fn read(adb_path: &std::path::Path, serial: &str) -> Self {
+        let abis = getprop(adb_path, serial, "ro.product.cpu.abilist")
+            .unwrap_or_default()
+            .split(',')
+            .map(|abi| abi.trim().replace('-', "_"))
+            .filter(|abi| !abi.is_empty())
+            .collect();
+
+        // `wm density` 输出 Physical density，修改过时还有 Override density，以后者为准
+        let density = new_command(adb_path)
+            .args(["-s", serial, "shell", "wm", "density"])
+            .output()
+            .ok()
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .filter_map(|(_, value)| value.trim().parse().ok())
+                    .next_back()
+            })
+            .or_else(|| getprop(adb_path, serial, "ro.sf.lcd_density").and_then(|v| v.parse().ok()));
+
+        let language = ["persist.sys.locale", "ro.product.locale"]
+            .iter()
+            .filter_map(|prop| getprop(adb_path, serial, prop))
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| locale.split(['-', '_']).next().map(str::to_lowercase));
+
+        Self { abis, density, language }
+    }
+}
+
+const ABI_SPLITS: [&str; 7] = ["arm64_v8a", "armeabi_v7a", "armeabi", "x86_64", "x86", "mips64", "mips"];
+const DENSITY_SPLITS: [(&str, u32); 7] = [
+    ("ldpi", 120),
+    ("mdpi", 160),
+    ("tvdpi", 213),
+    ("hdpi", 240),
+    ("xhdpi", 320),
+    ("xxhdpi", 480),
+    ("xxxhdpi", 640),
+];
+
+/// 按 bundletool 的规则挑选 `splits/` 下要安装的拆分包：每个安装时交付的模块安装 master，
+/// 以及与设备最匹配的一个 ABI、一个屏幕密度和设备语言的拆分包；其他维度（如纹理格式）的拆分包跳过。
+/// `install_time` 判断模块的 master 拆分包是否在安装时交付
+fn select_splits(names: &[String], device: &DeviceSpec, install_time: impl Fn(&str) -> bool) -> Vec<String> {
+    // 模块名 -> 该模块的拆分包后缀，如 base -> [master, arm64_v8a, xxhdpi, zh]
+    let mut modules: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for name in names {
+        let Some(stem) = name.strip_prefix("splits/").and_then(|n| n.strip_suffix(".apk")) else {
+            continue;
+        };
+        if let Some((module, suffix)) = stem.split_once('-') {
+            modules.entry(module).or_default().push(suffix);
+        }
+    }
+
+    let mut selected = Vec::new();
+    // base 放在最前面
+    let order = modules
+        .keys()
+        .copied()
+        .filter(|m| *m == "base")
+        .chain(modules.keys().copied().filter(|m| *m != "base"))
+        .collect::<Vec<_>>();
+    for module in order {
+        let suffixes = &modules[module];
+        let master = format!("splits/{}-master.apk", module);
+        if !suffixes.contains(&"master") || !install_time(&master) {
+            continue;
+        }
+        selected.push(master);
+
+        // ABI：设备最优先支持的那个；不知道设备 ABI 时按常见程度
+        let abis: Vec<&str> = suffixes.iter().copied().filter(|s| ABI_SPLITS.contains(s)).collect();
+        let abi = if device.abis.is_empty() {
+            ABI_SPLITS.iter().copied().find(|abi| abis.contains(abi))
+        } else {
+            device.abis.iter().map(String::as_str).find(|abi| abis.contains(abi))
+        };
+
+        // 屏幕密度：不低于设备密度的最小一档，都低于时取最高一档
+        let densities: Vec<(&str, u32)> = DENSITY_SPLITS
+            .iter()
+            .copied()
+            .filter(|(name, _)| suffixes.contains(name))
+            .collect();
+        let density = device
+            .density
+            .and_then(|dpi| densities.iter().find(|(_, d)| *d >= dpi))
+            .or(densities.last())
+            .map(|(name, _)| *name);
+
+        // 语言拆分包以语言代码命名，只安装设备当前语言的
+        let language = device
+            .language
+            .as_deref()
+            .filter(|lang| suffixes.contains(lang) && !ABI_SPLITS.contains(lang));
+
+        for suffix in [abi, density, language].into_iter().flatten() {
+            selected.push(format!("splits/{}-{}.apk", module, suffix));
+        }
+    }
+    selected
+}
+
+/// 从 bundletool 生成的 .apks 中解压适合该设备的拆分包
+fn extract_apk_set(path: &str, device: &DeviceSpec, dir: &std::path::Path) -> Result<Vec<std::path::PathBuf>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    // 按需交付的模块；读不出交付方式时按安装时交付处理
+    let mut deferred = HashSet::new();
+    for name in names.iter().filter(|n| n.starts_with("splits/") && n.ends_with("-master.apk")) {
+        let mut data = Vec::new();
+        if let Ok(mut entry) = archive.by_name(name) {
+            let _ = std::io::Read::read_to_end(&mut entry, &mut data);
+        }
+        if crate::inspect::is_install_time_split(&data) == Ok(false) {
+            deferred.insert(name.clone());
+        }
+    }
+
+    let mut selected = select_splits(&names, device, |master| !deferred.contains(master));
+    // `--mode=universal` 生成的包只有一个 universal.apk
+    if selected.is_empty() && names.iter().any(|name| name == "universal.apk") {
+        selected.push("universal.apk".to_string());
+    }
+    if selected.is_empty() {
+        return Err(format!("No installable APKs found in {}", path));
+    }
+
+    let mut files = Vec::with_capacity(selected.len());
+    for (index, name) in selected.iter().enumerate() {
+        let mut entry = archive
+            .by_name(name)
+            .map_err(|e| format!("Failed to read {} from {}: {}", name, path, e))?;
+        let local = dir.join(format!("{:03}.apk", index));
+        let mut out = std::fs::File::create(&local).map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        files.push(local);
+    }
+    Ok(files)
+}
+
+/// 逐个安装单元执行 `adb install` / `adb install-multiple`
+async fn install_packages(
+    serial: &str,
+    paths: &[String],
+    options: &InstallOptions,
+    app: &tauri::AppHandle,
+) -> Result<Vec<InstallOutcome>, String> {
+    if let Some(path) = paths.iter().find(|p| !has_extension(p, &["apk", "apks"])) {
+        return Err(format!("Unsupported package for Android: {}", path));
+    }
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+
+    let (apks, sets): (Vec<String>, Vec<String>) = paths.iter().cloned().partition(|p| has_extension(p, &["apk"]));
+    let mut units: Vec<Vec<String>> = if options.same_app && !apks.is_empty() {
+        vec![apks]
+    } else {
+        apks.into_iter().map(|p| vec![p]).collect()
+    };
+    units.extend(sets.into_iter().map(|p| vec![p]));
+
+    // 只有 .apks 需要按设备配置挑选拆分包
+    let device = if units.iter().any(|unit| has_extension(&unit[0], &["apks"])) {
+        DeviceSpec::read(&adb_path, serial)
+    } else {
+        DeviceSpec::default()
+    };
+
+    let total = units.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (index, unit) in units.into_iter().enumerate() {
+        let progress = |stage, message: Option<String>| {
+            InstallProgress {
+                platform: Platform::Android,
+                device_id: serial.to_string(),
+                paths: unit.clone(),
+                index,
+                total,
+                stage,
+                message,
+            }
+            .emit(app)
+        };
+        progress(InstallStage::Preparing, None);
+
+        // .apks 先解压到临时目录
+        let temp = match TempDir::new() {
+            Ok(temp) => temp,
+            Err(e) => {
+                progress(InstallStage::Failed, Some(e.clone()));
+                outcomes.push(InstallOutcome::failed(&unit, None, e));
+                continue;
+            }
+        };
+        let files: Vec<std::path::PathBuf> = if has_extension(&unit[0], &["apks"]) {
+            match extract_apk_set(&unit[0], &device, &temp.0) {
+                Ok(files) => files,
+                Err(e) => {
+                    progress(InstallStage::Failed, Some(e.clone()));
+                    outcomes.push(InstallOutcome::failed(&unit, None, e));
+                    continue;
+                }
+            }
+        } else {
+            unit.iter().map(std::path::PathBuf::from).collect()
+        };
+
+        let mut cmd = new_command(&adb_path);
+        cmd.args(["-s", serial, if files.len() > 1 { "install-multiple" } else { "install" }]);
+        if options.allow_test_packages {
+            cmd.arg("-t");
+        }
+        if options.reinstall {
+            cmd.arg("-r");
+        }
+        if options.allow_downgrade {
+            cmd.arg("-d");
+        }
+        if options.grant_permissions {
+            cmd.arg("-g");
+        }
+        cmd.args(&files);
+
+        progress(InstallStage::Installing, None);
+        let result = run_streaming(cmd, |line| progress(InstallStage::Installing, Some(line.to_string()))).await;
+        let outcome = match result {
+            Ok((true, output)) if output.lines().any(|line| line.trim() == "Success") => InstallOutcome {
+                paths: unit.clone(),
+                success: true,
+                code: None,
+                error: None,
+            },
+            Ok((_, output)) => {
+                let (code, message) = install_error(&output);
+                InstallOutcome::failed(&unit, code, message)
+            }
+            Err(e) => InstallOutcome::failed(&unit, None, e),
+        };
+        match &outcome.error {
+            None => progress(InstallStage::Completed, None),
+            Some(e) => progress(InstallStage::Failed, Some(e.clone())),
+        }
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+/// pm / am 出错时退出码不一定非零，按输出判断
+fn pm_failed(output: &str) -> bool {
+    output.contains("Exception") || output.contains("Error:") || output.contains("Failure")
+}
+
+/// 应用的启动 Activity，形如 `com.example/.MainActivity`
+fn launcher_activity(adb_path: &std::path::Path, serial: &str, package: &str) -> Option<String> {
+    let output = new_command(adb_path)
+        .args(["-s", serial, "shell", "cmd", "package", "resolve-activity", "--brief"])
+        .args(["-a", "android.intent.action.MAIN", "-c", "android.intent.category.LAUNCHER", package])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // 第一行是优先级等信息，最后一行为组件名；找不到时输出 "No activity found"
+    stdout
+        .lines()
+        .map(str::trim)
+        .rfind(|line| line.contains('/'))
+        .map(str::to_string)
+}
+
+fn run_package_action(serial: &str, package: &str, action: &PackageAction) -> Result<(), String> {
+    let adb_path = crate::toolchain::resolve(Tool::Adb)?;
+    let shell = |args: &[&str]| {
+        let mut cmd = new_command(&adb_path);
+        cmd.args(["-s", serial, "shell"]).args(args);
+        cmd
+    };
+
+    match action {
+        PackageAction::Uninstall { keep_data } => {
+            let mut args = vec!["pm", "uninstall"];
+            if *keep_data {
+                args.push("-k");
+            }
+            args.push(package);
+            run_checked(shell(&args), "uninstall package", |out| out.contains("Success"))?;
+        }
+        PackageAction::ClearData => {
+            run_checked(shell(&["pm", "clear", package]), "clear app data", |out| out.contains("Success"))?;
+        }
+        PackageAction::ForceStop => {
+            run_checked(shell(&["am", "force-stop", package]), "force stop app", |out| !pm_failed(out))?;
+        }
+        PackageAction::Launch => match launcher_activity(&adb_path, serial, package) {
+            Some(activity) => {
+                run_checked(shell(&["am", "start", "-n", &activity]), "launch app", |out| !pm_failed(out))?;
+            }
+            // 旧系统没有 resolve-activity，用 monkey 发送一次启动事件
+            None => {
+                run_checked(
+                    shell(&["monkey", "-p", package, "-c", "android.intent.category.LAUNCHER", "1"]),
+                    "launch app",
+                    |out| !out.contains("No activities found") && !out.contains("monkey aborted"),
+                )?;
+            }
+        },
+        PackageAction::Grant { permission } => {
+            run_checked(shell(&["pm", "grant", package, permission]), "grant permission", |out| !pm_failed(out))?;
+        }
+        PackageAction::Revoke { permission } => {
+            run_checked(shell(&["pm", "revoke", package, permission]), "revoke permission", |out| !pm_failed(out))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(edit: impl FnOnce(&mut EmulatorLaunchParams)) -> EmulatorLaunchParams {
+        let mut params = EmulatorLaunchParams {
+            gpu: String::new(),
+            ..Default::default()
+        };
+        edit(&mut params);
+        params
+    }
+
+    #[test]
+    fn launch_args_accept_valid_values() {
+        let cases: Vec<(EmulatorLaunchParams, Vec<&str>)> = vec![
+            (params(|_| {}), vec![]),
+            (EmulatorLaunchParams::default(), vec!["-gpu", "auto"]),
+            (params(|p| p.cores = Some(4)), vec!["-cores", "4"]),
+            (params(|p| p.port = Some(5560)), vec!["-port", "5560"]),
+            (params(|p| p.netspeed = "lte".into()), vec!["-netspeed", "lte"]),
+            (params(|p| p.netspeed = "14400:28800".into()), vec!["-netspeed", "14400:28800"]),
+            (params(|p| p.netdelay = " 100:200 ".into()), vec!["-netdelay", "100:200"]),
+            (params(|p| p.netdelay = "none".into()), vec!["-netdelay", "none"]),
+            (params(|p| p.camera_back = "webcam0".into()), vec!["-camera-back", "webcam0"]),
+            (params(|p| p.camera_back = "virtualscene".into()), vec!["-camera-back", "virtualscene"]),
+            (
+                params(|p| p.features = vec!["Vulkan".into(), " ".into(), "-GLDirectMem".into()]),
+                vec!["-feature", "Vulkan,-GLDirectMem"],
+            ),
+            (params(|p| p.dns_server = "8.8.8.8, 2001:4860:4860::8888".into()), vec!["-dns-server", "8.8.8.8, 2001:4860:4860::8888"]),
+            (params(|p| p.dns_server = "dns.google,router.local.".into()), vec!["-dns-server", "dns.google,router.local."]),
+            (
+                params(|p| {
+                    p.gps_longitude = "121.47".into();
+                    p.gps_latitude = "31.23".into();
+                }),
+                vec!["-gps", "121.47,31.23"],
+            ),
+            (
+                params(|p| p.extra_args = r#"-qemu -append "console=ttyS0 debug" -prop 'a=b c'"#.into()),
+                vec!["-qemu", "-append", "console=ttyS0 debug", "-prop", "a=b c"],
+            ),
+        ];
+        for (params, expected) in cases {
+            assert_eq!(launch_args(&params).unwrap(), expected, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn launch_args_reject_invalid_values() {
+        let cases: Vec<(EmulatorLaunchParams, &str)> = vec![
+            (params(|p| p.cores = Some(0)), "CPU cores"),
+            (params(|p| p.port = Some(5555)), "Console port"),
+            (params(|p| p.port = Some(5684)), "Console port"),
+            (params(|p| p.port = Some(5552)), "Console port"),
+            (params(|p| p.netspeed = "5g".into()), "Invalid network speed"),
+            (params(|p| p.netspeed = "1:2:3".into()), "Invalid network speed"),
+            (params(|p| p.netdelay = "-1".into()), "Invalid network delay"),
+            (params(|p| p.netdelay = "lte".into()), "Invalid network delay"),
+            (params(|p| p.camera_back = "webcam".into()), "Invalid back camera"),
+            (params(|p| p.camera_back = "webcamX".into()), "Invalid back camera"),
+            (params(|p| p.features = vec!["Vulkan;rm".into()]), "Invalid feature name"),
+            (params(|p| p.dns_server = "8.8.8.8,".into()), "Invalid DNS server address"),
+            (params(|p| p.dns_server = "-bad.example".into()), "Invalid DNS server address"),
+            (params(|p| p.dns_server = "a b".into()), "Invalid DNS server address"),
+            (params(|p| p.gps_longitude = "121.47".into()), "Both GPS"),
+            (params(|p| p.gpu = "metal".into()), "Unknown GPU mode"),
+            (params(|p| p.memory = Some(64)), "Memory"),
+            (params(|p| p.extra_args = r#"-prop "a=b"#.into()), "Unterminated quote"),
+        ];
+        for (params, expected) in cases {
+            let error = launch_args(&params).unwrap_err();
+            assert!(error.starts_with(expected), "{:?}: {}", params, error);
+        }
+    }
+
+    #[test]
+    fn numeric_ranges() {
+        for (value, expected) in [("100", true), ("100:200", true), ("", false), ("1:2:3", false), ("a:1", false), ("1:", false)] {
+            assert_eq!(is_numeric_range(value), expected, "{}", value);
+        }
+    }
+
+    fn apk_set() -> Vec<String> {
+        [
+            "toc.pb",
+            "splits/base-master.apk",
+            "splits/base-arm64_v8a.apk",
+            "splits/base-armeabi_v7a.apk",
+            "splits/base-x86_64.apk",
+            "splits/base-mdpi.apk",
+            "splits/base-hdpi.apk",
+            "splits/base-xhdpi.apk",
+            "splits/base-xxhdpi.apk",
+            "splits/base-xxxhdpi.apk",
+            "splits/base-en.apk",
+            "splits/base-zh.apk",
+            "splits/base-astc.apk",
+            "splits/camera-master.apk",
+            "splits/camera-arm64_v8a.apk",
+            "splits/camera-x86_64.apk",
+            "splits/camera-zh.apk",
+            "splits/ondemand-master.apk",
+            "splits/ondemand-arm64_v8a.apk",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn device(abis: &[&str], density: Option<u32>, language: Option<&str>) -> DeviceSpec {
+        DeviceSpec {
+            abis: abis.iter().map(|s| s.to_string()).collect(),
+            density,
+            language: language.map(str::to_string),
+        }
+    }
+
+    fn install_time(master: &str) -> bool {
+        master != "splits/ondemand-master.apk"
+    }
+
+    #[test]
+    fn selects_best_matching_splits_per_module() {
+        let selected = select_splits(&apk_set(), &device(&["arm64_v8a", "armeabi_v7a"], Some(420), Some("zh")), install_time);
+        assert_eq!(
+            selected,
+            [
+                "splits/base-master.apk",
+                "splits/base-arm64_v8a.apk",
+                "splits/base-xxhdpi.apk",
+                "splits/base-zh.apk",
+                "splits/camera-master.apk",
+                "splits/camera-arm64_v8a.apk",
+                "splits/camera-zh.apk",
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_when_device_has_no_exact_match() {
+        // x86 模拟器只有 x86_64 拆分包可用；密度高于所有档位时取最高档；没有对应语言包时不装
+        let selected = select_splits(&apk_set(), &device(&["x86", "x86_64"], Some(700), Some("fr")), install_time);
+        assert_eq!(
+            selected,
+            [
+                "splits/base-master.apk",
+                "splits/base-x86_64.apk",
+                "splits/base-xxxhdpi.apk",
+                "splits/camera-master.apk",
+                "splits/camera-x86_64.apk",
+            ]
+        );
+
+        let unknown = select_splits(&apk_set(), &DeviceSpec::default(), install_time);
+        assert_eq!(unknown[..3], ["splits/base-master.apk", "splits/base-arm64_v8a.apk", "splits/base-xxxhdpi.apk"]);
+    }
+
+    #[test]
+    fn density_prefers_next_higher_bucket() {
+        for (dpi, expected) in [(120, "mdpi"), (160, "mdpi"), (240, "hdpi"), (320, "xhdpi"), (400, "xxhdpi"), (560, "xxxhdpi")] {
+            let selected = select_splits(&apk_set(), &device(&["arm64_v8a"], Some(dpi), None), install_time);
+            assert_eq!(selected[2], format!("splits/base-{}.apk", expected), "{} dpi", dpi);
+        }
+    }
+
+    #[test]
+    fn ignores_modules_without_master() {
+        let names = vec!["splits/base-master.apk".to_string(), "splits/orphan-arm64_v8a.apk".to_string()];
+        assert_eq!(select_splits(&names, &DeviceSpec::default(), |_| true), ["splits/base-master.apk"]);
+    }
+
+    const DUMPSYS_PACKAGE: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/dumpsys_package.txt"));
+    const DUMPSYS_DISKSTATS: &str =
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/dumpsys_diskstats.txt"));
+
+    #[test]
+    fn parses_package_details_from_dumpsys() {
+        let info = parse_package_details(DUMPSYS_PACKAGE, "com.example.demo").unwrap();
+        assert_eq!(info.name, "com.example.demo");
+        assert!(!info.is_system);
+        assert_eq!(info.version_name.as_deref(), Some("2.1.0 (beta)"));
+        assert_eq!(info.version_code, Some(42));
+        assert_eq!((info.min_sdk, info.target_sdk), (Some(24), Some(34)));
+        assert_eq!(info.first_install_time.as_deref(), Some("2024-02-28 18:03:11"));
+        assert_eq!(info.last_update_time.as_deref(), Some("2024-03-01 09:15:43"));
+        assert_eq!(info.uid, Some(10187));
+        assert_eq!(
+            info.apk_path.as_deref(),
+            Some("/data/app/~~Yc2kP0w1bQ==/com.example.demo-Tf9aJ3xW8lQ==/base.apk")
+        );
+        assert_eq!(
+            info.requested_permissions,
+            [
+                "android.permission.INTERNET",
+                "android.permission.CAMERA",
+                "android.permission.ACCESS_FINE_LOCATION",
+                "android.permission.POST_NOTIFICATIONS",
+            ]
+        );
+        assert_eq!(info.granted_permissions, ["android.permission.CAMERA", "android.permission.INTERNET"]);
+    }
+
+    #[test]
+    fn reads_only_the_requested_package_section() {
+        // 包名是另一个包的前缀，且后面有同名的 Hidden system packages 段落
+        let helper = parse_package_details(DUMPSYS_PACKAGE, "com.example.demo.helper").unwrap();
+        assert!(helper.is_system);
+        assert_eq!(helper.version_code, Some(3));
+        assert_eq!(helper.apk_path.as_deref(), Some("/data/app/com.example.demo.helper-1/base.apk"));
+        assert!(helper.requested_permissions.is_empty());
+
+        assert!(parse_package_details(DUMPSYS_PACKAGE, "com.example.missing").is_none());
+    }
+
+    #[test]
+    fn parses_data_size_from_diskstats() {
+        assert_eq!(parse_data_size(DUMPSYS_DISKSTATS, "com.example.demo"), Some(4_194_304));
+        assert_eq!(parse_data_size(DUMPSYS_DISKSTATS, "com.example.missing"), None);
+        assert_eq!(parse_data_size("", "com.example.demo"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_shell_pid_before_exec() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "echo $$; exec echo recording"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let pid = read_shell_pid(&mut child);
+        assert_eq!(pid, Some(child.id()));
+        // 后续输出仍留在管道中
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "recording\n");
+
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "echo 'not found' >&2; exit 1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        assert_eq!(read_shell_pid(&mut child), None);
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn describes_known_install_errors() {
+        let cases = [
+            ("INSTALL_FAILED_VERSION_DOWNGRADE", Some("A newer version of this app is already installed")),
+            ("INSTALL_FAILED_UPDATE_INCOMPATIBLE", Some("The package is signed with a different key")),
+            ("INSTALL_PARSE_FAILED_INCONSISTENT_CERTIFICATES", Some("The package is signed with a different key")),
+            ("INSTALL_FAILED_TEST_ONLY", Some("The app is a test-only build")),
+            ("INSTALL_FAILED_NO_MATCHING_ABIS", Some("The app has no native libraries")),
+            ("INSTALL_FAILED_ABORTED", None),
+            ("", None),
+        ];
+        for (code, expected) in cases {
+            let actual = describe_install_error(code);
+            assert_eq!(actual.is_some(), expected.is_some(), "{}", code);
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                assert!(actual.starts_with(expected), "{}: {}", code, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn parses_adb_install_failures() {
+        let cases = [
+            (
+                "Performing Streamed Install\n\
+                 adb: failed to install app-debug.apk: Failure [INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected: Update version code 1 is older than current 2]\n",
+                Some("INSTALL_FAILED_VERSION_DOWNGRADE"),
+                "A newer version of this app is already installed. Allow downgrade or uninstall it first \
+                 (adb: failed to install app-debug.apk: Failure [INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected: Update version code 1 is older than current 2])",
+            ),
+            (
+                "Performing Push Install\n\
+                 app-debug.apk: 1 file pushed, 0 skipped. 98.2 MB/s (2519164 bytes in 0.024s)\n\
+                 \tpkg: /data/local/tmp/app-debug.apk\n\
+                 Failure [INSTALL_FAILED_ALREADY_EXISTS]\n",
+                Some("INSTALL_FAILED_ALREADY_EXISTS"),
+                "The app is already installed. Enable reinstall to replace it (Failure [INSTALL_FAILED_ALREADY_EXISTS])",
+            ),
+            (
+                "Performing Streamed Install\n\
+                 adb: failed to install app-debug.apk: Failure [INSTALL_FAILED_ABORTED: User rejected permissions]\n",
+                Some("INSTALL_FAILED_ABORTED"),
+                "adb: failed to install app-debug.apk: Failure [INSTALL_FAILED_ABORTED: User rejected permissions]",
+            ),
+            (
+                "adb: failed to install app-debug.apk: cmd: Can't find service: package\n",
+                None,
+                "adb: failed to install app-debug.apk: cmd: Can't find service: package",
+            ),
+            (
+                "adb: device 'emulator-5556' not found\n",
+                None,
+                "adb: device 'emulator-5556' not found",
+            ),
+            ("\n", None, "Installation failed"),
+        ];
+        for (output, code, message) in cases {
+            let (actual_code, actual_message) = install_error(output);
+            assert_eq!(actual_code.as_deref(), code, "{}", output);
+            assert_eq!(actual_message, message, "{}", output);
+        }
+    }
+}
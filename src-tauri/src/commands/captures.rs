@@ -0,0 +1,115 @@
+//! 截图与录屏历史
+//!
+//! 每次截图和录屏完成后都会写入数据库，记录设备、型号、系统版本和分辨率，供前端检索。
+
+use crate::db::captures::{Capture, CaptureKind, CaptureQuery, NewCapture};
+use crate::db::with_database;
+use super::device::Platform;
+
+/// 截图 / 录屏时采集的设备信息，取不到的字段为空
+#[derive(Debug, Clone, Default)]
+pub struct CaptureDevice {
+    pub model: Option<String>,
+    pub os_version: Option<String>,
+    /// 屏幕分辨率，截图时以图片尺寸为准
+    pub screen_size: Option<(u32, u32)>,
+}
+
+fn platform_key(platform: Platform) -> &'static str {
+    match platform {
+        Platform::Android => "android",
+        Platform::Ios => "ios",
+        Platform::Harmony => "harmony",
+    }
+}
+
+/// 记录一次截图或录屏；写入失败不影响截图本身
+pub fn record(kind: CaptureKind, platform: Platform, device_id: &str, device: &CaptureDevice, path: &str) {
+    let resolution = match kind {
+        CaptureKind::Screenshot => image::image_dimensions(path).ok().or(device.screen_size),
+        CaptureKind::Recording => device.screen_size,
+    };
+    let capture = NewCapture {
+        kind,
+        platform: platform_key(platform),
+        device_id,
+        device_model: device.model.as_deref().filter(|m| !m.is_empty()),
+        os_version: device.os_version.as_deref().filter(|v| !v.is_empty()),
+        resolution,
+        path,
+    };
+    if let Err(e) = with_database(|db| db.insert_capture(&capture)) {
+        crate::utils::app_log::error(format!("Failed to record capture {}: {}", path, e));
+    }
+}
+
+fn find_capture(id: i64) -> Result<Capture, String> {
+    with_database(|db| db.get_capture(id))?.ok_or_else(|| format!("Capture {} not found", id))
+}
+
+/// 按条件检索历史记录，最新的在前
+#[tauri::command]
+pub async fn list_captures(query: Option<CaptureQuery>) -> Result<Vec<Capture>, String> {
+    let query = query.unwrap_or_default();
+    with_database(|db| db.list_captures(&query))
+}
+
+/// 设置记录的标签，覆盖原有标签
+#[tauri::command]
+pub async fn tag_capture(id: i64, tags: Vec<String>) -> Result<Capture, String> {
+    find_capture(id)?;
+    with_database(|db| db.set_capture_tags(id, &tags))?;
+    find_capture(id)
+}
+
+/// 删除记录，`delete_file` 为 true 时同时删除文件
+#[tauri::command]
+pub async fn delete_capture(id: i64, delete_file: Option<bool>) -> Result<(), String> {
+    let capture = find_capture(id)?;
+    if delete_file.unwrap_or(false) {
+        match std::fs::remove_file(&capture.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to delete {}: {}", capture.path, e)),
+        }
+    }
+    with_database(|db| db.delete_capture(id))
+}
+
+/// 在文件管理器中显示文件
+#[tauri::command]
+pub async fn reveal_capture(id: i64) -> Result<(), String> {
+    let capture = find_capture(id)?;
+    let path = std::path::Path::new(&capture.path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", capture.path));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .arg(format!("/select,{}", capture.path))
+            .spawn()
+            .map_err(|e| format!("Failed to reveal file: {}", e))?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("-R")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("Failed to reveal file: {}", e))?;
+    }
+
+    // 大多数 Linux 文件管理器不支持选中文件，打开所在目录
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path.parent().unwrap_or(path))
+            .spawn()
+            .map_err(|e| format!("Failed to reveal file: {}", e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+//! 三个平台共用的设备模型与统一命令
+//!
+//! 每个平台实现一次 [`EmulatorBackend`]，前端通过 `platform` 参数调用下面的通用命令，
+//! 新能力只需在 trait 中加一个方法即可覆盖所有平台。
+
+use serde::{Deserialize, Serialize};
+
+use super::android::AndroidBackend;
+use super::harmony::HarmonyBackend;
+use super::ios::IosBackend;
+use super::packages::{InstallOptions, InstallOutcome, PackageAction, PackageInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Android,
+    Ios,
+    Harmony,
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Platform::Android => "Android",
+            Platform::Ios => "iOS",
+            Platform::Harmony => "HarmonyOS",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeviceStatus {
+    Stopped,
+    Booting,
+    Running,
+    ShuttingDown,
+    Error,
+}
+
+/// 模拟器 / 模拟设备
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+    pub device_type: String,
+    pub os_version: String,
+    pub status: DeviceStatus,
+    pub platform: Platform,
+}
+
+/// 启动过程所处阶段，随 `emulator-boot-progress` 事件推送
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BootStage {
+    /// 进程已启动，等待设备出现在 adb / hdc 中
+    WaitingForDevice,
+    /// 设备已连接，等待系统启动完成
+    Booting,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BootProgress {
+    pub platform: Platform,
+    pub id: String,
+    pub serial: Option<String>,
+    pub stage: BootStage,
+    pub elapsed_ms: u64,
+}
+
+impl BootProgress {
+    pub fn emit(&self, app: &tauri::AppHandle) {
+        use tauri::Emitter;
+        let _ = app.emit("emulator-boot-progress", self);
+    }
+}
+
+/// 启动完成后返回给前端的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BootResult {
+    pub id: String,
+    /// adb 序列号或 hdc connect key
+    pub serial: String,
+    pub boot_duration_ms: u64,
+}
+
+/// 未指定时等待启动完成的超时时间
+pub const DEFAULT_BOOT_TIMEOUT_SECS: u64 = 180;
+
+/// 平台后端；未实现的能力默认返回“不支持”错误
+#[allow(async_fn_in_trait)]
+pub trait EmulatorBackend {
+    fn platform(&self) -> Platform;
+
+    async fn list(&self) -> Result<Vec<Device>, String>;
+
+    async fn start(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String>;
+
+    async fn stop(&self, id: &str) -> Result<(), String>;
+
+    async fn delete(&self, id: &str) -> Result<(), String> {
+        let _ = id;
+        Err(unsupported(self.platform(), "delete"))
+    }
+
+    async fn wipe(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String> {
+        let _ = (id, app);
+        Err(unsupported(self.platform(), "wipe data"))
+    }
+
+    /// 截图并返回本地文件路径
+    async fn screenshot(&self, id: &str) -> Result<String, String>;
+
+    /// 读取设备上最近的 `lines` 行日志（一次性快照，不持续跟踪）
+    async fn logs(&self, id: &str, lines: usize) -> Result<Vec<String>, String> {
+        let _ = (id, lines);
+        Err(unsupported(self.platform(), "logs"))
+    }
+
+    /// 安装应用包，返回每个安装单元的结果；进度通过 `package-install-progress` 推送
+    async fn install(
+        &self,
+        id: &str,
+        paths: &[String],
+        options: &InstallOptions,
+        app: &tauri::AppHandle,
+    ) -> Result<Vec<InstallOutcome>, String> {
+        let _ = (id, paths, options, app);
+        Err(unsupported(self.platform(), "install"))
+    }
+
+    /// 已安装的应用，第三方应用在前
+    async fn packages(&self, id: &str) -> Result<Vec<PackageInfo>, String> {
+        let _ = id;
+        Err(unsupported(self.platform(), "package listing"))
+    }
+
+    /// 读取单个应用的版本、安装时间、权限等详细信息
+    async fn package_details(&self, id: &str, package: &str) -> Result<PackageInfo, String> {
+        let _ = (id, package);
+        Err(unsupported(self.platform(), "package details"))
+    }
+
+    /// 对已安装的应用执行卸载、清除数据、启动等操作
+    async fn package_action(&self, id: &str, package: &str, action: &PackageAction) -> Result<(), String> {
+        let _ = (id, package, action);
+        Err(unsupported(self.platform(), "package actions"))
+    }
+}
+
+pub fn unsupported(platform: Platform, action: &str) -> String {
+    format!("{} is not supported for {} devices", action, platform)
+}
+
+/// 未指定时 `get_device_logs` 返回的行数
+const DEFAULT_LOG_LINES: usize = 500;
+
+/// 按平台分发到具体后端。各后端是具体类型，async 方法返回的 future 仍满足 Send，可直接用于 Tauri 命令
+macro_rules! dispatch {
+    ($platform:expr, $backend:ident => $call:expr) => {
+        match $platform {
+            Platform::Android => {
+                let $backend = AndroidBackend;
+                $call.await
+            }
+            Platform::Ios => {
+                let $backend = IosBackend;
+                $call.await
+            }
+            Platform::Harmony => {
+                let $backend = HarmonyBackend;
+                $call.await
+            }
+        }
+    };
+}
+
+#[tauri::command]
+pub async fn list_devices(platform: Platform) -> Result<Vec<Device>, String> {
+    dispatch!(platform, backend => backend.list())
+}
+
+#[tauri::command]
+pub async fn start_device(platform: Platform, id: String, app: tauri::AppHandle) -> Result<(), String> {
+    dispatch!(platform, backend => backend.start(&id, &app))
+}
+
+#[tauri::command]
+pub async fn stop_device(platform: Platform, id: String) -> Result<(), String> {
+    dispatch!(platform, backend => backend.stop(&id))
+}
+
+#[tauri::command]
+pub async fn delete_device(platform: Platform, id: String) -> Result<(), String> {
+    dispatch!(platform, backend => backend.delete(&id))
+}
+
+#[tauri::command]
+pub async fn wipe_device(platform: Platform, id: String, app: tauri::AppHandle) -> Result<(), String> {
+    dispatch!(platform, backend => backend.wipe(&id, &app))
+}
+
+#[tauri::command]
+pub async fn screenshot_device(platform: Platform, id: String) -> Result<String, String> {
+    dispatch!(platform, backend => backend.screenshot(&id))
+}
+
+#[tauri::command]
+pub async fn get_device_logs(platform: Platform, id: String, lines: Option<usize>) -> Result<Vec<String>, String> {
+    let lines = lines.unwrap_or(DEFAULT_LOG_LINES);
+    dispatch!(platform, backend => backend.logs(&id, lines))
+}
+
+/// 安装一个或多个 .apk / .apks / .hap 文件
+#[tauri::command]
+pub async fn install_packages(
+    platform: Platform,
+    id: String,
+    paths: Vec<String>,
+    options: Option<InstallOptions>,
+    app: tauri::AppHandle,
+) -> Result<Vec<InstallOutcome>, String> {
+    if paths.is_empty() {
+        return Err("No packages to install".to_string());
+    }
+    let options = options.unwrap_or_default();
+    dispatch!(platform, backend => backend.install(&id, &paths, &options, &app))
+}
+
+#[tauri::command]
+pub async fn list_device_packages(platform: Platform, id: String) -> Result<Vec<PackageInfo>, String> {
+    dispatch!(platform, backend => backend.packages(&id))
+}
+
+#[tauri::command]
+pub async fn get_package_details(platform: Platform, id: String, package: String) -> Result<PackageInfo, String> {
+    dispatch!(platform, backend => backend.package_details(&id, &package))
+}
+
+#[tauri::command]
+pub async fn package_action(platform: Platform, id: String, package: String, action: PackageAction) -> Result<(), String> {
+    dispatch!(platform, backend => backend.package_action(&id, &package, &action))
+}
+
+/// 当前所有设备上正在进行的录屏
+#[tauri::command]
+pub async fn list_screen_recordings() -> Result<Vec<crate::screenrecord::RecordingInfo>, String> {
+    Ok(crate::screenrecord::list())
+}
+
+/// 批量截图中单台设备的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceScreenshot {
+    pub platform: Platform,
+    pub id: String,
+    pub name: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchScreenshotResult {
+    pub screenshots: Vec<DeviceScreenshot>,
+    /// 拼接后的总览图
+    pub contact_sheet: Option<String>,
+    pub contact_sheet_error: Option<String>,
+}
+
+/// 同时为所有运行中的设备截图，可选拼接为一张带设备标签的总览图
+#[tauri::command]
+pub async fn batch_screenshot(contact_sheet: Option<bool>) -> Result<BatchScreenshotResult, String> {
+    let (android, harmony, ios) = tokio::join!(
+        AndroidBackend.list(),
+        HarmonyBackend.list(),
+        IosBackend.list(),
+    );
+    // 某个平台不可用（如未安装 SDK、非 macOS）时只跳过该平台
+    let devices: Vec<Device> = [android, harmony, ios]
+        .into_iter()
+        .flat_map(|devices| devices.unwrap_or_default())
+        .filter(|device| device.status == DeviceStatus::Running)
+        .collect();
+    if devices.is_empty() {
+        return Err("No running devices".to_string());
+    }
+
+    let tasks: Vec<_> = devices
+        .into_iter()
+        .map(|device| {
+            tokio::spawn(async move {
+                let result = dispatch!(device.platform, backend => backend.screenshot(&device.id));
+                (device, result)
+            })
+        })
+        .collect();
+
+    let mut screenshots = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (device, result) = task
+            .await
+            .map_err(|e| format!("Screenshot task failed: {}", e))?;
+        let (path, error) = match result {
+            Ok(path) => (Some(path), None),
+            Err(e) => (None, Some(e)),
+        };
+        screenshots.push(DeviceScreenshot {
+            platform: device.platform,
+            id: device.id,
+            name: device.name,
+            path,
+            error,
+        });
+    }
+
+    let mut result = BatchScreenshotResult {
+        screenshots,
+        contact_sheet: None,
+        contact_sheet_error: None,
+    };
+    if contact_sheet.unwrap_or(false) {
+        match build_contact_sheet(&result.screenshots).await {
+            Ok(path) => result.contact_sheet = Some(path),
+            Err(e) => result.contact_sheet_error = Some(e),
+        }
+    }
+    Ok(result)
+}
+
+async fn build_contact_sheet(screenshots: &[DeviceScreenshot]) -> Result<String, String> {
+    let tiles: Vec<(String, String)> = screenshots
+        .iter()
+        .filter_map(|s| {
+            let path = s.path.clone()?;
+            Some((format!("{} - {} ({})", s.platform, s.name, s.id), path))
+        })
+        .collect();
+
+    let screenshot_dir = super::settings::get_screenshot_dir()
+        .ok_or_else(|| "Cannot find screenshot directory".to_string())?;
+    // 带毫秒，同一毫秒内重复时再加序号，避免覆盖之前的总览图
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%3f");
+    let dir = std::path::Path::new(&screenshot_dir);
+    let mut path = dir.join(format!("contact_sheet_{}.png", timestamp));
+    let mut index = 1;
+    while path.exists() {
+        index += 1;
+        path = dir.join(format!("contact_sheet_{}_{}.png", timestamp, index));
+    }
+
+    let output = path.clone();
+    tokio::task::spawn_blocking(move || crate::utils::contact_sheet::build(&tiles, &output))
+        .await
+        .map_err(|e| format!("Failed to build contact sheet: {}", e))??;
+    Ok(path.to_string_lossy().to_string())
+}
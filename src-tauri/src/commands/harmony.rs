@@ -1,44 +1,159 @@
-use serde::{Deserialize, Serialize};
 use tauri::Emitter;
 use crate::utils::new_command;
+use crate::toolchain::Tool;
+use super::settings::HarmonyLaunchParams;
+use super::device::{
+    BootProgress, BootResult, BootStage, Device, DeviceStatus, EmulatorBackend, Platform,
+    DEFAULT_BOOT_TIMEOUT_SECS,
+};
+use crate::logs::crash::{CrashCapture, CrashCaptureOptions};
+use crate::logs::hilog::HilogParser;
+use crate::logs::{self, LogFilter, LogFilterOptions, SessionId, StopReason};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::screenrecord::{self, RecordingOptions, RecordingResult, ScreenRecorder};
+use crate::db::captures::CaptureKind;
+use super::captures::{self, CaptureDevice};
+use super::packages::{
+    has_extension, run_checked, run_streaming, InstallOptions, InstallOutcome, InstallProgress, InstallStage,
+    PackageAction, PackageInfo, TempDir,
+};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HarmonyEmulator {
-    pub id: String,
-    pub name: String,
-    pub device_type: String,
-    pub os_version: String,
-    pub status: String,
+lazy_static::lazy_static! {
+    /// HVD 实例名 -> hdc connect key（如 127.0.0.1:5555），启动时记录并保存到数据库，
+    /// 应用重启后仍能识别正在运行的实例；首次使用时加载
+    static ref CONNECT_KEYS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+    /// 同一时间只有一个实例在等待自己的 target 出现，避免同时启动的两个实例互相认错
+    static ref TARGET_DISCOVERY: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
 }
 
+/// 数据库 settings 表中保存 connect key 映射的键
+const CONNECT_KEYS_SETTING: &str = "harmony_connect_keys";
+
+/// 截图远端文件的序号
+static SCREENSHOT_SEQ: AtomicU64 = AtomicU64::new(0);
+
 fn get_emulator_path() -> Result<std::path::PathBuf, String> {
-    let emulator_path = crate::commands::settings::get_harmony_emulator_path()
-        .ok_or_else(|| "Emulator path not configured. Please set DevEco Studio path or Emulator path in Settings.".to_string())?;
-    
-    let path = std::path::PathBuf::from(&emulator_path);
-    
-    if !path.exists() {
-        return Err(format!("Emulator not found at: {:?}. Please check your settings.", path));
-    }
-    
-    Ok(path)
+    crate::toolchain::resolve(Tool::HarmonyEmulator)
 }
 
 fn get_hdc_path() -> Result<std::path::PathBuf, String> {
-    let hdc_path = crate::commands::settings::get_harmony_hdc_path()
-        .ok_or_else(|| "hdc path not configured. Please set DevEco Studio path or hdc path in Settings.".to_string())?;
-    
-    let path = std::path::PathBuf::from(&hdc_path);
-    
-    if !path.exists() {
-        return Err(format!("hdc not found at: {:?}. Please check your settings.", path));
+    crate::toolchain::resolve(Tool::Hdc)
+}
+
+/// 无窗口启动参数，只有部分模拟器版本支持
+const HEADLESS_FLAG: &str = "-no-window";
+
+/// 帮助输出中是否列出了某个参数（按完整单词匹配，避免 -no-window-xxx 误判）
+fn help_lists_flag(help: &str, flag: &str) -> bool {
+    help.split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == ']' || c == '|')
+        .any(|word| word == flag)
+}
+
+/// 通过 `Emulator -help` 检测当前模拟器是否支持某个参数
+fn emulator_supports_flag(emulator_path: &std::path::Path, flag: &str) -> bool {
+    let Ok(output) = new_command(emulator_path).arg("-help").output() else {
+        return false;
+    };
+    help_lists_flag(&String::from_utf8_lossy(&output.stdout), flag)
+        || help_lists_flag(&String::from_utf8_lossy(&output.stderr), flag)
+}
+
+/// `hdc list targets` 中的全部 connect key
+fn list_targets(hdc_path: &std::path::Path) -> Vec<String> {
+    let Ok(output) = new_command(hdc_path).args(["list", "targets"]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        // 没有设备时输出 "[Empty]"
+        .filter(|line| !line.is_empty() && !line.starts_with('['))
+        .map(str::to_string)
+        .collect()
+}
+
+fn param_get(hdc_path: &std::path::Path, key: &str, name: &str) -> Option<String> {
+    let output = new_command(hdc_path)
+        .args(["-t", key, "shell", "param", "get", name])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
     }
-    
-    Ok(path)
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 截图历史中记录的设备型号和系统版本
+fn capture_device(hdc_path: &std::path::Path, key: &str) -> CaptureDevice {
+    CaptureDevice {
+        model: param_get(hdc_path, key, "const.product.model"),
+        os_version: param_get(hdc_path, key, "const.product.software.version")
+            .filter(|v| !v.is_empty())
+            .or_else(|| param_get(hdc_path, key, "const.ohos.fullname")),
+        screen_size: None,
+    }
+}
+
+fn is_boot_completed(hdc_path: &std::path::Path, key: &str) -> bool {
+    param_get(hdc_path, key, "bootevent.boot.completed").as_deref() == Some("true")
+}
+
+/// 读写 connect key 映射，有改动时写回数据库
+fn with_connect_keys<T>(f: impl FnOnce(&mut HashMap<String, String>) -> T) -> T {
+    let mut guard = CONNECT_KEYS.lock().unwrap();
+    let keys = guard.get_or_insert_with(|| {
+        crate::db::with_database(|db| db.get_setting(CONNECT_KEYS_SETTING))
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    });
+
+    let before = keys.clone();
+    let result = f(keys);
+    if *keys != before {
+        let saved = serde_json::to_string(&*keys)
+            .map_err(|e| e.to_string())
+            .and_then(|json| crate::db::with_database(|db| db.set_setting(CONNECT_KEYS_SETTING, &json)));
+        if let Err(e) = saved {
+            eprintln!("Failed to save HarmonyOS connect keys: {}", e);
+        }
+    }
+    result
+}
+
+/// 记录实例的 connect key；同一个 key 之前分配给其他实例的记录已过期，一并删除
+fn set_connect_key(id: &str, key: &str) {
+    with_connect_keys(|keys| {
+        keys.retain(|_, k| k != key);
+        keys.insert(id.to_string(), key.to_string());
+    });
+}
+
+fn remove_connect_key(id: &str) {
+    with_connect_keys(|keys| keys.remove(id));
+}
+
+/// HVD 实例当前对应的 connect key；未记录时按原样返回（兼容直接传入 connect key）
+fn connect_key(id: &str) -> String {
+    with_connect_keys(|keys| keys.get(id).cloned()).unwrap_or_else(|| id.to_string())
+}
+
+/// 启动期间新出现的 target 中属于模拟器的那个。模拟器以 `127.0.0.1:<端口>` 的 TCP 方式连接 hdc，
+/// 同时插入的 USB 真机（key 为序列号）不算
+fn find_new_emulator_target(existing: &[String], targets: &[String]) -> Option<String> {
+    targets
+        .iter()
+        .find(|target| target.contains(':') && !existing.contains(target))
+        .cloned()
 }
 
 #[tauri::command]
-pub async fn list_harmony_emulators() -> Result<Vec<HarmonyEmulator>, String> {
+pub async fn list_harmony_emulators() -> Result<Vec<Device>, String> {
     let emulator_path = get_emulator_path()?;
     
     let output = new_command(&emulator_path)
@@ -64,31 +179,29 @@ pub async fn list_harmony_emulators() -> Result<Vec<HarmonyEmulator>, String> {
         // Format may vary, adjust parsing as needed
         if !line.is_empty() {
             let name = line.to_string();
-            emulators.push(HarmonyEmulator {
+            emulators.push(Device {
                 id: name.clone(),
                 name: name.clone(),
                 device_type: "HarmonyOS Device".to_string(),
                 os_version: "HarmonyOS NEXT".to_string(),
-                status: "stopped".to_string(),
+                status: DeviceStatus::Stopped,
+                platform: Platform::Harmony,
             });
         }
     }
 
-    // Check running emulators using hdc
+    // 根据启动时记录的 connect key 判断运行状态，记录的 key 不在 hdc 中视为已退出
     if let Ok(hdc_path) = get_hdc_path() {
-        if let Ok(hdc_output) = new_command(&hdc_path).arg("list").arg("targets").output() {
-            let devices = String::from_utf8_lossy(&hdc_output.stdout);
-            for line in devices.lines() {
-                let line = line.trim();
-                if !line.is_empty() && !line.starts_with("[") {
-                    // Mark matching emulator as running
-                    for emu in &mut emulators {
-                        if line.contains(&emu.name) || emu.name.contains(line) {
-                            emu.status = "running".to_string();
-                            emu.id = line.to_string();
-                            break;
-                        }
-                    }
+        let targets = list_targets(&hdc_path);
+        let names: Vec<String> = emulators.iter().map(|emu| emu.id.clone()).collect();
+        let keys = with_connect_keys(|keys| keys.clone());
+        let running = running_instances(&names, &keys, &targets);
+        for emu in &mut emulators {
+            if let Some(key) = running.get(&emu.id) {
+                emu.status = DeviceStatus::Running;
+                // 不是由 SimHub 启动或 key 已失效的实例，记下新 key 供日志、安装等命令使用
+                if keys.get(&emu.id) != Some(key) {
+                    set_connect_key(&emu.id, key);
                 }
             }
         }
@@ -97,9 +210,182 @@ pub async fn list_harmony_emulators() -> Result<Vec<HarmonyEmulator>, String> {
     Ok(emulators)
 }
 
+/// 本机模拟器的 target，形如 `127.0.0.1:5555`
+fn is_local_emulator_target(target: &str) -> bool {
+    target.starts_with("127.0.0.1:") || target.starts_with("localhost:")
+}
+
+/// 正在运行的实例及其 connect key。优先使用启动时记录的 key；
+/// 从 DevEco Studio 启动或 key 未记录的实例，用未被占用的模拟器 target 匹配：
+/// target 含有实例名时直接匹配，否则只有一个候选实例时才认为是它
+fn running_instances(
+    instances: &[String],
+    keys: &HashMap<String, String>,
+    targets: &[String],
+) -> HashMap<String, String> {
+    let mut running = HashMap::new();
+    for name in instances {
+        if let Some(key) = keys.get(name).filter(|key| targets.contains(key)) {
+            running.insert(name.clone(), key.clone());
+        } else if targets.contains(name) {
+            // 兼容直接以 connect key 作为 id 的情况
+            running.insert(name.clone(), name.clone());
+        }
+    }
+
+    let mut unowned: Vec<&String> = targets
+        .iter()
+        .filter(|target| is_local_emulator_target(target) && !running.values().any(|key| key == *target))
+        .collect();
+    let mut candidates: Vec<&String> = instances
+        .iter()
+        .filter(|name| !running.contains_key(*name))
+        .collect();
+
+    candidates.retain(|name| match unowned.iter().position(|target| target.contains(name.as_str())) {
+        Some(index) => {
+            running.insert((*name).clone(), unowned.remove(index).clone());
+            false
+        }
+        None => true,
+    });
+    if let ([name], [target]) = (candidates.as_slice(), unowned.as_slice()) {
+        running.insert((*name).clone(), (*target).clone());
+    }
+    running
+}
+
+/// 启动前校验鸿蒙启动参数
+pub fn validate_launch_params(params: &HarmonyLaunchParams) -> Result<(), String> {
+    if let Some(memory) = params.memory {
+        if memory < 1024 {
+            return Err(format!("Memory must be at least 1024 MB, got {}", memory));
+        }
+    }
+    if let Some(cores) = params.cores {
+        if cores < 1 {
+            return Err(format!("CPU cores must be at least 1, got {}", cores));
+        }
+    }
+    match (params.resolution_width, params.resolution_height) {
+        (None, None) => {
+            if params.density.is_some() {
+                return Err("Density can only be set together with a resolution override".to_string());
+            }
+        }
+        (Some(w), Some(h)) => {
+            if w < 320 || h < 320 {
+                return Err(format!("Resolution is too small: {}x{}", w, h));
+            }
+        }
+        _ => return Err("Both resolution width and height are required".to_string()),
+    }
+    crate::utils::split_args(&params.extra_args)?;
+    Ok(())
+}
+
+/// 由启动参数管理的 config.ini 配置项
+const MANAGED_CONFIG_KEYS: [&str; 5] = ["hw.ramSize", "hw.cpu.ncore", "hw.lcd.width", "hw.lcd.height", "hw.lcd.density"];
+
+/// 实例目录下保存被覆盖配置项原始值的文件，值为 null 表示原来没有该项
+const CONFIG_BACKUP_FILE: &str = "simhub_config_backup.json";
+
+/// 启动参数对各配置项的期望值，None 表示使用实例自己的配置
+fn config_overrides(params: &HarmonyLaunchParams) -> Vec<(&'static str, Option<String>)> {
+    let resolution = params.resolution_width.zip(params.resolution_height);
+    let values = [
+        params.memory.map(|v| v.to_string()),
+        params.cores.map(|v| v.to_string()),
+        resolution.map(|(w, _)| w.to_string()),
+        resolution.map(|(_, h)| h.to_string()),
+        params.density.filter(|_| resolution.is_some()).map(|v| v.to_string()),
+    ];
+    MANAGED_CONFIG_KEYS.into_iter().zip(values).collect()
+}
+
+/// 按期望值改写 config.ini 内容。首次覆盖某项时把原值记入 `backup`，
+/// 参数清除后从 `backup` 恢复原值（原来没有的项则删除）
+fn update_config(
+    content: &str,
+    overrides: &[(&str, Option<String>)],
+    backup: &mut BTreeMap<String, Option<String>>,
+) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let find = |lines: &[String], key: &str| {
+        lines
+            .iter()
+            .position(|line| line.split('=').next().unwrap_or("").trim() == key)
+    };
+
+    for (key, value) in overrides {
+        let current = find(&lines, key);
+        let target = match value {
+            Some(v) => {
+                backup.entry(key.to_string()).or_insert_with(|| {
+                    current.map(|i| lines[i].split_once('=').map_or("", |(_, v)| v.trim()).to_string())
+                });
+                Some(v.clone())
+            }
+            None => match backup.remove(*key) {
+                Some(original) => original,
+                None => continue,
+            },
+        };
+
+        match (current, target) {
+            (Some(i), Some(v)) => lines[i] = format!("{}={}", key, v),
+            (Some(i), None) => {
+                lines.remove(i);
+            }
+            (None, Some(v)) => lines.push(format!("{}={}", key, v)),
+            (None, None) => {}
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// 把内存、核数、分辨率写入实例的 config.ini，保留其他配置项；清除的参数恢复为实例原来的配置
+fn apply_instance_config(instance_dir: &std::path::Path, params: &HarmonyLaunchParams) -> Result<(), String> {
+    let backup_path = instance_dir.join(CONFIG_BACKUP_FILE);
+    let mut backup: BTreeMap<String, Option<String>> = std::fs::read_to_string(&backup_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let overrides = config_overrides(params);
+    if backup.is_empty() && overrides.iter().all(|(_, v)| v.is_none()) {
+        return Ok(());
+    }
+
+    let config_path = instance_dir.join("config.ini");
+    let content = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read emulator config {:?}: {}", config_path, e))?;
+    let updated = update_config(&content, &overrides, &mut backup);
+
+    // 先保存原值再改写配置，中途失败时不会丢失原值
+    if backup.is_empty() {
+        let _ = std::fs::remove_file(&backup_path);
+    } else {
+        let json = serde_json::to_string_pretty(&backup)
+            .map_err(|e| format!("Failed to serialize emulator config backup: {}", e))?;
+        std::fs::write(&backup_path, json)
+            .map_err(|e| format!("Failed to write emulator config backup {:?}: {}", backup_path, e))?;
+    }
+    if updated != content {
+        std::fs::write(&config_path, updated)
+            .map_err(|e| format!("Failed to write emulator config {:?}: {}", config_path, e))?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn start_harmony_emulator(id: String, app: tauri::AppHandle) -> Result<(), String> {
+pub async fn start_harmony_emulator(
+    id: String,
+    boot_timeout_secs: Option<u64>,
+    app: tauri::AppHandle,
+) -> Result<BootResult, String> {
     let emulator_path = get_emulator_path()?;
+    let hdc_path = get_hdc_path()?;
     
     let emulator_location = crate::commands::settings::get_harmony_emulator_location()
         .ok_or_else(|| "Local Emulator Location not configured. Please set it in Settings.".to_string())?;
@@ -107,28 +393,126 @@ pub async fn start_harmony_emulator(id: String, app: tauri::AppHandle) -> Result
     let image_location = crate::commands::settings::get_harmony_image_location()
         .ok_or_else(|| "Local Image Location not configured. Please set it in Settings.".to_string())?;
     
+    // 参数有误时在启动前直接报错
+    let params = crate::commands::settings::get_harmony_launch_params_sync(&id);
+    validate_launch_params(&params)?;
+    apply_instance_config(&std::path::Path::new(&emulator_location).join(&id), &params)?;
+    
     let mut cmd = new_command(&emulator_path);
-    cmd.args(&["-hvd", &id, "-path", &emulator_location, "-imageRoot", &image_location]);
+    cmd.args(&["-hvd", &id, "-path", &emulator_location, "-imageRoot", &image_location])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if params.headless {
+        if !emulator_supports_flag(&emulator_path, HEADLESS_FLAG) {
+            return Err("Headless mode is not supported by this emulator build".to_string());
+        }
+        cmd.arg(HEADLESS_FLAG);
+    }
+    cmd.args(crate::utils::split_args(&params.extra_args)?);
     
     let _ = app.emit("add-log", serde_json::json!({
         "type": "command",
         "message": format!("{:?}", cmd),
         "source": "app"
     }));
-    
-    cmd.spawn()
+
+    // 启动前已存在的 target 不属于本实例，新出现的模拟器 target 就是它的 connect key。
+    // 找到之前其他实例的启动需要排队，否则无法区分两个同时出现的 target
+    let started = std::time::Instant::now();
+    let timeout = std::time::Duration::from_secs(boot_timeout_secs.unwrap_or(DEFAULT_BOOT_TIMEOUT_SECS));
+    let mut discovery = Some(TARGET_DISCOVERY.lock().await);
+    let existing = list_targets(&hdc_path);
+
+    let mut child = cmd.spawn()
         .map_err(|e| format!("Failed to start emulator: {}", e))?;
+    let stderr = crate::utils::capture_stderr(&mut child);
 
-    Ok(())
+    let mut key: Option<String> = None;
+    let mut last_stage = None;
+
+    loop {
+        let elapsed = started.elapsed();
+        let stage = if key.is_some() { BootStage::Booting } else { BootStage::WaitingForDevice };
+        if last_stage != Some(stage) {
+            last_stage = Some(stage);
+            BootProgress {
+                platform: Platform::Harmony,
+                id: id.clone(),
+                serial: key.clone(),
+                stage,
+                elapsed_ms: elapsed.as_millis() as u64,
+            }
+            .emit(&app);
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                remove_connect_key(&id);
+                let output = stderr.lock().unwrap().trim().to_string();
+                return Err(if !output.is_empty() {
+                    format!("Emulator '{}' exited during boot ({}): {}", id, status, output)
+                } else {
+                    format!("Emulator '{}' exited during boot: {}", id, status)
+                });
+            }
+            Ok(None) => {}
+            Err(e) => return Err(format!("Failed to check process status: {}", e)),
+        }
+
+        if elapsed > timeout {
+            // 超时的实例不再保留，避免残留一个无人管理的模拟器进程
+            let _ = child.kill();
+            let _ = child.wait();
+            remove_connect_key(&id);
+            return Err(format!(
+                "Emulator '{}' did not finish booting within {} seconds",
+                id,
+                timeout.as_secs()
+            ));
+        }
+
+        match &key {
+            None => {
+                key = find_new_emulator_target(&existing, &list_targets(&hdc_path));
+                if let Some(k) = &key {
+                    set_connect_key(&id, k);
+                    drop(discovery.take());
+                }
+            }
+            Some(k) => {
+                if is_boot_completed(&hdc_path, k) {
+                    let elapsed_ms = started.elapsed().as_millis() as u64;
+                    BootProgress {
+                        platform: Platform::Harmony,
+                        id: id.clone(),
+                        serial: Some(k.clone()),
+                        stage: BootStage::Completed,
+                        elapsed_ms,
+                    }
+                    .emit(&app);
+
+                    return Ok(BootResult {
+                        id,
+                        serial: k.clone(),
+                        boot_duration_ms: elapsed_ms,
+                    });
+                }
+            }
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+    }
 }
 
 #[tauri::command]
 pub async fn stop_harmony_emulator(id: String) -> Result<(), String> {
     let hdc_path = get_hdc_path()?;
+    let key = connect_key(&id);
     
     // Use hdc to kill the emulator
     let output = new_command(&hdc_path)
-        .args(&["-t", &id, "shell", "reboot", "-p"])
+        .args(&["-t", &key, "shell", "reboot", "-p"])
         .output()
         .map_err(|e| format!("Failed to stop emulator: {}", e))?;
 
@@ -141,41 +525,965 @@ pub async fn stop_harmony_emulator(id: String) -> Result<(), String> {
             .map_err(|e| format!("Failed to stop emulator: {}", e))?;
     }
 
+    remove_connect_key(&id);
+    crate::logs::stop_device(&key);
     Ok(())
 }
 
 #[tauri::command]
 pub async fn screenshot_harmony(id: String) -> Result<String, String> {
     let hdc_path = get_hdc_path()?;
+    let key = connect_key(&id);
     
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("screenshot_{}_{}.png", id, timestamp);
-    let remote_path = "/data/local/tmp/screenshot.png";
+    // 本地和远端文件名都带毫秒和序号，避免同一秒内的截图互相覆盖
+    let now = chrono::Local::now();
+    let seq = SCREENSHOT_SEQ.fetch_add(1, Ordering::Relaxed);
+    let filename = format!("screenshot_{}_{}_{}.png", id, now.format("%Y%m%d_%H%M%S_%3f"), seq);
+    let remote_path = format!(
+        "/data/local/tmp/simhub_screenshot_{}_{}.png",
+        now.format("%Y%m%d%H%M%S%3f"),
+        seq
+    );
     
     // Get screenshot directory from settings
     let screenshot_dir = crate::commands::settings::get_screenshot_dir()
         .ok_or_else(|| "Cannot find screenshot directory".to_string())?;
     let local_path = std::path::Path::new(&screenshot_dir).join(&filename);
 
-    let output = new_command(&hdc_path)
-        .args(&["-t", &id, "shell", "snapshot_display", "-f", remote_path])
+    let result = capture_screenshot(&hdc_path, &key, &remote_path, &local_path);
+    remove_remote_file(&hdc_path, &key, &remote_path);
+    result?;
+
+    let path = local_path.to_string_lossy().to_string();
+    captures::record(CaptureKind::Screenshot, Platform::Harmony, &key, &capture_device(&hdc_path, &key), &path);
+    Ok(path)
+}
+
+fn capture_screenshot(
+    hdc_path: &std::path::Path,
+    key: &str,
+    remote_path: &str,
+    local_path: &std::path::Path,
+) -> Result<(), String> {
+    let output = new_command(hdc_path)
+        .args(["-t", key, "shell", "snapshot_display", "-f", remote_path])
         .output()
         .map_err(|e| format!("Failed to take screenshot: {}", e))?;
 
-    if !output.status.success() {
+    // snapshot_display 失败时退出码也为 0，需要检查输出
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.to_lowercase().contains("fail") {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Screenshot failed: {}{}", stdout.trim(), stderr.trim()));
+    }
+
+    recv_file(key, remote_path, local_path)
+}
+
+fn remove_remote_file(hdc_path: &std::path::Path, key: &str, remote_path: &str) {
+    let _ = new_command(hdc_path)
+        .args(["-t", key, "shell", "rm", "-f", remote_path])
+        .output();
+}
+
+fn remote_file_exists(hdc_path: &std::path::Path, key: &str, remote_path: &str) -> bool {
+    let script = format!("test -s '{}' && echo exists", remote_path);
+    new_command(hdc_path)
+        .args(["-t", key, "shell", &script])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "exists")
+}
+
+const SCREEN_RECORDER_BUNDLE: &str = "com.huawei.hmos.screenrecorder";
+const SCREEN_RECORDER_ABILITY: &str = "com.huawei.hmos.screenrecorder.ServiceExtAbility";
+
+/// 通过系统录屏服务录屏。服务按调用切换开始 / 停止，文件写入 /data/local/tmp。
+/// 这是系统录屏应用未公开的接口，不同镜像上不一定存在或行为不同，开始前和接收前都需检查
+struct HarmonyScreenRecorder {
+    hdc_path: std::path::PathBuf,
+}
+
+impl HarmonyScreenRecorder {
+    /// 设备上没有系统录屏应用时无法录屏
+    fn check_available(&self, key: &str) -> Result<(), String> {
+        dump_bundle(&self.hdc_path, key, SCREEN_RECORDER_BUNDLE).map(|_| ()).map_err(|_| {
+            format!(
+                "Screen recording is not available on this device: the system screen recorder ({}) is not installed",
+                SCREEN_RECORDER_BUNDLE
+            )
+        })
+    }
+
+    /// 开始或停止录屏
+    fn toggle(&self, key: &str, file_name: &str) -> Result<(), String> {
+        let output = new_command(&self.hdc_path)
+            .args(["-t", key, "shell", "aa", "start", "-b", SCREEN_RECORDER_BUNDLE, "-a", SCREEN_RECORDER_ABILITY])
+            .args(["--ps", "CustomizedFileName", file_name])
+            .output()
+            .map_err(|e| format!("Failed to start screen recording: {}", e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() || !stdout.contains("successfully") {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Screen recording failed: {}{}", stdout.trim(), stderr.trim()));
+        }
+        Ok(())
+    }
+}
+
+impl ScreenRecorder for HarmonyScreenRecorder {
+    fn platform(&self) -> Platform {
+        Platform::Harmony
+    }
+
+    /// 服务没有时长限制，按段切分只是为了控制单个文件的大小
+    fn max_segment_secs(&self) -> u64 {
+        600
+    }
+
+    fn remote_path(&self, name: &str) -> String {
+        format!("/data/local/tmp/{}", name)
+    }
+
+    fn spawn(&self, device_id: &str, remote: &str, _options: &RecordingOptions, secs: u64) -> Result<std::process::Child, String> {
+        let file_name = remote.rsplit('/').next().unwrap_or(remote);
+        self.toggle(device_id, file_name)?;
+        // 设备端计时，设备断开时随之结束
+        let timer = new_command(&self.hdc_path)
+            .args(["-t", device_id, "shell", "sleep", &secs.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+        timer.map_err(|e| {
+            self.end_segment(device_id, remote);
+            format!("Failed to start screen recording: {}", e)
+        })
+    }
+
+    fn interrupt(&self, _device_id: &str, child: &mut std::process::Child) {
+        let _ = child.kill();
+    }
+
+    fn end_segment(&self, device_id: &str, remote: &str) {
+        let file_name = remote.rsplit('/').next().unwrap_or(remote);
+        if self.toggle(device_id, file_name).is_ok() {
+            // 服务停止后还需要写完文件
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    }
+
+    fn pull(&self, device_id: &str, remote: &str, local: &std::path::Path) -> Result<(), String> {
+        // 录屏服务接受了请求却没有写出文件，说明该镜像上的服务不支持按文件名录制
+        if !remote_file_exists(&self.hdc_path, device_id, remote) {
+            return Err(format!(
+                "The system screen recorder did not produce {}; screen recording via {} is not supported on this device",
+                remote, SCREEN_RECORDER_ABILITY
+            ));
+        }
+        recv_file(device_id, remote, local)
+    }
+
+    fn remove(&self, device_id: &str, remote: &str) {
+        remove_remote_file(&self.hdc_path, device_id, remote);
+    }
+
+    fn describe(&self, device_id: &str) -> CaptureDevice {
+        capture_device(&self.hdc_path, device_id)
+    }
+}
+
+/// 开始录屏，参数与 Android 相同；HarmonyOS 录屏服务不支持设置码率和分辨率
+#[tauri::command]
+pub async fn start_recording_harmony(id: String, options: Option<RecordingOptions>, app: tauri::AppHandle) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    if options.bit_rate.is_some() || options.size.is_some() {
+        return Err("Bit rate and size are not supported by the HarmonyOS screen recorder".to_string());
+    }
+    let recorder = HarmonyScreenRecorder { hdc_path: get_hdc_path()? };
+    let key = connect_key(&id);
+    recorder.check_available(&key)?;
+    screenrecord::start(recorder, &key, options, app)
+}
+
+/// 停止录屏，返回接收到截图目录中的视频文件
+#[tauri::command]
+pub async fn stop_recording_harmony(id: String) -> Result<RecordingResult, String> {
+    let key = connect_key(&id);
+    tokio::task::spawn_blocking(move || screenrecord::stop(&key))
+        .await
+        .map_err(|e| format!("Failed to stop screen recording: {}", e))?
+}
+
+/// 开始持续抓取 hilog，返回会话 id；日志通过 `log-lines` 事件推送，语义与 logcat 相同
+#[tauri::command]
+pub async fn start_hilog(
+    device_id: String,
+    filter: Option<LogFilterOptions>,
+    crash: Option<CrashCaptureOptions>,
+    app: tauri::AppHandle,
+) -> Result<SessionId, String> {
+    let hdc_path = get_hdc_path()?;
+    let key = connect_key(&device_id);
+    let filter = Arc::new(LogFilter::new(&filter.unwrap_or_default())?);
+    let crash_capture = CrashCapture::new(crash, recv_file)?;
+
+    // 按包名过滤时先查一次进程号，避免开头的日志被全部过滤
+    if let Some(bundle) = filter.package() {
+        filter.set_pids(bundle_pids(&hdc_path, &key, bundle).await);
+    }
+
+    let mut cmd = tokio::process::Command::from(new_command(&hdc_path));
+    let child = cmd
+        .args(["-t", &key, "shell", "hilog"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start hilog: {}", e))?;
+
+    let session = logs::register(Platform::Harmony, &key, filter.clone());
+    let session_id = session.id;
+    logs::spawn_process(session.clone(), child, HilogParser, crash_capture, app)?;
+
+    // hilog 中没有进程启动日志，应用重启后的进程号只能靠定时刷新
+    if let Some(bundle) = filter.package().map(str::to_string) {
+        tokio::spawn(async move {
+            while session.is_running() {
+                tokio::time::sleep(logs::PID_REFRESH_INTERVAL).await;
+                filter.set_pids(bundle_pids(&hdc_path, &key, &bundle).await);
+            }
+        });
+    }
+
+    Ok(session_id)
+}
+
+/// 应用正在运行的全部进程；应用未运行时为空
+async fn bundle_pids(hdc_path: &std::path::Path, key: &str, bundle: &str) -> HashSet<u32> {
+    let mut cmd = tokio::process::Command::from(new_command(hdc_path));
+    cmd.args(["-t", key, "shell", "ps", "-A", "-o", "PID,NAME"]);
+    let Ok(output) = cmd.output().await else {
+        return HashSet::new();
+    };
+    logs::parse_package_pids(&String::from_utf8_lossy(&output.stdout), bundle)
+}
+
+/// 从设备拉取文件（faultlog 等）
+fn recv_file(key: &str, remote: &str, local: &std::path::Path) -> Result<(), String> {
+    let hdc_path = get_hdc_path()?;
+    let output = new_command(&hdc_path)
+        .args(["-t", key, "file", "recv", remote])
+        .arg(local)
+        .output()
+        .map_err(|e| format!("Failed to receive {}: {}", remote, e))?;
+
+    // hdc 出错时退出码也可能为 0，需要检查输出
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("[Fail]") {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Screenshot failed: {}", stderr));
+        return Err(format!("Failed to receive {}: {}{}", remote, stdout.trim(), stderr.trim()));
+    }
+    Ok(())
+}
+
+/// 停止 hilog：指定 session_id 只停该会话，指定 device_id 停该设备的全部会话，都不指定则全部停止
+#[tauri::command]
+pub async fn stop_hilog(device_id: Option<String>, session_id: Option<SessionId>) -> Result<(), String> {
+    if let Some(id) = session_id {
+        logs::stop(id, StopReason::Stopped);
+        return Ok(());
     }
 
+    let key = device_id.as_deref().map(connect_key);
+    logs::stop_where(StopReason::Stopped, |session| {
+        session.platform == Platform::Harmony
+            && key.as_deref().is_none_or(|k| session.device_id == k)
+    });
+    Ok(())
+}
+
+async fn read_recent_hilog(id: &str, lines: usize) -> Result<Vec<String>, String> {
+    let hdc_path = get_hdc_path()?;
+    let key = connect_key(id);
+
+    // -x 输出缓冲区中的日志后立即退出
     let output = new_command(&hdc_path)
-        .args(&["-t", &id, "file", "recv", remote_path, local_path.to_str().unwrap()])
+        .args(&["-t", &key, "shell", "hilog", "-x"])
         .output()
-        .map_err(|e| format!("Failed to download screenshot: {}", e))?;
+        .map_err(|e| format!("Failed to read hilog: {}", e))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to download screenshot: {}", stderr));
+        return Err(format!("Failed to read hilog: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let all: Vec<&str> = stdout.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|line| line.to_string()).collect())
+}
+
+pub struct HarmonyBackend;
+
+impl EmulatorBackend for HarmonyBackend {
+    fn platform(&self) -> Platform {
+        Platform::Harmony
+    }
+
+    async fn list(&self) -> Result<Vec<Device>, String> {
+        list_harmony_emulators().await
+    }
+
+    async fn start(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String> {
+        start_harmony_emulator(id.to_string(), None, app.clone()).await.map(|_| ())
+    }
+
+    async fn stop(&self, id: &str) -> Result<(), String> {
+        stop_harmony_emulator(id.to_string()).await
+    }
+
+    async fn screenshot(&self, id: &str) -> Result<String, String> {
+        screenshot_harmony(id.to_string()).await
+    }
+
+    async fn logs(&self, id: &str, lines: usize) -> Result<Vec<String>, String> {
+        read_recent_hilog(id, lines).await
+    }
+
+    async fn install(
+        &self,
+        id: &str,
+        paths: &[String],
+        options: &InstallOptions,
+        app: &tauri::AppHandle,
+    ) -> Result<Vec<InstallOutcome>, String> {
+        install_packages(&connect_key(id), paths, options, app).await
+    }
+
+    async fn packages(&self, id: &str) -> Result<Vec<PackageInfo>, String> {
+        let key = connect_key(id);
+        tokio::task::spawn_blocking(move || list_packages(&key))
+            .await
+            .map_err(|e| format!("Failed to list bundles: {}", e))?
+    }
+
+    async fn package_details(&self, id: &str, package: &str) -> Result<PackageInfo, String> {
+        let (key, package) = (connect_key(id), package.to_string());
+        tokio::task::spawn_blocking(move || package_details(&key, &package))
+            .await
+            .map_err(|e| format!("Failed to read bundle info: {}", e))?
+    }
+
+    async fn package_action(&self, id: &str, package: &str, action: &PackageAction) -> Result<(), String> {
+        let (key, package, action) = (connect_key(id), package.to_string(), action.clone());
+        tokio::task::spawn_blocking(move || run_package_action(&key, &package, &action))
+            .await
+            .map_err(|e| format!("Package action failed: {}", e))?
+    }
+}
+
+/// 常见 bm 安装错误码的说明
+fn describe_install_error(code: &str) -> Option<&'static str> {
+    let message = match code {
+        "9568322" => "The package signature is not trusted by this device. Sign it with a debug profile that includes this device",
+        "9568332" => "The package is signed differently from the installed app. Uninstall the existing app first",
+        "9568278" => "The modules being installed have different version codes",
+        "9568305" => "A module this package depends on is not installed. Install all modules of the app together",
+        "9568347" => "The package's native libraries do not match the device CPU architecture",
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// 从 hdc install 输出中取出错误码和可读说明
+fn install_error(output: &str) -> (Option<String>, String) {
+    let code = regex::Regex::new(r"code:\s*(\d+)")
+        .ok()
+        .and_then(|re| re.captures(output).map(|c| c[1].to_string()));
+    let raw = output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| line.contains("error:") || line.contains("[Fail]"))
+        .or_else(|| output.lines().map(str::trim).rfind(|line| !line.is_empty()))
+        .unwrap_or("Installation failed")
+        .to_string();
+    let description = code.as_deref().and_then(describe_install_error).or_else(|| {
+        raw.to_lowercase()
+            .contains("downgrade")
+            .then_some("A newer version of this app is already installed. Uninstall it first")
+    });
+    let message = match description {
+        Some(description) => format!("{} ({})", description, raw),
+        None => raw,
+    };
+    (code, message)
+}
+
+/// 逐个安装单元执行 `hdc install`；同一应用的多个模块放入同一目录一起安装
+async fn install_packages(
+    key: &str,
+    paths: &[String],
+    options: &InstallOptions,
+    app: &tauri::AppHandle,
+) -> Result<Vec<InstallOutcome>, String> {
+    if let Some(path) = paths.iter().find(|p| !has_extension(p, &["hap", "hsp"])) {
+        return Err(format!("Unsupported package for HarmonyOS: {}", path));
+    }
+    let hdc_path = get_hdc_path()?;
+
+    let units: Vec<Vec<String>> = if options.same_app {
+        vec![paths.to_vec()]
+    } else {
+        paths.iter().map(|p| vec![p.clone()]).collect()
+    };
+
+    let total = units.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (index, unit) in units.into_iter().enumerate() {
+        let progress = |stage, message: Option<String>| {
+            InstallProgress {
+                platform: Platform::Harmony,
+                device_id: key.to_string(),
+                paths: unit.clone(),
+                index,
+                total,
+                stage,
+                message,
+            }
+            .emit(app)
+        };
+        progress(InstallStage::Preparing, None);
+
+        // hdc install 只接受单个文件或目录
+        let mut _temp = None;
+        let target = if unit.len() > 1 {
+            let prepared = TempDir::new().and_then(|temp| {
+                for (i, path) in unit.iter().enumerate() {
+                    let name = std::path::Path::new(path)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    std::fs::copy(path, temp.0.join(format!("{:03}_{}", i, name)))
+                        .map_err(|e| format!("Failed to copy {}: {}", path, e))?;
+                }
+                Ok(temp)
+            });
+            match prepared {
+                Ok(temp) => _temp.insert(temp).0.clone(),
+                Err(e) => {
+                    progress(InstallStage::Failed, Some(e.clone()));
+                    outcomes.push(InstallOutcome::failed(&unit, None, e));
+                    continue;
+                }
+            }
+        } else {
+            std::path::PathBuf::from(&unit[0])
+        };
+
+        let mut cmd = new_command(&hdc_path);
+        cmd.args(["-t", key, "install"]);
+        if options.reinstall {
+            cmd.arg("-r");
+        }
+        cmd.arg(&target);
+
+        progress(InstallStage::Installing, None);
+        let result = run_streaming(cmd, |line| progress(InstallStage::Installing, Some(line.to_string()))).await;
+        // hdc 出错时退出码也可能为 0，需要检查输出
+        let outcome = match result {
+            Ok((true, output)) if output.contains("successfully") && !output.contains("[Fail]") => InstallOutcome {
+                paths: unit.clone(),
+                success: true,
+                code: None,
+                error: None,
+            },
+            Ok((_, output)) => {
+                let (code, message) = install_error(&output);
+                InstallOutcome::failed(&unit, code, message)
+            }
+            Err(e) => InstallOutcome::failed(&unit, None, e),
+        };
+        match &outcome.error {
+            None => progress(InstallStage::Completed, None),
+            Some(e) => progress(InstallStage::Failed, Some(e.clone())),
+        }
+        outcomes.push(outcome);
     }
+    Ok(outcomes)
+}
 
-    Ok(local_path.to_string_lossy().to_string())
+/// `bm dump -n` 输出的应用信息；输出首行为包名，其后是 JSON
+fn dump_bundle(hdc_path: &std::path::Path, key: &str, bundle: &str) -> Result<serde_json::Value, String> {
+    let output = new_command(hdc_path)
+        .args(["-t", key, "shell", "bm", "dump", "-n", bundle])
+        .output()
+        .map_err(|e| format!("Failed to read bundle info: {}", e))?;
+    parse_bundle_dump(&String::from_utf8_lossy(&output.stdout), bundle)
+}
+
+fn parse_bundle_dump(stdout: &str, bundle: &str) -> Result<serde_json::Value, String> {
+    let json = stdout
+        .find('{')
+        .map(|start| &stdout[start..])
+        .ok_or_else(|| format!("Bundle {} not found: {}", bundle, stdout.trim()))?;
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse bundle info: {}", e))
+}
+
+/// `bm dump -a` 输出形如 "ID: 100:" 后跟每行一个包名
+fn parse_bundle_list(output: &str) -> Vec<String> {
+    let mut bundles: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("ID:") && !line.contains(' '))
+        .map(str::to_string)
+        .collect();
+    bundles.sort();
+    bundles.dedup();
+    bundles
+}
+
+/// 每次 shell 调用 dump 的包数，避免命令行过长
+const SYSTEM_BUNDLE_BATCH: usize = 20;
+
+/// 按批在一次 shell 调用中依次 dump 多个包，按 applicationInfo.isSystemApp 分类
+fn system_bundles(hdc_path: &std::path::Path, key: &str, bundles: &[String]) -> Result<HashSet<String>, String> {
+    let mut system = HashSet::new();
+    for batch in bundles.chunks(SYSTEM_BUNDLE_BATCH) {
+        let script = batch
+            .iter()
+            .map(|b| format!("bm dump -n {}", b))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let output = new_command(hdc_path)
+            .args(["-t", key, "shell", &script])
+            .output()
+            .map_err(|e| format!("Failed to read bundle info: {}", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() || stdout.contains("[Fail]") {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to read bundle info: {}{}", stdout.trim(), stderr.trim()));
+        }
+        system.extend(parse_system_bundles(&stdout));
+    }
+    Ok(system)
+}
+
+/// 连续的 `bm dump -n` 输出中系统应用的包名；每段以 "包名:" 开头，其后是该包的 JSON
+fn parse_system_bundles(output: &str) -> HashSet<String> {
+    let mut system = HashSet::new();
+    let mut rest = output;
+    while let Some(start) = rest.find('{') {
+        let mut stream = serde_json::Deserializer::from_str(&rest[start..]).into_iter::<serde_json::Value>();
+        match stream.next() {
+            Some(Ok(info)) => {
+                if info["applicationInfo"]["isSystemApp"].as_bool() == Some(true) {
+                    if let Some(name) = info["name"].as_str().or_else(|| info["applicationInfo"]["bundleName"].as_str()) {
+                        system.insert(name.to_string());
+                    }
+                }
+                rest = &rest[start + stream.byte_offset()..];
+            }
+            // 无法解析的段落跳过
+            _ => rest = &rest[start + 1..],
+        }
+    }
+    system
+}
+
+fn list_packages(key: &str) -> Result<Vec<PackageInfo>, String> {
+    let hdc_path = get_hdc_path()?;
+    let output = new_command(&hdc_path)
+        .args(["-t", key, "shell", "bm", "dump", "-a"])
+        .output()
+        .map_err(|e| format!("Failed to list bundles: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("[Fail]") {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to list bundles: {}{}", stdout.trim(), stderr.trim()));
+    }
+
+    let bundles = parse_bundle_list(&stdout);
+    let system = system_bundles(&hdc_path, key, &bundles)?;
+    let mut packages: Vec<PackageInfo> = bundles
+        .iter()
+        .map(|name| PackageInfo::new(name, system.contains(name)))
+        .collect();
+
+    // 排序：第三方包在前，系统包在后
+    packages.sort_by(|a, b| a.is_system.cmp(&b.is_system).then_with(|| a.name.cmp(&b.name)));
+    Ok(packages)
+}
+
+/// bm 中的时间为毫秒时间戳，转换为与 Android 相同的本地时间格式
+fn format_millis(value: &serde_json::Value) -> Option<String> {
+    let millis = value.as_i64().filter(|ms| *ms > 0)?;
+    let time = chrono::DateTime::from_timestamp_millis(millis)?;
+    Some(time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// 从 `bm dump -n` 的 JSON 中取字段，顶层没有时读取 applicationInfo
+fn bundle_field<'a>(info: &'a serde_json::Value, key: &str, app_key: &str) -> Option<&'a serde_json::Value> {
+    [&info[key], &info["applicationInfo"][app_key]]
+        .into_iter()
+        .find(|v| !v.is_null())
+}
+
+fn package_details(key: &str, bundle: &str) -> Result<PackageInfo, String> {
+    let hdc_path = get_hdc_path()?;
+    Ok(bundle_details(&dump_bundle(&hdc_path, key, bundle)?, bundle))
+}
+
+/// `bm dump -n` 中的应用信息；授予状态需通过 atm 查询，granted_permissions 留空
+fn bundle_details(info: &serde_json::Value, bundle: &str) -> PackageInfo {
+    let app = &info["applicationInfo"];
+
+    let mut package = PackageInfo::new(bundle, app["isSystemApp"].as_bool().unwrap_or(false));
+    package.version_name = bundle_field(info, "versionName", "versionName")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    package.version_code = bundle_field(info, "versionCode", "versionCode").and_then(|v| v.as_u64());
+    package.min_sdk = bundle_field(info, "compatibleVersion", "apiCompatibleVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    package.target_sdk = bundle_field(info, "targetVersion", "apiTargetVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    package.first_install_time = format_millis(&info["installTime"]);
+    package.last_update_time = format_millis(&info["updateTime"]);
+    package.uid = bundle_field(info, "uid", "uid").and_then(|v| v.as_u64()).map(|v| v as u32);
+    // 优先取入口模块的 HAP
+    let entry = info["entryModuleName"].as_str().unwrap_or_default();
+    package.apk_path = info["hapModuleInfos"]
+        .as_array()
+        .and_then(|modules| {
+            modules
+                .iter()
+                .filter(|m| m["hapPath"].is_string())
+                .max_by_key(|m| m["moduleName"].as_str() == Some(entry))
+        })
+        .and_then(|m| m["hapPath"].as_str())
+        .or_else(|| app["codePath"].as_str())
+        .map(str::to_string);
+    package.requested_permissions = info["reqPermissions"]
+        .as_array()
+        .map(|permissions| {
+            permissions
+                .iter()
+                .filter_map(|p| p.as_str().or_else(|| p["name"].as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    package
+}
+
+/// 入口模块及其主 Ability
+fn main_ability(info: &serde_json::Value) -> Option<(String, String)> {
+    let modules = info["hapModuleInfos"].as_array()?;
+    let entry = info["entryModuleName"].as_str().unwrap_or_default();
+    modules
+        .iter()
+        .filter(|m| !m["mainAbility"].as_str().unwrap_or_default().is_empty())
+        .max_by_key(|m| m["moduleName"].as_str() == Some(entry))
+        .map(|m| {
+            (
+                m["moduleName"].as_str().unwrap_or_default().to_string(),
+                m["mainAbility"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+}
+
+fn run_package_action(key: &str, bundle: &str, action: &PackageAction) -> Result<(), String> {
+    let hdc_path = get_hdc_path()?;
+    let shell = |args: &[&str]| {
+        let mut cmd = new_command(&hdc_path);
+        cmd.args(["-t", key, "shell"]).args(args);
+        cmd
+    };
+
+    match action {
+        PackageAction::Uninstall { keep_data } => {
+            let mut args = vec!["bm", "uninstall", "-n", bundle];
+            if *keep_data {
+                args.push("-k");
+            }
+            run_checked(shell(&args), "uninstall bundle", |out| out.contains("successfully"))?;
+        }
+        PackageAction::ClearData => {
+            run_checked(shell(&["bm", "clean", "-n", bundle, "-d"]), "clear app data", |out| out.contains("successfully"))?;
+        }
+        PackageAction::ForceStop => {
+            run_checked(shell(&["aa", "force-stop", bundle]), "force stop app", |out| out.contains("successfully"))?;
+        }
+        PackageAction::Launch => {
+            let info = dump_bundle(&hdc_path, key, bundle)?;
+            let (module, ability) =
+                main_ability(&info).ok_or_else(|| format!("No launchable ability found in {}", bundle))?;
+            run_checked(
+                shell(&["aa", "start", "-b", bundle, "-m", &module, "-a", &ability]),
+                "launch app",
+                |out| out.contains("successfully"),
+            )?;
+        }
+        // 权限通过 atm 按应用的 access token 授予 / 撤销
+        PackageAction::Grant { permission } | PackageAction::Revoke { permission } => {
+            let info = dump_bundle(&hdc_path, key, bundle)?;
+            let token = info["applicationInfo"]["accessTokenId"]
+                .as_u64()
+                .ok_or_else(|| format!("Failed to find access token of {}", bundle))?
+                .to_string();
+            let (flag, name) = match action {
+                PackageAction::Grant { .. } => ("-g", "grant permission"),
+                _ => ("-c", "revoke permission"),
+            };
+            run_checked(
+                shell(&["atm", "perm", flag, "-i", &token, "-p", permission]),
+                name,
+                |out| out.contains("Success") || out.contains("success"),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn new_emulator_target_ignores_existing_and_usb_devices() {
+        let existing = targets(&["127.0.0.1:5555", "FMR0223B08000459"]);
+        let current = targets(&["127.0.0.1:5555", "FMR0223B08000459", "23E0224A16003811", "127.0.0.1:5557"]);
+        assert_eq!(find_new_emulator_target(&existing, &current).as_deref(), Some("127.0.0.1:5557"));
+        assert_eq!(find_new_emulator_target(&existing, &targets(&["23E0224A16003811"])), None);
+    }
+
+    const CONFIG: &str = "hw.cpu.arch=arm64\nhw.ramSize=4096\nhw.lcd.width=1260\nhw.lcd.height=2720\n";
+
+    fn params(memory: Option<i32>, resolution: Option<(u32, u32)>, density: Option<u32>) -> HarmonyLaunchParams {
+        HarmonyLaunchParams {
+            memory,
+            resolution_width: resolution.map(|(w, _)| w),
+            resolution_height: resolution.map(|(_, h)| h),
+            density,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn config_overrides_are_restored_when_cleared() {
+        let mut backup = BTreeMap::new();
+        let overridden = update_config(
+            CONFIG,
+            &config_overrides(&params(Some(8192), Some((1080, 2340)), Some(480))),
+            &mut backup,
+        );
+        assert_eq!(
+            overridden,
+            "hw.cpu.arch=arm64\nhw.ramSize=8192\nhw.lcd.width=1080\nhw.lcd.height=2340\nhw.lcd.density=480\n"
+        );
+        assert_eq!(backup.get("hw.ramSize"), Some(&Some("4096".to_string())));
+        assert_eq!(backup.get("hw.lcd.density"), Some(&None));
+        assert!(!backup.contains_key("hw.cpu.ncore"));
+
+        // 再次覆盖时保留最初的原值
+        let changed = update_config(&overridden, &config_overrides(&params(Some(6144), None, None)), &mut backup);
+        assert_eq!(changed, "hw.cpu.arch=arm64\nhw.ramSize=6144\nhw.lcd.width=1260\nhw.lcd.height=2720\n");
+        assert_eq!(backup.get("hw.ramSize"), Some(&Some("4096".to_string())));
+        assert_eq!(backup.len(), 1);
+
+        let restored = update_config(&changed, &config_overrides(&HarmonyLaunchParams::default()), &mut backup);
+        assert_eq!(restored, CONFIG);
+        assert!(backup.is_empty());
+    }
+
+    #[test]
+    fn headless_flag_detected_in_help() {
+        let help = "Usage: Emulator -hvd <name> -path <dir> [options]\n  -no-window        Run without a window\n  -help\n";
+        assert!(help_lists_flag(help, HEADLESS_FLAG));
+        assert!(help_lists_flag("[-no-window|-window]", HEADLESS_FLAG));
+        assert!(!help_lists_flag("  -no-window-border  Hide the frame\n", HEADLESS_FLAG));
+        assert!(!help_lists_flag("Usage: Emulator -hvd <name>\n", HEADLESS_FLAG));
+    }
+
+    #[test]
+    fn density_requires_resolution() {
+        let overrides = config_overrides(&params(None, None, Some(480)));
+        assert!(overrides.iter().all(|(_, v)| v.is_none()));
+    }
+
+    const BM_DUMP_BUNDLE: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bm_dump_bundle.txt"));
+
+    #[test]
+    fn parses_bundle_details_from_bm_dump() {
+        let info = parse_bundle_dump(BM_DUMP_BUNDLE, "com.example.demo").unwrap();
+        let package = bundle_details(&info, "com.example.demo");
+        assert_eq!(package.name, "com.example.demo");
+        assert!(!package.is_system);
+        assert_eq!(package.version_name.as_deref(), Some("1.0.2"));
+        assert_eq!(package.version_code, Some(1_000_002));
+        assert_eq!((package.min_sdk, package.target_sdk), (Some(12), Some(12)));
+        assert_eq!(package.uid, Some(20_020_041));
+        assert_eq!(package.apk_path.as_deref(), Some("/data/app/el1/bundle/public/com.example.demo/entry.hap"));
+        assert_eq!(package.requested_permissions, ["ohos.permission.INTERNET", "ohos.permission.CAMERA"]);
+        assert!(package.granted_permissions.is_empty());
+        // 时间按本地时区格式化，只检查格式
+        for time in [&package.first_install_time, &package.last_update_time] {
+            let time = time.as_deref().unwrap();
+            assert!(chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").is_ok(), "{}", time);
+        }
+        assert_eq!(main_ability(&info), Some(("entry".to_string(), "EntryAbility".to_string())));
+    }
+
+    #[test]
+    fn falls_back_to_application_info_fields() {
+        let info = serde_json::json!({
+            "applicationInfo": {
+                "isSystemApp": true,
+                "versionName": "5.0.0",
+                "versionCode": 500,
+                "apiCompatibleVersion": 10,
+                "apiTargetVersion": 11,
+                "codePath": "/system/app/Settings",
+                "uid": 1000
+            },
+            "reqPermissions": [{ "name": "ohos.permission.MANAGE_SETTINGS" }]
+        });
+        let package = bundle_details(&info, "com.ohos.settings");
+        assert!(package.is_system);
+        assert_eq!(package.version_name.as_deref(), Some("5.0.0"));
+        assert_eq!((package.min_sdk, package.target_sdk), (Some(10), Some(11)));
+        assert_eq!(package.apk_path.as_deref(), Some("/system/app/Settings"));
+        assert_eq!(package.first_install_time, None);
+        assert_eq!(package.requested_permissions, ["ohos.permission.MANAGE_SETTINGS"]);
+    }
+
+    #[test]
+    fn reports_missing_bundle() {
+        let error = parse_bundle_dump("error: failed to get information and the parameters may be wrong.\n", "x").unwrap_err();
+        assert!(error.starts_with("Bundle x not found"), "{}", error);
+    }
+
+    #[test]
+    fn classifies_system_bundles_and_skips_broken_segments() {
+        let output = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bm_dump_bundles.txt"));
+        let mut system: Vec<String> = parse_system_bundles(output).into_iter().collect();
+        system.sort();
+        assert_eq!(system, ["com.ohos.launcher", "com.ohos.settings"]);
+        assert!(parse_system_bundles("").is_empty());
+    }
+
+    fn keys(items: &[(&str, &str)]) -> HashMap<String, String> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn running_status_prefers_recorded_keys() {
+        let instances = targets(&["Phone", "Tablet"]);
+        let running = running_instances(
+            &instances,
+            &keys(&[("Phone", "127.0.0.1:5557"), ("Tablet", "127.0.0.1:5555")]),
+            &targets(&["127.0.0.1:5557", "FMR0223B08000459"]),
+        );
+        assert_eq!(running, keys(&[("Phone", "127.0.0.1:5557")]));
+    }
+
+    #[test]
+    fn running_status_adopts_unowned_emulator_target() {
+        // 从 DevEco Studio 启动、SimHub 没有记录 key 的实例
+        let instances = targets(&["Phone", "Tablet"]);
+        let running = running_instances(
+            &instances,
+            &keys(&[("Tablet", "127.0.0.1:5555")]),
+            &targets(&["127.0.0.1:5555", "127.0.0.1:5557", "FMR0223B08000459", "192.168.1.8:8710"]),
+        );
+        assert_eq!(running, keys(&[("Phone", "127.0.0.1:5557"), ("Tablet", "127.0.0.1:5555")]));
+
+        // 记录的 key 已失效时同样按未占用的 target 匹配
+        let running = running_instances(&targets(&["Phone"]), &keys(&[("Phone", "127.0.0.1:5555")]), &targets(&["127.0.0.1:5559"]));
+        assert_eq!(running, keys(&[("Phone", "127.0.0.1:5559")]));
+    }
+
+    #[test]
+    fn running_status_is_not_guessed_when_ambiguous() {
+        let instances = targets(&["Phone", "Tablet"]);
+        assert!(running_instances(&instances, &HashMap::new(), &targets(&["127.0.0.1:5557"])).is_empty());
+        assert!(running_instances(&instances, &HashMap::new(), &targets(&["FMR0223B08000459"])).is_empty());
+        // 真机或网络设备不会被当作模拟器
+        assert!(running_instances(&targets(&["Phone"]), &HashMap::new(), &targets(&["192.168.1.8:8710"])).is_empty());
+    }
+
+    #[test]
+    fn describes_known_install_errors() {
+        let cases = [
+            ("9568322", Some("The package signature is not trusted by this device")),
+            ("9568332", Some("The package is signed differently")),
+            ("9568305", Some("A module this package depends on is not installed")),
+            ("9568347", Some("The package's native libraries do not match")),
+            ("9568289", None),
+            ("", None),
+        ];
+        for (code, expected) in cases {
+            let actual = describe_install_error(code);
+            assert_eq!(actual.is_some(), expected.is_some(), "{}", code);
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                assert!(actual.starts_with(expected), "{}: {}", code, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn parses_hdc_install_failures() {
+        let cases = [
+            (
+                "[Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568322 error: signature verification failed due to not trusted app source.\n\
+                 AppMod finish\n",
+                Some("9568322"),
+                "The package signature is not trusted by this device. Sign it with a debug profile that includes this device \
+                 ([Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568322 error: signature verification failed due to not trusted app source.)",
+            ),
+            (
+                "[Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code: 9568332 error: install sign info inconsistent.\n\
+                 AppMod finish\n",
+                Some("9568332"),
+                "The package is signed differently from the installed app. Uninstall the existing app first \
+                 ([Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code: 9568332 error: install sign info inconsistent.)",
+            ),
+            (
+                "[Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568263 error: install version downgrade.\n\
+                 AppMod finish\n",
+                Some("9568263"),
+                "A newer version of this app is already installed. Uninstall it first \
+                 ([Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568263 error: install version downgrade.)",
+            ),
+            (
+                "[Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568289 error: install failed due to grant request permissions failed.\n\
+                 AppMod finish\n",
+                Some("9568289"),
+                "[Info]App install path:/tmp/entry-default-signed.hap, queuesize:0, msg:error: failed to install bundle. \
+                 code:9568289 error: install failed due to grant request permissions failed.",
+            ),
+            (
+                "[Fail]ExecuteCommand need connect-key? please confirm a device by help info\n",
+                None,
+                "[Fail]ExecuteCommand need connect-key? please confirm a device by help info",
+            ),
+            ("", None, "Installation failed"),
+        ];
+        for (output, code, message) in cases {
+            let (actual_code, actual_message) = install_error(output);
+            assert_eq!(actual_code.as_deref(), code, "{}", output);
+            assert_eq!(actual_message, message, "{}", output);
+        }
+    }
 }
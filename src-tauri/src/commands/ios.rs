@@ -1,20 +1,24 @@
-use serde::{Deserialize, Serialize};
 use tauri::Emitter;
 
 #[cfg(target_os = "macos")]
 use crate::utils::new_command;
+#[cfg(target_os = "macos")]
+use crate::toolchain::Tool;
+use super::device::{Device, EmulatorBackend, Platform};
+#[cfg(target_os = "macos")]
+use super::device::DeviceStatus;
+#[cfg(target_os = "macos")]
+use super::captures::{self, CaptureDevice};
+#[cfg(target_os = "macos")]
+use crate::db::captures::CaptureKind;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct IOSSimulator {
-    pub id: String,
-    pub name: String,
-    pub device_type: String,
-    pub os_version: String,
-    pub status: String,
+#[cfg(target_os = "macos")]
+fn xcrun() -> Result<std::process::Command, String> {
+    Ok(new_command(crate::toolchain::resolve(Tool::Xcrun)?))
 }
 
 #[tauri::command]
-pub async fn list_ios_simulators() -> Result<Vec<IOSSimulator>, String> {
+pub async fn list_ios_simulators() -> Result<Vec<Device>, String> {
     #[cfg(not(target_os = "macos"))]
     {
         return Err("iOS simulators are only available on macOS".to_string());
@@ -22,7 +26,7 @@ pub async fn list_ios_simulators() -> Result<Vec<IOSSimulator>, String> {
 
     #[cfg(target_os = "macos")]
     {
-        let output = new_command("xcrun")
+        let output = xcrun()?
             .args(&["simctl", "list", "devices", "--json"])
             .output()
             .map_err(|e| format!("Failed to execute xcrun command: {}", e))?;
@@ -46,10 +50,11 @@ pub async fn list_ios_simulators() -> Result<Vec<IOSSimulator>, String> {
                             device["name"].as_str(),
                             device["state"].as_str(),
                         ) {
-                            let status = if state == "Booted" {
-                                "running"
-                            } else {
-                                "stopped"
+                            let status = match state {
+                                "Booted" => DeviceStatus::Running,
+                                "Booting" => DeviceStatus::Booting,
+                                "Shutting Down" => DeviceStatus::ShuttingDown,
+                                _ => DeviceStatus::Stopped,
                             };
 
                             let device_type = device["deviceTypeIdentifier"]
@@ -57,12 +62,13 @@ pub async fn list_ios_simulators() -> Result<Vec<IOSSimulator>, String> {
                                 .unwrap_or(name)
                                 .replace("com.apple.CoreSimulator.SimDeviceType.", "");
 
-                            simulators.push(IOSSimulator {
+                            simulators.push(Device {
                                 id: udid.to_string(),
                                 name: name.to_string(),
                                 device_type: device_type,
                                 os_version: runtime.replace("com.apple.CoreSimulator.SimRuntime.", ""),
-                                status: status.to_string(),
+                                status,
+                                platform: Platform::Ios,
                             });
                         }
                     }
@@ -84,7 +90,7 @@ pub async fn start_ios_simulator(id: String, app: tauri::AppHandle) -> Result<()
 
     #[cfg(target_os = "macos")]
     {
-        let mut boot_cmd = new_command("xcrun");
+        let mut boot_cmd = xcrun()?;
         boot_cmd.args(&["simctl", "boot", &id]);
         
         let _ = app.emit("add-log", serde_json::json!({
@@ -121,7 +127,7 @@ pub async fn stop_ios_simulator(id: String) -> Result<(), String> {
 
     #[cfg(target_os = "macos")]
     {
-        new_command("xcrun")
+        xcrun()?
             .args(&["simctl", "shutdown", &id])
             .output()
             .map_err(|e| format!("Failed to shutdown simulator: {}", e))?;
@@ -139,7 +145,7 @@ pub async fn delete_ios_simulator(id: String) -> Result<(), String> {
 
     #[cfg(target_os = "macos")]
     {
-        new_command("xcrun")
+        xcrun()?
             .args(&["simctl", "delete", &id])
             .output()
             .map_err(|e| format!("Failed to delete simulator: {}", e))?;
@@ -157,7 +163,7 @@ pub async fn wipe_ios_data(id: String) -> Result<(), String> {
 
     #[cfg(target_os = "macos")]
     {
-        new_command("xcrun")
+        xcrun()?
             .args(&["simctl", "erase", &id])
             .output()
             .map_err(|e| format!("Failed to erase simulator: {}", e))?;
@@ -183,7 +189,7 @@ pub async fn screenshot_ios(id: String) -> Result<String, String> {
             .ok_or_else(|| "Cannot find screenshot directory".to_string())?;
         let path = std::path::Path::new(&screenshot_dir).join(&filename);
 
-        let output = new_command("xcrun")
+        let output = xcrun()?
             .args(&["simctl", "io", &id, "screenshot", path.to_str().unwrap()])
             .output()
             .map_err(|e| format!("Failed to take screenshot: {}", e))?;
@@ -193,6 +199,80 @@ pub async fn screenshot_ios(id: String) -> Result<String, String> {
             return Err(format!("Screenshot failed: {}", stderr));
         }
 
-        Ok(path.to_string_lossy().to_string())
+        let device = list_ios_simulators()
+            .await
+            .ok()
+            .and_then(|devices| devices.into_iter().find(|d| d.id == id))
+            .map(|d| CaptureDevice {
+                model: Some(d.name),
+                os_version: Some(d.os_version),
+                screen_size: None,
+            })
+            .unwrap_or_default();
+        let path = path.to_string_lossy().to_string();
+        captures::record(CaptureKind::Screenshot, Platform::Ios, &id, &device, &path);
+        Ok(path)
+    }
+}
+
+async fn read_recent_logs(id: &str, lines: usize) -> Result<Vec<String>, String> {
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (id, lines);
+        return Err("iOS simulators are only available on macOS".to_string());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = xcrun()?
+            .args(&["simctl", "spawn", id, "log", "show", "--style", "compact", "--last", "5m"])
+            .output()
+            .map_err(|e| format!("Failed to read simulator logs: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to read simulator logs: {}", stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let all: Vec<&str> = stdout.lines().collect();
+        let start = all.len().saturating_sub(lines);
+        Ok(all[start..].iter().map(|line| line.to_string()).collect())
+    }
+}
+
+pub struct IosBackend;
+
+impl EmulatorBackend for IosBackend {
+    fn platform(&self) -> Platform {
+        Platform::Ios
+    }
+
+    async fn list(&self) -> Result<Vec<Device>, String> {
+        list_ios_simulators().await
+    }
+
+    async fn start(&self, id: &str, app: &tauri::AppHandle) -> Result<(), String> {
+        start_ios_simulator(id.to_string(), app.clone()).await
+    }
+
+    async fn stop(&self, id: &str) -> Result<(), String> {
+        stop_ios_simulator(id.to_string()).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), String> {
+        delete_ios_simulator(id.to_string()).await
+    }
+
+    async fn wipe(&self, id: &str, _app: &tauri::AppHandle) -> Result<(), String> {
+        wipe_ios_data(id.to_string()).await
+    }
+
+    async fn screenshot(&self, id: &str) -> Result<String, String> {
+        screenshot_ios(id.to_string()).await
+    }
+
+    async fn logs(&self, id: &str, lines: usize) -> Result<Vec<String>, String> {
+        read_recent_logs(id, lines).await
     }
 }
@@ -0,0 +1,40 @@
+//! 与平台无关的日志会话命令
+
+use crate::logs::recorder::{self, RecordOptions, RecordingInfo};
+use crate::logs::{self, SessionId, SessionInfo};
+
+/// 当前所有日志会话
+#[tauri::command]
+pub async fn list_log_sessions() -> Result<Vec<SessionInfo>, String> {
+    Ok(logs::list())
+}
+
+/// 前端处理完一批 `log-lines` 后确认，后端据此继续推送
+#[tauri::command]
+pub async fn ack_log_lines(session_id: SessionId) -> Result<(), String> {
+    if !logs::ack(session_id) {
+        return Err(format!("Log session {} not found", session_id));
+    }
+    Ok(())
+}
+
+/// 把正在运行的日志会话同时写入磁盘，返回保存目录
+#[tauri::command]
+pub async fn start_log_recording(session_id: SessionId, options: Option<RecordOptions>) -> Result<String, String> {
+    logs::start_recording(session_id, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn stop_log_recording(session_id: SessionId) -> Result<(), String> {
+    if !logs::stop_recording(session_id) {
+        return Err(format!("Log session {} not found", session_id));
+    }
+    Ok(())
+}
+
+/// 列出目录中的录制文件，未指定目录时使用默认目录
+#[tauri::command]
+pub async fn list_log_recordings(dir: Option<String>) -> Result<Vec<RecordingInfo>, String> {
+    let dir = recorder::recording_dir(dir.as_deref())?;
+    recorder::list_recordings(&dir)
+}
@@ -1,5 +1,9 @@
 pub mod android;
+pub mod captures;
+pub mod device;
 pub mod ios;
 pub mod harmony;
+pub mod logs;
+pub mod packages;
 pub mod settings;
 pub mod usb_device;
@@ -0,0 +1,240 @@
+//! 应用包管理的公共类型
+//!
+//! 已安装应用的操作统一为 [`PackageAction`]，由各平台后端翻译为 `pm` / `am` 或 `bm` / `aa` 命令。
+//!
+//! 安装过程通过 `package-install-progress` 事件推送，每个安装单元（单个包或一起安装的一组拆分包 / 模块）
+//! 依次经历 preparing -> installing -> completed / failed。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::Emitter;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+use super::device::Platform;
+
+/// 前端传入的安装选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct InstallOptions {
+    /// 覆盖安装已存在的应用
+    pub reinstall: bool,
+    /// 允许降级安装（仅 Android）
+    pub allow_downgrade: bool,
+    /// 安装时授予所有运行时权限（仅 Android）
+    pub grant_permissions: bool,
+    /// 把传入的多个 .apk / .hap 作为同一应用的拆分包或模块一起安装；否则逐个安装
+    pub same_app: bool,
+    /// 允许安装 testOnly 包，如 Android Studio 直接运行生成的 APK（仅 Android）
+    pub allow_test_packages: bool,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            reinstall: true,
+            allow_downgrade: false,
+            grant_permissions: false,
+            same_app: false,
+            allow_test_packages: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstallStage {
+    Preparing,
+    Installing,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallProgress {
+    pub platform: Platform,
+    pub device_id: String,
+    /// 当前安装单元包含的文件
+    pub paths: Vec<String>,
+    /// 当前单元的序号（从 0 开始）和单元总数
+    pub index: usize,
+    pub total: usize,
+    pub stage: InstallStage,
+    /// 安装工具输出的一行，或失败原因
+    pub message: Option<String>,
+}
+
+impl InstallProgress {
+    pub fn emit(&self, app: &tauri::AppHandle) {
+        let _ = app.emit("package-install-progress", self);
+    }
+}
+
+/// 单个安装单元的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallOutcome {
+    pub paths: Vec<String>,
+    pub success: bool,
+    /// 设备返回的错误码，如 `INSTALL_FAILED_VERSION_DOWNGRADE` 或 `9568322`
+    pub code: Option<String>,
+    /// 可读的错误说明
+    pub error: Option<String>,
+}
+
+impl InstallOutcome {
+    pub fn failed(paths: &[String], code: Option<String>, error: String) -> Self {
+        Self {
+            paths: paths.to_vec(),
+            success: false,
+            code,
+            error: Some(error),
+        }
+    }
+}
+
+/// 已安装的应用。列表只填写开销小的字段，其余字段由 `get_package_details` 按需读取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    pub is_system: bool,
+    pub version_name: Option<String>,
+    pub version_code: Option<u64>,
+    /// 设备本地时间，`YYYY-MM-DD HH:MM:SS`
+    pub first_install_time: Option<String>,
+    pub last_update_time: Option<String>,
+    pub min_sdk: Option<u32>,
+    pub target_sdk: Option<u32>,
+    /// APK / HAP 在设备上的路径
+    pub apk_path: Option<String>,
+    pub uid: Option<u32>,
+    /// 应用数据大小 (字节)
+    pub data_size: Option<u64>,
+    #[serde(default)]
+    pub requested_permissions: Vec<String>,
+    #[serde(default)]
+    pub granted_permissions: Vec<String>,
+}
+
+impl PackageInfo {
+    pub fn new(name: &str, is_system: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            is_system,
+            ..Default::default()
+        }
+    }
+}
+
+/// 对已安装应用执行的操作
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PackageAction {
+    Uninstall {
+        /// 保留应用数据和缓存
+        #[serde(default)]
+        keep_data: bool,
+    },
+    ClearData,
+    ForceStop,
+    /// 启动应用的默认入口
+    Launch,
+    Grant { permission: String },
+    Revoke { permission: String },
+}
+
+/// 执行命令并检查输出，`success` 判断合并后的 stdout / stderr 是否表示成功
+pub fn run_checked(
+    mut command: std::process::Command,
+    action: &str,
+    success: impl Fn(&str) -> bool,
+) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to {}: {}", action, e))?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !output.status.success() || !success(&text) {
+        return Err(format!("Failed to {}: {}", action, text.trim()));
+    }
+    Ok(text)
+}
+
+/// 按扩展名（不区分大小写）检查文件
+pub fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+static TEMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 安装过程中使用的临时目录，drop 时删除
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new() -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!(
+            "simhub_install_{}_{}",
+            std::process::id(),
+            TEMP_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 运行安装工具，逐行回调 stdout，返回 (是否成功退出, stdout + stderr 全文)
+pub async fn run_streaming(
+    command: std::process::Command,
+    mut on_line: impl FnMut(&str),
+) -> Result<(bool, String), String> {
+    let mut child = tokio::process::Command::from(command)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run installer: {}", e))?;
+
+    let stdout = child.stdout.take().ok_or("Failed to capture installer output")?;
+    let mut stderr = child.stderr.take().ok_or("Failed to capture installer output")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text).await;
+        text
+    });
+
+    let mut output = String::new();
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if !line.is_empty() {
+            on_line(line);
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to run installer: {}", e))?;
+    output.push_str(&stderr_task.await.unwrap_or_default());
+    Ok((status.success(), output))
+}
+
+/// 离线读取安装包（.apk / .apks / .hap / .hsp）的元数据，供安装前预览
+#[tauri::command]
+pub async fn inspect_package(path: String) -> Result<crate::inspect::PackageMetadata, String> {
+    tokio::task::spawn_blocking(move || crate::inspect::inspect(&path))
+        .await
+        .map_err(|e| format!("Failed to inspect package: {}", e))?
+}
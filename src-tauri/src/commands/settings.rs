@@ -242,74 +242,6 @@ pub fn get_harmony_image_location() -> Option<String> {
     None
 }
 
-pub fn get_harmony_emulator_path() -> Option<String> {
-    if let Ok(settings) = load_settings_from_file() {
-        // 优先使用用户配置的路径
-        if !settings.harmony_emulator_path.is_empty() {
-            return Some(settings.harmony_emulator_path);
-        }
-        // 否则从 DevEco Studio 路径拼接
-        if !settings.deveco_home.is_empty() {
-            let emulator_path = if cfg!(target_os = "macos") {
-                format!("{}/Contents/tools/emulator/Emulator", settings.deveco_home)
-            } else if cfg!(target_os = "windows") {
-                format!("{}/tools/emulator/Emulator.exe", settings.deveco_home)
-            } else {
-                format!("{}/tools/emulator/Emulator", settings.deveco_home)
-            };
-            return Some(emulator_path);
-        }
-    }
-    
-    // 从 get_deveco_home() 获取（含默认路径 fallback）
-    if let Some(deveco_home) = get_deveco_home() {
-        let emulator_path = if cfg!(target_os = "macos") {
-            format!("{}/Contents/tools/emulator/Emulator", deveco_home)
-        } else if cfg!(target_os = "windows") {
-            format!("{}/tools/emulator/Emulator.exe", deveco_home)
-        } else {
-            format!("{}/tools/emulator/Emulator", deveco_home)
-        };
-        return Some(emulator_path);
-    }
-    
-    None
-}
-
-pub fn get_harmony_hdc_path() -> Option<String> {
-    if let Ok(settings) = load_settings_from_file() {
-        // 优先使用用户配置的路径
-        if !settings.harmony_hdc_path.is_empty() {
-            return Some(settings.harmony_hdc_path);
-        }
-        // 否则从 DevEco Studio 路径拼接
-        if !settings.deveco_home.is_empty() {
-            let hdc_path = if cfg!(target_os = "macos") {
-                format!("{}/Contents/sdk/default/openharmony/toolchains/hdc", settings.deveco_home)
-            } else if cfg!(target_os = "windows") {
-                format!("{}/sdk/default/openharmony/toolchains/hdc.exe", settings.deveco_home)
-            } else {
-                format!("{}/sdk/default/openharmony/toolchains/hdc", settings.deveco_home)
-            };
-            return Some(hdc_path);
-        }
-    }
-    
-    // 从 get_deveco_home() 获取（含默认路径 fallback）
-    if let Some(deveco_home) = get_deveco_home() {
-        let hdc_path = if cfg!(target_os = "macos") {
-            format!("{}/Contents/sdk/default/openharmony/toolchains/hdc", deveco_home)
-        } else if cfg!(target_os = "windows") {
-            format!("{}/sdk/default/openharmony/toolchains/hdc.exe", deveco_home)
-        } else {
-            format!("{}/sdk/default/openharmony/toolchains/hdc", deveco_home)
-        };
-        return Some(hdc_path);
-    }
-    
-    None
-}
-
 #[tauri::command]
 pub async fn get_settings() -> Result<Settings, String> {
     let mut settings = load_settings_from_file()?;
@@ -350,10 +282,20 @@ pub async fn get_settings() -> Result<Settings, String> {
 #[tauri::command]
 pub async fn save_settings(settings: Settings) -> Result<(), String> {
     save_settings_to_file(&settings)?;
+    // 路径可能已变化，下次使用时重新定位工具
+    crate::toolchain::invalidate();
     Ok(())
 }
 
+/// 检查各 SDK 工具是否可用，返回缺失或配置错误的项目及原因
+#[tauri::command]
+pub async fn diagnose_environment() -> Result<crate::toolchain::EnvironmentReport, String> {
+    Ok(crate::toolchain::diagnose())
+}
+
+/// Android 模拟器启动参数；缺失的字段取默认值，兼容旧版本保存的文件
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EmulatorLaunchParams {
     pub no_window: bool,
     pub wipe_data: bool,
@@ -365,6 +307,19 @@ pub struct EmulatorLaunchParams {
     pub http_proxy: String,
     pub cores: Option<i32>,
     pub gpu: String,
+    /// 后置摄像头：emulated / virtualscene / webcamN / none
+    pub camera_back: String,
+    /// 网络速率预设（如 lte、full）或 "上行:下行" kbps
+    pub netspeed: String,
+    /// 网络延迟预设（如 umts、none）或 "最小:最大" 毫秒
+    pub netdelay: String,
+    pub read_only: bool,
+    /// 控制台端口，adb 端口为其 +1
+    pub port: Option<u16>,
+    /// 启用的 feature，以 "-" 开头表示禁用
+    pub features: Vec<String>,
+    /// 追加到命令行末尾的其他参数，支持引号
+    pub extra_args: String,
 }
 
 impl Default for EmulatorLaunchParams {
@@ -380,6 +335,13 @@ impl Default for EmulatorLaunchParams {
             http_proxy: String::new(),
             cores: None,
             gpu: "auto".to_string(),
+            camera_back: String::new(),
+            netspeed: String::new(),
+            netdelay: String::new(),
+            read_only: false,
+            port: None,
+            features: Vec::new(),
+            extra_args: String::new(),
         }
     }
 }
@@ -397,48 +359,82 @@ fn get_emulator_params_path(emulator_id: &str, emulator_type: &str) -> Result<Pa
     Ok(app_dir.join(filename))
 }
 
+/// 鸿蒙模拟器启动参数。内存、核数、分辨率写入实例目录下的 config.ini，其余作为命令行参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarmonyLaunchParams {
+    /// 内存大小 (MB)
+    pub memory: Option<i32>,
+    pub cores: Option<i32>,
+    /// 分辨率覆盖，需同时设置宽和高
+    pub resolution_width: Option<u32>,
+    pub resolution_height: Option<u32>,
+    /// 屏幕密度 (dpi)，仅在覆盖分辨率时生效
+    pub density: Option<u32>,
+    /// 无窗口启动，需要模拟器支持 -no-window
+    pub headless: bool,
+    /// 追加到命令行末尾的其他参数，支持引号
+    pub extra_args: String,
+}
+
+fn read_params<T: serde::de::DeserializeOwned + Default>(emulator_id: &str, emulator_type: &str) -> T {
+    let Ok(params_path) = get_emulator_params_path(emulator_id, emulator_type) else {
+        return T::default();
+    };
+
+    fs::read_to_string(&params_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(params).map_err(|e| format!("Invalid launch params: {}", e))
+}
+
+/// 按模拟器类型返回对应的参数结构（android: EmulatorLaunchParams，harmony: HarmonyLaunchParams）
 #[tauri::command]
-pub async fn get_emulator_launch_params(emulator_id: String, emulator_type: String) -> Result<EmulatorLaunchParams, String> {
-    let params_path = get_emulator_params_path(&emulator_id, &emulator_type)?;
-    
-    if params_path.exists() {
-        let content = fs::read_to_string(&params_path)
-            .map_err(|e| format!("Failed to read emulator params file: {}", e))?;
-        let params: EmulatorLaunchParams = serde_json::from_str(&content)
-            .unwrap_or_default();
-        Ok(params)
-    } else {
-        Ok(EmulatorLaunchParams::default())
-    }
+pub async fn get_emulator_launch_params(emulator_id: String, emulator_type: String) -> Result<serde_json::Value, String> {
+    let value = match emulator_type.as_str() {
+        "android" => serde_json::to_value(get_emulator_launch_params_sync(&emulator_id, &emulator_type)),
+        "harmony" => serde_json::to_value(get_harmony_launch_params_sync(&emulator_id)),
+        _ => return Err(format!("Launch params are not supported for emulator type: {}", emulator_type)),
+    };
+    value.map_err(|e| format!("Failed to serialize params: {}", e))
 }
 
 #[tauri::command]
 pub async fn save_emulator_launch_params(
     emulator_id: String,
     emulator_type: String,
-    params: EmulatorLaunchParams
+    params: serde_json::Value
 ) -> Result<(), String> {
+    // 提前校验，避免保存无法启动的参数
+    let content = match emulator_type.as_str() {
+        "android" => {
+            let params: EmulatorLaunchParams = parse_params(params)?;
+            crate::commands::android::launch_args(&params)?;
+            serde_json::to_string_pretty(&params)
+        }
+        "harmony" => {
+            let params: HarmonyLaunchParams = parse_params(params)?;
+            crate::commands::harmony::validate_launch_params(&params)?;
+            serde_json::to_string_pretty(&params)
+        }
+        _ => return Err(format!("Launch params are not supported for emulator type: {}", emulator_type)),
+    }
+    .map_err(|e| format!("Failed to serialize params: {}", e))?;
+
     let params_path = get_emulator_params_path(&emulator_id, &emulator_type)?;
-    let content = serde_json::to_string_pretty(&params)
-        .map_err(|e| format!("Failed to serialize params: {}", e))?;
     fs::write(&params_path, content)
         .map_err(|e| format!("Failed to write params file: {}", e))?;
     Ok(())
 }
 
 pub fn get_emulator_launch_params_sync(emulator_id: &str, emulator_type: &str) -> EmulatorLaunchParams {
-    let params_path = match get_emulator_params_path(emulator_id, emulator_type) {
-        Ok(path) => path,
-        Err(_) => return EmulatorLaunchParams::default(),
-    };
-    
-    if params_path.exists() {
-        if let Ok(content) = fs::read_to_string(&params_path) {
-            if let Ok(params) = serde_json::from_str::<EmulatorLaunchParams>(&content) {
-                return params;
-            }
-        }
-    }
-    
-    EmulatorLaunchParams::default()
+    read_params(emulator_id, emulator_type)
+}
+
+pub fn get_harmony_launch_params_sync(emulator_id: &str) -> HarmonyLaunchParams {
+    read_params(emulator_id, "harmony")
 }
@@ -1,117 +1,56 @@
-use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
 
-const MAX_DEVICES: usize = 64;
+use crate::usb::watcher::{UsbEvent, UsbWatcher, DEFAULT_POLL_INTERVAL};
+pub use crate::usb::UsbDevice;
 
-const DEV_ANDROID: i32 = 0;
-const DEV_IOS: i32 = 1;
-const DEV_HARMONY: i32 = 2;
+static USB_WATCHER: Mutex<Option<UsbWatcher>> = Mutex::new(None);
 
-/// 与 C 侧 CDeviceInfo 结构体内存布局一致
-#[repr(C)]
-struct CDeviceInfo {
-    device_type: i32,
-    name: [u8; 256],
-    serial: [u8; 256],
-    brand: [u8; 256],
-    vendor_id: [u8; 8],
-    product_id: [u8; 8],
-    usb_debugging: bool,
-    trusted: bool,
-}
-
-extern "C" {
-    fn c_scan_usb_devices(devices: *mut CDeviceInfo, max_devices: i32) -> i32;
-}
-
-/// 从 C 字符数组提取字符串，找到第一个 \0 截断
-fn c_buf_to_bytes(buf: &[u8]) -> &[u8] {
-    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
-    &buf[..len]
+#[tauri::command]
+pub async fn list_usb_devices() -> Result<Vec<UsbDevice>, String> {
+    let scanner = crate::usb::default_scanner()?;
+    crate::usb::scan_mobile_devices(scanner.as_ref())
 }
 
-/// Windows ANSI (GBK) → UTF-8，macOS/其他平台直接当 UTF-8
-fn decode_c_string(buf: &[u8]) -> String {
-    let bytes = c_buf_to_bytes(buf);
-    if bytes.is_empty() {
-        return String::new();
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        // Windows 的 A 系列 API 返回系统本地编码（中文系统是 GBK）
-        let (decoded, _, _) = encoding_rs::GBK.decode(bytes);
-        decoded.into_owned()
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        String::from_utf8_lossy(bytes).into_owned()
+/// 启动 USB 热插拔监听，设备变化时推送 `usb-device-attached` / `usb-device-detached` /
+/// `usb-device-changed` 事件
+#[tauri::command]
+pub async fn start_usb_watcher(interval_ms: Option<u64>, app: tauri::AppHandle) -> Result<(), String> {
+    let mut watcher = USB_WATCHER.lock().unwrap();
+    if watcher.as_ref().is_some_and(|w| w.is_running()) {
+        return Ok(());
     }
-}
 
-/// 纯 ASCII 字段（VID/PID/serial），直接当 UTF-8 解析
-fn decode_ascii_string(buf: &[u8]) -> String {
-    let bytes = c_buf_to_bytes(buf);
-    String::from_utf8_lossy(bytes).into_owned()
-}
+    let scanner = crate::usb::default_scanner()?;
+    let interval = interval_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_POLL_INTERVAL);
+
+    *watcher = Some(UsbWatcher::start(scanner, interval, move |event| {
+        let _ = match event {
+            UsbEvent::Attached(device) => app.emit("usb-device-attached", device),
+            UsbEvent::Detached(device) => {
+                // 设备拔出后其日志进程不会再有输出，直接结束
+                crate::logs::stop_device(&device.serial);
+                app.emit("usb-device-detached", device)
+            }
+            // 授权状态等变化时设备没有断开，日志会话继续
+            UsbEvent::Changed(device) => app.emit("usb-device-changed", device),
+        };
+    }));
 
-#[derive(Debug, Serialize, Clone)]
-pub struct UsbDevice {
-    #[serde(rename = "type")]
-    pub device_type: String,
-    pub name: String,
-    pub serial: String,
-    pub brand: String,
-    pub vendor_id: String,
-    pub product_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub usb_debugging: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub trusted: Option<bool>,
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn list_usb_devices() -> Result<Vec<UsbDevice>, String> {
-    let mut c_devices: Vec<CDeviceInfo> = Vec::with_capacity(MAX_DEVICES);
-    for _ in 0..MAX_DEVICES {
-        c_devices.push(unsafe { std::mem::zeroed() });
-    }
-
-    let count = unsafe { c_scan_usb_devices(c_devices.as_mut_ptr(), MAX_DEVICES as i32) };
-
-    if count < 0 {
-        return Err("USB scan failed".to_string());
+pub async fn stop_usb_watcher() -> Result<(), String> {
+    let watcher = USB_WATCHER.lock().unwrap().take();
+    if let Some(mut watcher) = watcher {
+        // stop 会等待扫描线程退出，不阻塞异步运行时
+        tokio::task::spawn_blocking(move || watcher.stop())
+            .await
+            .map_err(|e| format!("Failed to stop USB watcher: {}", e))?;
     }
-
-    let mut devices = Vec::new();
-    for i in 0..count as usize {
-        let c = &c_devices[i];
-        let type_str = match c.device_type {
-            DEV_ANDROID => "android",
-            DEV_IOS => "ios",
-            DEV_HARMONY => "harmony",
-            _ => "unknown",
-        };
-
-        devices.push(UsbDevice {
-            device_type: type_str.to_string(),
-            name: decode_c_string(&c.name),
-            serial: decode_ascii_string(&c.serial),
-            brand: decode_c_string(&c.brand),
-            vendor_id: decode_ascii_string(&c.vendor_id),
-            product_id: decode_ascii_string(&c.product_id),
-            usb_debugging: if c.device_type == DEV_IOS {
-                None
-            } else {
-                Some(c.usb_debugging)
-            },
-            trusted: if c.device_type == DEV_IOS {
-                Some(c.trusted)
-            } else {
-                None
-            },
-        });
-    }
-
-    Ok(devices)
+    Ok(())
 }
@@ -0,0 +1,340 @@
+//! 截图与录屏的历史记录
+
+use rusqlite::{params, params_from_iter, Result, Row};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureKind {
+    Screenshot,
+    Recording,
+}
+
+impl CaptureKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            CaptureKind::Screenshot => "screenshot",
+            CaptureKind::Recording => "recording",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Capture {
+    pub id: i64,
+    pub kind: CaptureKind,
+    pub platform: String,
+    pub device_id: String,
+    pub device_model: Option<String>,
+    pub os_version: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub path: String,
+    /// Unix 时间戳 (秒)
+    pub created_at: i64,
+    pub tags: Vec<String>,
+}
+
+/// 新增记录时的字段
+pub struct NewCapture<'a> {
+    pub kind: CaptureKind,
+    pub platform: &'a str,
+    pub device_id: &'a str,
+    pub device_model: Option<&'a str>,
+    pub os_version: Option<&'a str>,
+    pub resolution: Option<(u32, u32)>,
+    pub path: &'a str,
+}
+
+/// 查询条件，均为可选
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CaptureQuery {
+    /// 按空格分词，每个词须匹配设备、型号、系统版本、文件路径或标签之一
+    pub text: Option<String>,
+    pub kind: Option<CaptureKind>,
+    pub platform: Option<String>,
+    pub device_id: Option<String>,
+    pub tag: Option<String>,
+    /// 起止时间 (Unix 秒)
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+const DEFAULT_LIMIT: u32 = 200;
+
+const SELECT_CAPTURE: &str = "SELECT c.id, c.kind, c.platform, c.device_id, c.device_model, c.os_version,
+        c.width, c.height, c.path, c.created_at,
+        (SELECT group_concat(tag, char(10)) FROM capture_tags t WHERE t.capture_id = c.id)
+    FROM captures c";
+
+fn capture_from_row(row: &Row) -> Result<Capture> {
+    let kind: String = row.get(1)?;
+    let tags: Option<String> = row.get(10)?;
+    let mut tags: Vec<String> = tags
+        .map(|tags| tags.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort();
+    Ok(Capture {
+        id: row.get(0)?,
+        kind: if kind == "recording" { CaptureKind::Recording } else { CaptureKind::Screenshot },
+        platform: row.get(2)?,
+        device_id: row.get(3)?,
+        device_model: row.get(4)?,
+        os_version: row.get(5)?,
+        width: row.get(6)?,
+        height: row.get(7)?,
+        path: row.get(8)?,
+        created_at: row.get(9)?,
+        tags,
+    })
+}
+
+impl Database {
+    pub(super) fn init_capture_tables(&self) -> Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS captures (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                device_id TEXT NOT NULL,
+                device_model TEXT,
+                os_version TEXT,
+                width INTEGER,
+                height INTEGER,
+                path TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_captures_created_at ON captures (created_at)",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS capture_tags (
+                capture_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (capture_id, tag)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn insert_capture(&self, capture: &NewCapture) -> Result<i64> {
+        let (width, height) = capture.resolution.unzip();
+        self.conn.execute(
+            "INSERT INTO captures (kind, platform, device_id, device_model, os_version, width, height, path, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                capture.kind.as_str(),
+                capture.platform,
+                capture.device_id,
+                capture.device_model,
+                capture.os_version,
+                width,
+                height,
+                capture.path,
+                chrono::Local::now().timestamp(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_capture(&self, id: i64) -> Result<Option<Capture>> {
+        let mut stmt = self.conn.prepare(&format!("{} WHERE c.id = ?", SELECT_CAPTURE))?;
+        let mut rows = stmt.query([id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(capture_from_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 按时间倒序返回符合条件的记录
+    pub fn list_captures(&self, query: &CaptureQuery) -> Result<Vec<Capture>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        for word in query.text.as_deref().unwrap_or("").split_whitespace() {
+            conditions.push(
+                "(c.device_id LIKE ?1 ESCAPE '\\' OR c.device_model LIKE ?1 ESCAPE '\\'
+                  OR c.os_version LIKE ?1 ESCAPE '\\' OR c.path LIKE ?1 ESCAPE '\\'
+                  OR EXISTS (SELECT 1 FROM capture_tags t WHERE t.capture_id = c.id AND t.tag LIKE ?1 ESCAPE '\\'))"
+                    .replace("?1", &format!("?{}", values.len() + 1)),
+            );
+            values.push(format!("%{}%", escape_like(word)).into());
+        }
+        if let Some(kind) = query.kind {
+            conditions.push(format!("c.kind = ?{}", values.len() + 1));
+            values.push(kind.as_str().to_string().into());
+        }
+        if let Some(platform) = &query.platform {
+            conditions.push(format!("c.platform = ?{}", values.len() + 1));
+            values.push(platform.clone().into());
+        }
+        if let Some(device_id) = &query.device_id {
+            conditions.push(format!("c.device_id = ?{}", values.len() + 1));
+            values.push(device_id.clone().into());
+        }
+        if let Some(tag) = &query.tag {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM capture_tags t WHERE t.capture_id = c.id AND t.tag = ?{})",
+                values.len() + 1
+            ));
+            values.push(tag.clone().into());
+        }
+        if let Some(since) = query.since {
+            conditions.push(format!("c.created_at >= ?{}", values.len() + 1));
+            values.push(since.into());
+        }
+        if let Some(until) = query.until {
+            conditions.push(format!("c.created_at < ?{}", values.len() + 1));
+            values.push(until.into());
+        }
+
+        let mut sql = SELECT_CAPTURE.to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(&format!(
+            " ORDER BY c.created_at DESC, c.id DESC LIMIT {} OFFSET {}",
+            query.limit.unwrap_or(DEFAULT_LIMIT),
+            query.offset.unwrap_or(0)
+        ));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), capture_from_row)?;
+        rows.collect()
+    }
+
+    /// 用给定的标签替换记录原有的标签
+    pub fn set_capture_tags(&mut self, id: i64, tags: &[String]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM capture_tags WHERE capture_id = ?", [id])?;
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            tx.execute(
+                "INSERT OR IGNORE INTO capture_tags (capture_id, tag) VALUES (?, ?)",
+                params![id, tag],
+            )?;
+        }
+        tx.commit()
+    }
+
+    pub fn delete_capture(&mut self, id: i64) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM capture_tags WHERE capture_id = ?", [id])?;
+        tx.execute("DELETE FROM captures WHERE id = ?", [id])?;
+        tx.commit()
+    }
+}
+
+fn escape_like(word: &str) -> String {
+    word.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(db: &Database, kind: CaptureKind, platform: &str, device_id: &str, model: Option<&str>, path: &str) -> i64 {
+        db.insert_capture(&NewCapture {
+            kind,
+            platform,
+            device_id,
+            device_model: model,
+            os_version: Some("14"),
+            resolution: Some((1080, 2400)),
+            path,
+        })
+        .unwrap()
+    }
+
+    /// 三条记录：Pixel 截图、Pixel 录屏、Mate 截图
+    fn sample() -> (Database, [i64; 3]) {
+        let db = Database::in_memory().unwrap();
+        let ids = [
+            insert(&db, CaptureKind::Screenshot, "android", "emulator-5554", Some("Pixel 7"), "/shots/login_100%.png"),
+            insert(&db, CaptureKind::Recording, "android", "emulator-5554", Some("Pixel 7"), "/shots/checkout.mp4"),
+            insert(&db, CaptureKind::Screenshot, "harmony", "127.0.0.1:5555", Some("Mate 60"), "/shots/home.png"),
+        ];
+        (db, ids)
+    }
+
+    fn ids(db: &Database, query: CaptureQuery) -> Vec<i64> {
+        db.list_captures(&query).unwrap().into_iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn inserts_and_reads_back() {
+        let (db, [first, ..]) = sample();
+        let capture = db.get_capture(first).unwrap().unwrap();
+        assert_eq!(capture.kind, CaptureKind::Screenshot);
+        assert_eq!(capture.platform, "android");
+        assert_eq!(capture.device_id, "emulator-5554");
+        assert_eq!(capture.device_model.as_deref(), Some("Pixel 7"));
+        assert_eq!(capture.os_version.as_deref(), Some("14"));
+        assert_eq!((capture.width, capture.height), (Some(1080), Some(2400)));
+        assert_eq!(capture.path, "/shots/login_100%.png");
+        assert!(capture.tags.is_empty());
+        assert!(db.get_capture(first + 100).unwrap().is_none());
+    }
+
+    #[test]
+    fn filters_by_fields() {
+        let (db, [shot, recording, harmony]) = sample();
+        // 同一秒内插入，按 id 倒序
+        assert_eq!(ids(&db, CaptureQuery::default()), [harmony, recording, shot]);
+        assert_eq!(ids(&db, CaptureQuery { kind: Some(CaptureKind::Recording), ..Default::default() }), [recording]);
+        assert_eq!(ids(&db, CaptureQuery { platform: Some("harmony".into()), ..Default::default() }), [harmony]);
+        assert_eq!(
+            ids(&db, CaptureQuery { device_id: Some("emulator-5554".into()), ..Default::default() }),
+            [recording, shot]
+        );
+        assert_eq!(ids(&db, CaptureQuery { limit: Some(1), offset: Some(1), ..Default::default() }), [recording]);
+
+        let now = chrono::Local::now().timestamp();
+        assert!(ids(&db, CaptureQuery { since: Some(now + 60), ..Default::default() }).is_empty());
+        assert_eq!(ids(&db, CaptureQuery { until: Some(now + 60), ..Default::default() }).len(), 3);
+    }
+
+    #[test]
+    fn searches_text_and_tags() {
+        let (mut db, [shot, recording, harmony]) = sample();
+        db.set_capture_tags(recording, &["bug-123".into(), " checkout ".into(), "".into()]).unwrap();
+        assert_eq!(db.get_capture(recording).unwrap().unwrap().tags, ["bug-123", "checkout"]);
+
+        let search = |db: &Database, text: &str| ids(db, CaptureQuery { text: Some(text.into()), ..Default::default() });
+        assert_eq!(search(&db, "pixel"), [recording, shot]);
+        assert_eq!(search(&db, "5555"), [harmony]);
+        assert_eq!(search(&db, "bug-123"), [recording]);
+        // 多个词须同时匹配
+        assert_eq!(search(&db, "pixel png"), [shot]);
+        // LIKE 通配符按字面匹配
+        assert_eq!(search(&db, "100%"), [shot]);
+        assert!(search(&db, "_%").is_empty());
+
+        assert_eq!(ids(&db, CaptureQuery { tag: Some("checkout".into()), ..Default::default() }), [recording]);
+        db.set_capture_tags(recording, &[]).unwrap();
+        assert!(ids(&db, CaptureQuery { tag: Some("checkout".into()), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn deletes_capture_and_tags() {
+        let (mut db, [shot, ..]) = sample();
+        db.set_capture_tags(shot, &["keep".into()]).unwrap();
+        db.delete_capture(shot).unwrap();
+        assert!(db.get_capture(shot).unwrap().is_none());
+        assert!(ids(&db, CaptureQuery { tag: Some("keep".into()), ..Default::default() }).is_empty());
+    }
+}
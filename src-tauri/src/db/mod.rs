@@ -1,5 +1,34 @@
 use rusqlite::{Connection, Result};
 use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub mod captures;
+
+lazy_static::lazy_static! {
+    /// 应用数据库，首次使用时打开
+    static ref DATABASE: Mutex<Option<Database>> = Mutex::new(None);
+}
+
+fn database_path() -> std::result::Result<PathBuf, String> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| "Failed to get config directory".to_string())?;
+    let app_dir = config_dir.join("SimHub");
+    std::fs::create_dir_all(&app_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    Ok(app_dir.join("simhub.db"))
+}
+
+/// 在全局数据库连接上执行操作
+pub fn with_database<T>(f: impl FnOnce(&mut Database) -> Result<T>) -> std::result::Result<T, String> {
+    let mut database = DATABASE.lock().unwrap();
+    if database.is_none() {
+        let db = Database::new(database_path()?)
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+        *database = Some(db);
+    }
+    let db = database.as_mut().expect("database initialized above");
+    f(db).map_err(|e| format!("Database error: {}", e))
+}
 
 pub struct Database {
     conn: Connection,
@@ -13,6 +42,14 @@ impl Database {
         Ok(db)
     }
 
+    /// 内存数据库，用于测试
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self> {
+        let db = Database { conn: Connection::open_in_memory()? };
+        db.init_tables()?;
+        Ok(db)
+    }
+
     fn init_tables(&self) -> Result<()> {
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS emulators (
@@ -37,6 +74,8 @@ impl Database {
             [],
         )?;
 
+        self.init_capture_tables()?;
+
         Ok(())
     }
 
@@ -0,0 +1,252 @@
+//! 二进制 XML（AXML）解析，只读取 `AndroidManifest.xml` 中需要的信息
+//!
+//! 文件由若干 chunk 组成：字符串池、资源 id 表，以及开始 / 结束元素。
+//! 混淆过的清单中属性名可能为空字符串，此时按资源 id 识别属性。
+
+use std::collections::HashMap;
+
+const CHUNK_STRING_POOL: u16 = 0x0001;
+const CHUNK_XML: u16 = 0x0003;
+const CHUNK_RESOURCE_MAP: u16 = 0x0180;
+const CHUNK_START_ELEMENT: u16 = 0x0102;
+const CHUNK_END_ELEMENT: u16 = 0x0103;
+
+const UTF8_FLAG: u32 = 0x100;
+const NO_INDEX: u32 = 0xFFFF_FFFF;
+
+const TYPE_REFERENCE: u8 = 0x01;
+const TYPE_STRING: u8 = 0x03;
+const TYPE_INT_DEC: u8 = 0x10;
+const TYPE_INT_HEX: u8 = 0x11;
+const TYPE_BOOLEAN: u8 = 0x12;
+
+/// android: 命名空间下常用属性的资源 id
+const ATTR_IDS: [(u32, &str); 7] = [
+    (0x0101_000e, "enabled"),
+    (0x0101_0003, "name"),
+    (0x0101_020c, "minSdkVersion"),
+    (0x0101_021b, "versionCode"),
+    (0x0101_021c, "versionName"),
+    (0x0101_0270, "targetSdkVersion"),
+    (0x0101_0202, "targetActivity"),
+];
+
+#[derive(Debug, Clone)]
+pub enum AttrValue {
+    String(String),
+    Int(u32),
+    Bool(bool),
+    /// 资源引用，需要 resources.arsc 才能解析
+    Reference(u32),
+}
+
+impl AttrValue {
+    /// 字符串形式；资源引用显示为 `@0x7f0e001b`
+    pub fn as_string(&self) -> String {
+        match self {
+            AttrValue::String(s) => s.clone(),
+            AttrValue::Int(v) => v.to_string(),
+            AttrValue::Bool(b) => b.to_string(),
+            AttrValue::Reference(id) => format!("@0x{:08x}", id),
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            AttrValue::Int(v) => Some(*v as u64),
+            AttrValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Start { name: String, attrs: HashMap<String, AttrValue> },
+    End { name: String },
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn u8(&self, offset: usize) -> Result<u8, String> {
+        self.data.get(offset).copied().ok_or_else(truncated)
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, String> {
+        let bytes = self.data.get(offset..offset + 2).ok_or_else(truncated)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, String> {
+        let bytes = self.data.get(offset..offset + 4).ok_or_else(truncated)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn truncated() -> String {
+    "Invalid binary XML: unexpected end of data".to_string()
+}
+
+fn read_strings(reader: &Reader, chunk: usize) -> Result<Vec<String>, String> {
+    let count = reader.u32(chunk + 8)? as usize;
+    let flags = reader.u32(chunk + 16)?;
+    let strings_start = chunk + reader.u32(chunk + 20)? as usize;
+    let header_size = reader.u16(chunk + 2)? as usize;
+    let utf8 = flags & UTF8_FLAG != 0;
+
+    let mut strings = Vec::with_capacity(count);
+    for i in 0..count {
+        let offset = strings_start + reader.u32(chunk + header_size + i * 4)? as usize;
+        let string = if utf8 {
+            // 先是字符数，再是字节数，各占 1 或 2 字节
+            let skip = if reader.u8(offset)? & 0x80 != 0 { 2 } else { 1 };
+            let mut pos = offset + skip;
+            let mut len = reader.u8(pos)? as usize;
+            if len & 0x80 != 0 {
+                len = ((len & 0x7F) << 8) | reader.u8(pos + 1)? as usize;
+                pos += 2;
+            } else {
+                pos += 1;
+            }
+            let bytes = reader.data.get(pos..pos + len).ok_or_else(truncated)?;
+            String::from_utf8_lossy(bytes).to_string()
+        } else {
+            let mut pos = offset;
+            let mut len = reader.u16(pos)? as usize;
+            if len & 0x8000 != 0 {
+                len = ((len & 0x7FFF) << 16) | reader.u16(pos + 2)? as usize;
+                pos += 4;
+            } else {
+                pos += 2;
+            }
+            let units = (0..len)
+                .map(|j| reader.u16(pos + j * 2))
+                .collect::<Result<Vec<u16>, String>>()?;
+            String::from_utf16_lossy(&units)
+        };
+        strings.push(string);
+    }
+    Ok(strings)
+}
+
+/// 解析为开始 / 结束元素的序列
+pub fn parse(data: &[u8]) -> Result<Vec<Event>, String> {
+    let reader = Reader { data };
+    if reader.u16(0)? != CHUNK_XML {
+        return Err("Not a binary XML file".to_string());
+    }
+
+    let mut strings: Vec<String> = Vec::new();
+    let mut resource_ids: Vec<u32> = Vec::new();
+    let mut events = Vec::new();
+
+    let string = |strings: &[String], index: u32| -> String {
+        if index == NO_INDEX {
+            String::new()
+        } else {
+            strings.get(index as usize).cloned().unwrap_or_default()
+        }
+    };
+
+    let mut offset = reader.u16(2)? as usize;
+    while offset + 8 <= data.len() {
+        let chunk_type = reader.u16(offset)?;
+        let header_size = reader.u16(offset + 2)? as usize;
+        let size = reader.u32(offset + 4)? as usize;
+        if size < 8 || header_size < 8 || header_size > size || offset + size > data.len() {
+            return Err("Invalid binary XML: bad chunk size".to_string());
+        }
+
+        match chunk_type {
+            CHUNK_STRING_POOL => strings = read_strings(&reader, offset)?,
+            CHUNK_RESOURCE_MAP => {
+                resource_ids = (0..(size - header_size) / 4)
+                    .map(|i| reader.u32(offset + header_size + i * 4))
+                    .collect::<Result<_, _>>()?;
+            }
+            CHUNK_START_ELEMENT => {
+                let ext = offset + header_size;
+                let name = string(&strings, reader.u32(ext + 4)?);
+                let attr_start = reader.u16(ext + 8)? as usize;
+                let attr_size = reader.u16(ext + 10)? as usize;
+                let attr_count = reader.u16(ext + 12)? as usize;
+
+                let mut attrs = HashMap::new();
+                for i in 0..attr_count {
+                    let attr = ext + attr_start + i * attr_size;
+                    let name_index = reader.u32(attr + 4)?;
+                    let raw_value = reader.u32(attr + 8)?;
+                    let data_type = reader.u8(attr + 15)?;
+                    let value_data = reader.u32(attr + 16)?;
+
+                    let mut attr_name = string(&strings, name_index);
+                    if let Some((_, known)) = resource_ids
+                        .get(name_index as usize)
+                        .and_then(|id| ATTR_IDS.iter().find(|(attr_id, _)| attr_id == id))
+                    {
+                        attr_name = known.to_string();
+                    }
+                    let value = match data_type {
+                        TYPE_STRING => AttrValue::String(string(&strings, value_data)),
+                        TYPE_INT_DEC | TYPE_INT_HEX => AttrValue::Int(value_data),
+                        TYPE_BOOLEAN => AttrValue::Bool(value_data != 0),
+                        TYPE_REFERENCE => AttrValue::Reference(value_data),
+                        _ if raw_value != NO_INDEX => AttrValue::String(string(&strings, raw_value)),
+                        _ => AttrValue::Int(value_data),
+                    };
+                    attrs.insert(attr_name, value);
+                }
+                events.push(Event::Start { name, attrs });
+            }
+            CHUNK_END_ELEMENT => {
+                let name = string(&strings, reader.u32(offset + header_size + 4)?);
+                events.push(Event::End { name });
+            }
+            _ => {}
+        }
+        offset += size;
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XML 文件头后跟一个 chunk
+    fn document(chunk_type: u16, header_size: u16, size: u32, body: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(CHUNK_XML.to_le_bytes());
+        data.extend(8u16.to_le_bytes());
+        data.extend((16 + body.len() as u32).to_le_bytes());
+        data.extend(chunk_type.to_le_bytes());
+        data.extend(header_size.to_le_bytes());
+        data.extend(size.to_le_bytes());
+        data.extend(body);
+        data
+    }
+
+    #[test]
+    fn rejects_chunk_header_larger_than_chunk() {
+        let data = document(CHUNK_RESOURCE_MAP, 16, 8, &[]);
+        let error = parse(&data).unwrap_err();
+        assert!(error.starts_with("Invalid binary XML"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_chunk_past_end_of_data() {
+        let data = document(CHUNK_RESOURCE_MAP, 8, 64, &[0; 8]);
+        assert!(parse(&data).unwrap_err().starts_with("Invalid binary XML"));
+        assert!(parse(&document(CHUNK_END_ELEMENT, 4, 16, &[0; 8])).is_err());
+    }
+
+    #[test]
+    fn reads_resource_map() {
+        let data = document(CHUNK_RESOURCE_MAP, 8, 16, &[0x03, 0x00, 0x01, 0x01, 0x1b, 0x02, 0x01, 0x01]);
+        assert!(parse(&data).unwrap().is_empty());
+        assert!(parse(&[0x00, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! HAP / HSP 元数据，读取 `module.json`，没有时回退到 `pack.info`
+
+use serde_json::Value;
+use std::io::{Read, Seek};
+
+use super::{native_abis, PackageFormat, PackageMetadata};
+
+fn read_json<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Option<Value> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut text = String::new();
+    entry.read_to_string(&mut text).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// 数字或数字字符串
+fn num_field(value: &Value, key: &str) -> Option<u64> {
+    let field = value.get(key)?;
+    field.as_u64().or_else(|| field.as_str()?.trim().parse().ok())
+}
+
+/// API 版本可能编码为 `主版本 * 10^6 + 次版本 * 1000 + API 级别`（如 50000012 表示 API 12），只取 API 级别
+fn api_level(value: u64) -> u32 {
+    if value > 1000 {
+        (value % 1000) as u32
+    } else {
+        value as u32
+    }
+}
+
+/// 带有桌面入口 skill 的 ability，否则为模块的 mainElement
+fn launch_ability(module: &Value) -> Option<String> {
+    let is_home = |skill: &Value| {
+        let has = |key: &str, expected: &[&str]| {
+            skill
+                .get(key)
+                .and_then(Value::as_array)
+                .is_some_and(|items| items.iter().any(|i| i.as_str().is_some_and(|s| expected.contains(&s))))
+        };
+        has("entities", &["entity.system.home"]) && has("actions", &["action.system.home", "ohos.want.action.home"])
+    };
+    module
+        .get("abilities")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|ability| {
+            ability
+                .get("skills")
+                .and_then(Value::as_array)
+                .is_some_and(|skills| skills.iter().any(is_home))
+        })
+        .and_then(|ability| str_field(ability, "name"))
+        .or_else(|| str_field(module, "mainElement"))
+}
+
+pub fn inspect<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    format: PackageFormat,
+) -> Result<PackageMetadata, String> {
+    let mut metadata = PackageMetadata::new(format);
+    metadata.abis = native_abis(archive, "libs/");
+
+    if let Some(json) = read_json(archive, "module.json") {
+        let app = json.get("app").cloned().unwrap_or_default();
+        let module = json.get("module").cloned().unwrap_or_default();
+        metadata.package = str_field(&app, "bundleName").unwrap_or_default();
+        metadata.version_name = str_field(&app, "versionName");
+        metadata.version_code = num_field(&app, "versionCode");
+        metadata.min_sdk = num_field(&app, "minAPIVersion").map(api_level);
+        metadata.target_sdk = num_field(&app, "targetAPIVersion").map(api_level);
+        metadata.module_name = str_field(&module, "name");
+        metadata.module_type = str_field(&module, "type");
+        metadata.permissions = module
+            .get("requestPermissions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|p| str_field(p, "name"))
+            .collect();
+        if format == PackageFormat::Hap {
+            metadata.launchable = launch_ability(&module);
+        }
+    } else if let Some(json) = read_json(archive, "pack.info") {
+        // pack.info 只有概要信息，没有权限列表
+        let summary = json.get("summary").cloned().unwrap_or_default();
+        let app = summary.get("app").cloned().unwrap_or_default();
+        let version = app.get("version").cloned().unwrap_or_default();
+        metadata.package = str_field(&app, "bundleName").unwrap_or_default();
+        metadata.version_name = str_field(&version, "name");
+        metadata.version_code = num_field(&version, "code");
+
+        if let Some(module) = summary.get("modules").and_then(Value::as_array).and_then(|m| m.first()) {
+            let api = module.get("apiVersion").cloned().unwrap_or_default();
+            let distro = module.get("distro").cloned().unwrap_or_default();
+            metadata.min_sdk = num_field(&api, "compatible").map(api_level);
+            metadata.target_sdk = num_field(&api, "target").map(api_level);
+            metadata.module_name = str_field(&distro, "moduleName");
+            metadata.module_type = str_field(&distro, "moduleType");
+            if format == PackageFormat::Hap {
+                metadata.launchable = str_field(module, "mainAbility");
+            }
+        }
+    } else {
+        return Err("Invalid HAP: module.json and pack.info not found".to_string());
+    }
+
+    if metadata.package.is_empty() {
+        return Err("Invalid HAP: bundle name not found".to_string());
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_api_levels() {
+        let cases = [(50_000_012, 12), (50_001_013, 13), (40_100_011, 11), (12, 12), (9, 9), (1000, 1000)];
+        for (value, expected) in cases {
+            assert_eq!(api_level(value), expected, "{}", value);
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! 离线读取安装包元数据，不依赖 aapt / hdc 或网络
+//!
+//! - `.apk`：解析二进制 `AndroidManifest.xml` 和签名块
+//! - `.apks`：bundletool 生成的 APK 集，读取其中的 base / universal APK，ABI 取自拆分包名
+//! - `.hap` / `.hsp`：读取 `module.json` 或 `pack.info`
+
+mod axml;
+mod hap;
+mod signing;
+
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use signing::SigningInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageFormat {
+    Apk,
+    Apks,
+    Hap,
+    Hsp,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageMetadata {
+    pub format: PackageFormat,
+    /// Android 包名或 HarmonyOS bundleName
+    pub package: String,
+    pub version_name: Option<String>,
+    pub version_code: Option<u64>,
+    /// Android 为 SDK 版本，HarmonyOS 为 API 版本
+    pub min_sdk: Option<u32>,
+    pub target_sdk: Option<u32>,
+    pub permissions: Vec<String>,
+    /// 包含的原生库架构，如 `arm64-v8a`；为空表示不含原生库
+    pub abis: Vec<String>,
+    /// 桌面入口的 Activity（完整类名）或 Ability
+    pub launchable: Option<String>,
+    /// HAP 模块名和类型（entry / feature / shared）
+    pub module_name: Option<String>,
+    pub module_type: Option<String>,
+    /// APK 签名信息，HAP 为空
+    pub signing: SigningInfo,
+    /// 文件大小 (字节)
+    pub file_size: u64,
+}
+
+impl PackageMetadata {
+    fn new(format: PackageFormat) -> Self {
+        Self {
+            format,
+            package: String::new(),
+            version_name: None,
+            version_code: None,
+            min_sdk: None,
+            target_sdk: None,
+            permissions: Vec::new(),
+            abis: Vec::new(),
+            launchable: None,
+            module_name: None,
+            module_type: None,
+            signing: SigningInfo::default(),
+            file_size: 0,
+        }
+    }
+}
+
+/// 读取安装包元数据，按扩展名判断格式
+pub fn inspect(path: &str) -> Result<PackageMetadata, String> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    let mut metadata = match extension.as_str() {
+        "apk" => inspect_apk(file),
+        "apks" => inspect_apk_set(file),
+        "hap" | "hsp" => {
+            let format = if extension == "hap" { PackageFormat::Hap } else { PackageFormat::Hsp };
+            let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read package: {}", e))?;
+            hap::inspect(&mut archive, format)
+        }
+        _ => Err("Unsupported package format".to_string()),
+    }
+    .map_err(|e| format!("{} ({})", e, path))?;
+    metadata.file_size = file_size;
+    Ok(metadata)
+}
+
+/// `<prefix><abi>/xxx.so` 中出现的架构
+fn native_abis<R: Read + Seek>(archive: &zip::ZipArchive<R>, prefix: &str) -> Vec<String> {
+    archive
+        .file_names()
+        .filter_map(|name| name.strip_prefix(prefix))
+        .filter_map(|rest| rest.split_once('/'))
+        .filter(|(abi, file)| !abi.is_empty() && !file.is_empty())
+        .map(|(abi, _)| abi.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn inspect_apk<R: Read + Seek>(mut reader: R) -> Result<PackageMetadata, String> {
+    let signing = signing::read(&mut reader)?;
+    let mut archive = zip::ZipArchive::new(reader).map_err(|e| format!("Failed to read APK: {}", e))?;
+
+    let mut manifest = Vec::new();
+    archive
+        .by_name("AndroidManifest.xml")
+        .map_err(|_| "Invalid APK: AndroidManifest.xml not found".to_string())?
+        .read_to_end(&mut manifest)
+        .map_err(|e| format!("Failed to read AndroidManifest.xml: {}", e))?;
+
+    let mut metadata = PackageMetadata::new(PackageFormat::Apk);
+    read_manifest(&axml::parse(&manifest)?, &mut metadata);
+    if metadata.package.is_empty() {
+        return Err("Invalid APK: package name not found".to_string());
+    }
+    metadata.abis = native_abis(&archive, "lib/");
+    metadata.signing = signing;
+    Ok(metadata)
+}
+
+fn read_manifest(events: &[axml::Event], metadata: &mut PackageMetadata) {
+    let mut stack: Vec<&str> = Vec::new();
+    // 当前 activity 的名字，以及当前 intent-filter 是否含 MAIN / LAUNCHER
+    let mut activity: Option<String> = None;
+    let (mut main, mut launcher) = (false, false);
+
+    for event in events {
+        let (name, attrs) = match event {
+            axml::Event::Start { name, attrs } => (name.as_str(), attrs),
+            axml::Event::End { name } => {
+                match name.as_str() {
+                    "intent-filter" if main && launcher && metadata.launchable.is_none() => {
+                        metadata.launchable = activity.clone();
+                    }
+                    "activity" | "activity-alias" => activity = None,
+                    _ => {}
+                }
+                stack.pop();
+                continue;
+            }
+        };
+        let attr = |key: &str| attrs.get(key).map(|v| v.as_string());
+        let parent = stack.last().copied();
+
+        match (parent, name) {
+            (None, "manifest") => {
+                metadata.package = attr("package").unwrap_or_default();
+                metadata.version_name = attr("versionName");
+                metadata.version_code = attrs.get("versionCode").and_then(|v| v.as_u64());
+            }
+            (Some("manifest"), "uses-sdk") => {
+                metadata.min_sdk = attrs.get("minSdkVersion").and_then(|v| v.as_u64()).map(|v| v as u32);
+                // 未声明 targetSdkVersion 时与 minSdkVersion 相同
+                metadata.target_sdk = attrs
+                    .get("targetSdkVersion")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32)
+                    .or(metadata.min_sdk);
+            }
+            (Some("manifest"), "uses-permission" | "uses-permission-sdk-23") => {
+                if let Some(permission) = attr("name").filter(|p| !metadata.permissions.contains(p)) {
+                    metadata.permissions.push(permission);
+                }
+            }
+            (Some("application"), "activity" | "activity-alias") => {
+                let enabled = !matches!(attrs.get("enabled"), Some(axml::AttrValue::Bool(false)));
+                activity = attr("name")
+                    .filter(|_| enabled)
+                    .map(|n| qualify_class(&metadata.package, &n));
+            }
+            (Some("activity" | "activity-alias"), "intent-filter") => (main, launcher) = (false, false),
+            (Some("intent-filter"), "action") => main |= attr("name").as_deref() == Some("android.intent.action.MAIN"),
+            (Some("intent-filter"), "category") => {
+                launcher |= attr("name").as_deref() == Some("android.intent.category.LAUNCHER")
+            }
+            _ => {}
+        }
+        stack.push(name);
+    }
+}
+
+/// APK 集中某个模块的拆分包是否在安装时交付。按需（on-demand）和 fast-follow 交付的模块
+/// 由应用运行时自行下载，安装时不应一起安装
+pub fn is_install_time_split(apk: &[u8]) -> Result<bool, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(apk)).map_err(|e| format!("Failed to read APK: {}", e))?;
+    let mut manifest = Vec::new();
+    archive
+        .by_name("AndroidManifest.xml")
+        .map_err(|_| "Invalid APK: AndroidManifest.xml not found".to_string())?
+        .read_to_end(&mut manifest)
+        .map_err(|e| format!("Failed to read AndroidManifest.xml: {}", e))?;
+
+    // <dist:module dist:onDemand="true"> 是旧写法，新写法为 <dist:delivery> 下的子元素
+    let mut on_demand = false;
+    let mut delivery: Option<bool> = None;
+    let mut stack: Vec<String> = Vec::new();
+    for event in axml::parse(&manifest)? {
+        match event {
+            axml::Event::Start { name, attrs } => {
+                match (stack.last().map(String::as_str), name.as_str()) {
+                    (Some("manifest"), "module") => {
+                        on_demand = attrs.get("onDemand").is_some_and(|v| v.as_string() == "true");
+                    }
+                    (Some("delivery"), "install-time") => delivery = Some(true),
+                    (Some("delivery"), "on-demand" | "fast-follow") => {
+                        delivery.get_or_insert(false);
+                    }
+                    _ => {}
+                }
+                stack.push(name);
+            }
+            axml::Event::End { .. } => {
+                stack.pop();
+            }
+        }
+    }
+    Ok(delivery.unwrap_or(!on_demand))
+}
+
+/// `.MainActivity` / `MainActivity` 补全为完整类名
+fn qualify_class(package: &str, name: &str) -> String {
+    if name.starts_with('.') {
+        format!("{}{}", package, name)
+    } else if !name.contains('.') {
+        format!("{}.{}", package, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// APK 集：读取 base-master.apk（或 universal.apk），ABI 合并拆分包名中的架构
+fn inspect_apk_set(file: std::fs::File) -> Result<PackageMetadata, String> {
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read APK set: {}", e))?;
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+
+    let base = ["splits/base-master.apk", "universal.apk"]
+        .into_iter()
+        .find(|name| names.iter().any(|n| n == name))
+        .or_else(|| {
+            names
+                .iter()
+                .find(|n| n.starts_with("standalones/") && n.ends_with(".apk"))
+                .map(String::as_str)
+        })
+        .ok_or_else(|| "Invalid APK set: base APK not found".to_string())?;
+
+    let mut data = Vec::new();
+    archive
+        .by_name(base)
+        .and_then(|mut entry| Ok(entry.read_to_end(&mut data)?))
+        .map_err(|e| format!("Failed to read {}: {}", base, e))?;
+
+    let mut metadata = inspect_apk(Cursor::new(data))?;
+    metadata.format = PackageFormat::Apks;
+
+    let mut abis: BTreeSet<String> = metadata.abis.drain(..).collect();
+    for name in &names {
+        let Some(stem) = name.strip_prefix("splits/").and_then(|n| n.strip_suffix(".apk")) else {
+            continue;
+        };
+        if let Some(abi) = ["arm64_v8a", "armeabi_v7a", "armeabi", "x86_64", "x86", "mips64", "mips"]
+            .iter()
+            .find(|abi| stem.ends_with(&format!("-{}", abi)))
+        {
+            abis.insert(abi.replace('_', "-"));
+        }
+    }
+    metadata.abis = abis.into_iter().collect();
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn reads_apk_manifest_and_signatures() {
+        let metadata = inspect(&fixture("signed_v1_v2.apk")).unwrap();
+        assert_eq!(metadata.format, PackageFormat::Apk);
+        assert_eq!(metadata.package, "com.example.demo");
+        assert_eq!(metadata.version_name.as_deref(), Some("1.2.3"));
+        assert_eq!(metadata.version_code, Some(42));
+        assert_eq!((metadata.min_sdk, metadata.target_sdk), (Some(24), Some(34)));
+        assert_eq!(metadata.permissions, ["android.permission.INTERNET", "android.permission.CAMERA"]);
+        assert_eq!(metadata.abis, ["arm64-v8a", "x86_64"]);
+        // 第一个 activity 没有 MAIN / LAUNCHER，入口为第二个
+        assert_eq!(metadata.launchable.as_deref(), Some("com.example.demo.MainActivity"));
+        assert_eq!(metadata.file_size, std::fs::metadata(fixture("signed_v1_v2.apk")).unwrap().len());
+
+        // v1 和 v2 使用同一证书，只保留一份
+        assert_eq!(metadata.signing.schemes, ["v1", "v2"]);
+        assert_eq!(metadata.signing.certificates.len(), 1);
+        let certificate = &metadata.signing.certificates[0];
+        assert_eq!(certificate.subject, "CN=Demo Dev, O=Example, C=US");
+        assert_eq!(
+            certificate.sha256,
+            "4C:F0:63:DA:9E:8F:A4:E3:00:E5:47:FF:41:A3:BB:66:C6:07:06:3D:CC:CA:B9:C3:5B:1D:69:11:E1:3C:E1:E6"
+        );
+    }
+
+    #[test]
+    fn reads_hap_module_json() {
+        let metadata = inspect(&fixture("entry.hap")).unwrap();
+        assert_eq!(metadata.format, PackageFormat::Hap);
+        assert_eq!(metadata.package, "com.example.harmony");
+        assert_eq!(metadata.version_name.as_deref(), Some("1.0.0"));
+        assert_eq!(metadata.version_code, Some(1_000_000));
+        // targetAPIVersion 在该文件中是字符串
+        assert_eq!((metadata.min_sdk, metadata.target_sdk), (Some(12), Some(12)));
+        assert_eq!(metadata.module_name.as_deref(), Some("entry"));
+        assert_eq!(metadata.module_type.as_deref(), Some("entry"));
+        assert_eq!(metadata.launchable.as_deref(), Some("EntryAbility"));
+        assert_eq!(metadata.permissions, ["ohos.permission.INTERNET"]);
+        assert_eq!(metadata.abis, ["arm64-v8a"]);
+        assert!(metadata.signing.schemes.is_empty());
+    }
+
+    #[test]
+    fn rejects_unsupported_and_corrupt_files() {
+        assert!(inspect(&fixture("missing.apk")).unwrap_err().starts_with("Failed to open"));
+        let error = inspect(&fixture("dumpsys_package.txt")).unwrap_err();
+        assert!(error.starts_with("Unsupported package format"), "{}", error);
+
+        let path = std::env::temp_dir().join(format!("simhub_inspect_{}.apk", std::process::id()));
+        std::fs::write(&path, b"not a zip").unwrap();
+        let error = inspect(path.to_str().unwrap()).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert!(error.starts_with("Failed to read APK"), "{}", error);
+    }
+
+    #[test]
+    fn qualifies_activity_names() {
+        assert_eq!(qualify_class("com.example", ".Main"), "com.example.Main");
+        assert_eq!(qualify_class("com.example", "Main"), "com.example.Main");
+        assert_eq!(qualify_class("com.example", "org.other.Main"), "org.other.Main");
+    }
+}
@@ -0,0 +1,271 @@
+//! APK 签名证书读取
+//!
+//! 读取 v1 的 `META-INF/*.RSA|DSA|EC` 和 APK Signing Block（v2 / v3 / v3.1）中的证书，
+//! 只提取证书，不校验签名。相同的证书只保留一份。
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, SeekFrom};
+
+const EOCD_MAGIC: u32 = 0x0605_4b50;
+const SIG_BLOCK_MAGIC: &[u8; 16] = b"APK Sig Block 42";
+
+const SCHEMES: [(u32, &str); 3] = [
+    (0x7109_871a, "v2"),
+    (0xf053_68c0, "v3"),
+    (0x1b93_ad61, "v3.1"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateInfo {
+    /// 证书主题，如 `CN=Android Debug, O=Android, C=US`
+    pub subject: String,
+    /// DER 编码的 SHA-256 指纹，冒号分隔的大写十六进制
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SigningInfo {
+    /// 存在的签名方案，如 `["v1", "v2", "v3"]`
+    pub schemes: Vec<String>,
+    /// 去重后的签名证书
+    pub certificates: Vec<CertificateInfo>,
+}
+
+impl SigningInfo {
+    fn add(&mut self, scheme: &str, certificates: Vec<Vec<u8>>) {
+        if certificates.is_empty() {
+            return;
+        }
+        self.schemes.push(scheme.to_string());
+        for der in certificates {
+            let sha256 = fingerprint(&der);
+            if self.certificates.iter().all(|c| c.sha256 != sha256) {
+                self.certificates.push(CertificateInfo {
+                    subject: certificate_subject(&der).unwrap_or_default(),
+                    sha256,
+                });
+            }
+        }
+    }
+}
+
+/// 读取 APK 的签名方案和证书
+pub fn read<R: Read + Seek>(reader: &mut R) -> Result<SigningInfo, String> {
+    let mut info = SigningInfo::default();
+
+    let mut v1 = Vec::new();
+    {
+        let mut archive = zip::ZipArchive::new(&mut *reader).map_err(|e| format!("Failed to read APK: {}", e))?;
+        let names: Vec<String> = archive
+            .file_names()
+            .filter(|name| {
+                let upper = name.to_ascii_uppercase();
+                upper.starts_with("META-INF/")
+                    && !upper["META-INF/".len()..].contains('/')
+                    && (upper.ends_with(".RSA") || upper.ends_with(".DSA") || upper.ends_with(".EC"))
+            })
+            .map(str::to_string)
+            .collect();
+        for name in names {
+            let mut data = Vec::new();
+            if let Ok(mut entry) = archive.by_name(&name) {
+                let _ = entry.read_to_end(&mut data);
+            }
+            v1.extend(pkcs7_certificates(&data));
+        }
+    }
+    info.add("v1", v1);
+
+    for (id, value) in signing_block_pairs(reader)? {
+        if let Some((_, scheme)) = SCHEMES.iter().find(|(scheme_id, _)| *scheme_id == id) {
+            info.add(scheme, scheme_certificates(&value));
+        }
+    }
+    Ok(info)
+}
+
+/// 读取 APK Signing Block 中的 (id, value)，没有签名块时返回空
+fn signing_block_pairs<R: Read + Seek>(reader: &mut R) -> Result<Vec<(u32, Vec<u8>)>, String> {
+    let io_err = |e: std::io::Error| format!("Failed to read APK: {}", e);
+
+    // EOCD 在文件末尾，后面可能跟最长 64KB 的注释
+    let file_size = reader.seek(SeekFrom::End(0)).map_err(io_err)?;
+    let tail_size = file_size.min(22 + 0xFFFF);
+    reader.seek(SeekFrom::Start(file_size - tail_size)).map_err(io_err)?;
+    let mut tail = vec![0u8; tail_size as usize];
+    reader.read_exact(&mut tail).map_err(io_err)?;
+
+    let Some(eocd) = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| u32_at(&tail, i) == Some(EOCD_MAGIC))
+    else {
+        return Err("Failed to read APK: end of central directory not found".to_string());
+    };
+    let cd_offset = u32_at(&tail, eocd + 16).unwrap_or(0) as u64;
+    if cd_offset < 32 {
+        return Ok(Vec::new());
+    }
+
+    // 签名块紧挨在中央目录之前，以 (块大小, 魔数) 结尾
+    let mut footer = [0u8; 24];
+    reader.seek(SeekFrom::Start(cd_offset - 24)).map_err(io_err)?;
+    reader.read_exact(&mut footer).map_err(io_err)?;
+    if &footer[8..] != SIG_BLOCK_MAGIC {
+        return Ok(Vec::new());
+    }
+    let block_size = u64::from_le_bytes(footer[..8].try_into().unwrap_or_default());
+    if block_size < 24 || block_size + 8 > cd_offset {
+        return Err("Failed to read APK: invalid signing block".to_string());
+    }
+
+    let pairs_size = (block_size - 24) as usize;
+    let mut pairs = vec![0u8; pairs_size];
+    reader.seek(SeekFrom::Start(cd_offset - block_size)).map_err(io_err)?;
+    reader.read_exact(&mut pairs).map_err(io_err)?;
+
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset + 12 <= pairs.len() {
+        let len = u64::from_le_bytes(pairs[offset..offset + 8].try_into().unwrap_or_default()) as usize;
+        let end = offset + 8 + len;
+        if len < 4 || end > pairs.len() {
+            break;
+        }
+        let id = u32_at(&pairs, offset + 8).unwrap_or(0);
+        result.push((id, pairs[offset + 12..end].to_vec()));
+        offset = end;
+    }
+    Ok(result)
+}
+
+/// v2 / v3 的值：signers -> signer -> signed data -> (digests, certificates, ...)
+fn scheme_certificates(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut certificates = Vec::new();
+    let Some(signers) = prefixed(value, 0).map(|(s, _)| s) else {
+        return certificates;
+    };
+    for signer in prefixed_items(signers) {
+        let Some((signed_data, _)) = prefixed(signer, 0) else { continue };
+        let Some((_, after_digests)) = prefixed(signed_data, 0) else { continue };
+        let Some((certs, _)) = prefixed(signed_data, after_digests) else { continue };
+        certificates.extend(prefixed_items(certs).map(<[u8]>::to_vec));
+    }
+    certificates
+}
+
+/// 读取 u32 长度前缀的一段，返回 (内容, 下一段的偏移)
+fn prefixed(data: &[u8], offset: usize) -> Option<(&[u8], usize)> {
+    let len = u32_at(data, offset)? as usize;
+    let start = offset + 4;
+    data.get(start..start + len).map(|slice| (slice, start + len))
+}
+
+fn prefixed_items(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let (item, next) = prefixed(data, offset)?;
+        offset = next;
+        Some(item)
+    })
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// 读取一个 DER TLV，返回 (tag, 内容, 整个 TLV 的长度)
+fn der(data: &[u8]) -> Option<(u8, &[u8], usize)> {
+    let tag = *data.first()?;
+    let first = *data.get(1)? as usize;
+    let (len, header) = if first & 0x80 == 0 {
+        (first, 2)
+    } else {
+        let count = first & 0x7F;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let len = data
+            .get(2..2 + count)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, 2 + count)
+    };
+    data.get(header..header + len).map(|content| (tag, content, header + len))
+}
+
+fn der_items(data: &[u8]) -> impl Iterator<Item = (u8, &[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let (tag, content, len) = der(rest)?;
+        let raw = &rest[..len];
+        rest = &rest[len..];
+        Some((tag, content, raw))
+    })
+}
+
+/// PKCS#7 SignedData 中 `certificates [0]` 里的证书
+fn pkcs7_certificates(data: &[u8]) -> Vec<Vec<u8>> {
+    // ContentInfo ::= SEQUENCE { contentType OID, content [0] EXPLICIT SignedData }
+    let signed_data = der(data)
+        .and_then(|(_, content_info, _)| der_items(content_info).find(|(tag, _, _)| *tag == 0xA0))
+        .and_then(|(_, explicit, _)| der(explicit));
+    let Some((_, signed_data, _)) = signed_data else {
+        return Vec::new();
+    };
+    der_items(signed_data)
+        .find(|(tag, _, _)| *tag == 0xA0)
+        .map(|(_, certs, _)| der_items(certs).map(|(_, _, raw)| raw.to_vec()).collect())
+        .unwrap_or_default()
+}
+
+/// 证书主题中常见的属性
+fn attribute_name(oid: &[u8]) -> Option<&'static str> {
+    match oid {
+        [0x55, 0x04, 0x03] => Some("CN"),
+        [0x55, 0x04, 0x06] => Some("C"),
+        [0x55, 0x04, 0x07] => Some("L"),
+        [0x55, 0x04, 0x08] => Some("ST"),
+        [0x55, 0x04, 0x0A] => Some("O"),
+        [0x55, 0x04, 0x0B] => Some("OU"),
+        _ => None,
+    }
+}
+
+fn certificate_subject(der_cert: &[u8]) -> Option<String> {
+    let (_, certificate, _) = der(der_cert)?;
+    let (_, tbs, _) = der(certificate)?;
+    // TBSCertificate: [0] version（可选）, serial, signature, issuer, validity, subject
+    let mut fields = der_items(tbs).skip_while(|(tag, _, _)| *tag == 0xA0);
+    let (_, subject, _) = fields.nth(4)?;
+
+    let mut parts = Vec::new();
+    for (_, rdn, _) in der_items(subject) {
+        for (_, attribute, _) in der_items(rdn) {
+            let mut items = der_items(attribute);
+            let (_, oid, _) = items.next()?;
+            let (tag, value, _) = items.next()?;
+            let Some(name) = attribute_name(oid) else { continue };
+            let value = match tag {
+                // BMPString
+                0x1E => String::from_utf16_lossy(
+                    &value.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect::<Vec<_>>(),
+                ),
+                _ => String::from_utf8_lossy(value).to_string(),
+            };
+            parts.push(format!("{}={}", name, value));
+        }
+    }
+    // 与 keytool 一致，从最后一个 RDN 开始显示
+    parts.reverse();
+    Some(parts.join(", "))
+}
@@ -0,0 +1,564 @@
+//! 从日志中识别崩溃、ANR 和 HarmonyOS faultlog
+//!
+//! - Java 崩溃：`AndroidRuntime` 的 `FATAL EXCEPTION`
+//! - Native 崩溃：`DEBUG` 标签输出的 `*** *** ***` 崩溃转储，直到 `Tombstone written to` 为止
+//! - ANR：`ActivityManager` 的 `ANR in`
+//! - HarmonyOS：日志中出现的 `/data/log/faultlog/` 故障日志文件
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::filter::LogEntry;
+
+/// Native 崩溃转储在没有 tombstone 行时，超过该时间即视为结束
+const NATIVE_DUMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 同一个 tombstone / faultlog 文件在该时间内只上报一次（tombstone 文件名会循环复用）
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(60);
+
+const FAULTLOG_DIR: &str = "/data/log/faultlog/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrashKind {
+    JavaCrash,
+    NativeCrash,
+    Anr,
+    /// HarmonyOS C++ 崩溃
+    CppCrash,
+    /// HarmonyOS ArkTS / JS 崩溃
+    JsCrash,
+    /// HarmonyOS 应用卡死
+    AppFreeze,
+    /// 其他 faultlog
+    Fault,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    pub kind: CrashKind,
+    pub package: Option<String>,
+    pub pid: Option<u32>,
+    /// 设备上的日志时间
+    pub timestamp: String,
+    /// 本地检测到的时间 (RFC 3339)
+    pub detected_at: String,
+    pub stack_trace: String,
+    /// 设备上的 tombstone / faultlog 文件
+    pub remote_file: Option<String>,
+    /// 拉取到本地的文件
+    pub local_file: Option<String>,
+    pub pull_error: Option<String>,
+}
+
+/// 前端传入的崩溃捕获选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CrashCaptureOptions {
+    pub enabled: bool,
+    /// 把 tombstone / faultlog 文件拉取到本地
+    pub pull_files: bool,
+    /// 本地保存目录，默认为截图目录下的 crashes
+    pub dir: Option<String>,
+}
+
+impl Default for CrashCaptureOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pull_files: false,
+            dir: None,
+        }
+    }
+}
+
+/// 从设备拉取文件：(设备, 远端路径, 本地路径)
+pub type PullFile = fn(&str, &str, &std::path::Path) -> Result<(), String>;
+
+/// 会话的崩溃捕获配置
+#[derive(Clone)]
+pub struct CrashCapture {
+    pub pull_dir: Option<PathBuf>,
+    pub pull: PullFile,
+}
+
+impl CrashCapture {
+    /// 未启用时返回 None
+    pub fn new(options: Option<CrashCaptureOptions>, pull: PullFile) -> Result<Option<Self>, String> {
+        let options = options.unwrap_or_default();
+        if !options.enabled {
+            return Ok(None);
+        }
+        let pull_dir = if options.pull_files {
+            Some(options.local_dir()?)
+        } else {
+            None
+        };
+        Ok(Some(Self { pull_dir, pull }))
+    }
+
+    /// 按需拉取崩溃文件；faultlog 的完整堆栈在文件中，拉取成功后替换日志中的摘要
+    pub fn collect(&self, device_id: &str, report: &mut CrashReport) {
+        let (Some(dir), Some(remote)) = (&self.pull_dir, &report.remote_file) else {
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            report.pull_error = Some(format!("Failed to create crash directory: {}", e));
+            return;
+        }
+
+        let device: String = device_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let name = remote.rsplit('/').next().unwrap_or(remote);
+        let local = dir.join(format!(
+            "{}_{}_{}",
+            device,
+            chrono::Local::now().format("%Y%m%d_%H%M%S"),
+            name
+        ));
+
+        match (self.pull)(device_id, remote, &local) {
+            Ok(()) => {
+                if !matches!(report.kind, CrashKind::JavaCrash | CrashKind::NativeCrash | CrashKind::Anr) {
+                    if let Ok(content) = std::fs::read_to_string(&local) {
+                        report.stack_trace = content;
+                    }
+                }
+                report.local_file = Some(local.to_string_lossy().to_string());
+            }
+            Err(e) => report.pull_error = Some(e),
+        }
+    }
+}
+
+impl CrashCaptureOptions {
+    pub fn local_dir(&self) -> Result<PathBuf, String> {
+        match self.dir.as_deref().filter(|d| !d.is_empty()) {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => crate::commands::settings::get_screenshot_dir()
+                .map(|dir| PathBuf::from(dir).join("crashes"))
+                .ok_or_else(|| "Cannot find crash directory".to_string()),
+        }
+    }
+}
+
+fn report(kind: CrashKind, entry: &LogEntry, stack_trace: String) -> CrashReport {
+    CrashReport {
+        kind,
+        package: None,
+        pid: None,
+        timestamp: entry.timestamp.clone(),
+        detected_at: chrono::Local::now().to_rfc3339(),
+        stack_trace,
+        remote_file: None,
+        local_file: None,
+        pull_error: None,
+    }
+}
+
+/// 正在收集的 native 崩溃转储
+struct NativeDump {
+    report: CrashReport,
+    last_line: Instant,
+}
+
+#[derive(Default)]
+pub struct CrashDetector {
+    native: Option<NativeDump>,
+    /// 最近上报过的设备文件及上报时间
+    reported: HashMap<String, Instant>,
+}
+
+impl CrashDetector {
+    /// 处理一条日志，返回已完整识别的崩溃；同一个设备文件被多行日志提到时只返回一次
+    pub fn observe(&mut self, entry: &LogEntry) -> Vec<CrashReport> {
+        let mut found = self.detect(entry);
+        found.retain(|report| self.is_first_report(report));
+        found
+    }
+
+    /// 记录上报的设备文件，返回是否为窗口期内第一次上报
+    fn is_first_report(&mut self, report: &CrashReport) -> bool {
+        let Some(remote) = &report.remote_file else {
+            return true;
+        };
+        self.reported.retain(|_, at| at.elapsed() < DUPLICATE_WINDOW);
+        self.reported.insert(remote.clone(), Instant::now()).is_none()
+    }
+
+    fn detect(&mut self, entry: &LogEntry) -> Vec<CrashReport> {
+        let mut found = Vec::new();
+
+        if let Some(path) = entry.message.strip_prefix("Tombstone written to: ") {
+            if let Some(mut dump) = self.native.take() {
+                dump.report.remote_file = Some(path.trim().to_string());
+                found.push(dump.report);
+            }
+            return found;
+        }
+
+        if entry.tag == "DEBUG" {
+            if entry.message.starts_with("*** *** ***") {
+                found.extend(self.native.take().map(|dump| dump.report));
+                self.native = Some(NativeDump {
+                    report: report(CrashKind::NativeCrash, entry, String::new()),
+                    last_line: Instant::now(),
+                });
+            }
+            if let Some(dump) = self.native.as_mut() {
+                if let Some((pid, package)) = parse_native_header(&entry.message) {
+                    dump.report.pid = Some(pid);
+                    dump.report.package = Some(package);
+                }
+                if !dump.report.stack_trace.is_empty() {
+                    dump.report.stack_trace.push('\n');
+                }
+                dump.report.stack_trace.push_str(&entry.message);
+                dump.last_line = Instant::now();
+            }
+            return found;
+        }
+
+        found.extend(self.flush_stale());
+
+        if entry.tag == "AndroidRuntime" && entry.message.starts_with("FATAL EXCEPTION") {
+            let mut crash = report(CrashKind::JavaCrash, entry, entry.message.clone());
+            // 第二行形如 "Process: com.example, PID: 1234"
+            if let Some(line) = entry.message.lines().find(|l| l.starts_with("Process: ")) {
+                let mut parts = line["Process: ".len()..].split(", PID: ");
+                crash.package = parts.next().map(|p| p.trim().to_string());
+                crash.pid = parts.next().and_then(|p| p.trim().parse().ok());
+            }
+            found.push(crash);
+        } else if entry.tag == "ActivityManager" && entry.message.starts_with("ANR in ") {
+            let mut anr = report(CrashKind::Anr, entry, entry.message.clone());
+            anr.package = entry.message["ANR in ".len()..]
+                .split_whitespace()
+                .next()
+                .map(str::to_string);
+            anr.pid = entry
+                .message
+                .lines()
+                .find_map(|l| l.strip_prefix("PID: "))
+                .and_then(|p| p.trim().parse().ok());
+            found.push(anr);
+        } else if let Some(path) = find_faultlog_path(&entry.message) {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            let (kind, package) = parse_faultlog_name(name);
+            let mut fault = report(kind, entry, entry.message.clone());
+            fault.package = package;
+            fault.remote_file = Some(path.to_string());
+            found.push(fault);
+        }
+
+        found
+    }
+
+    /// 没有 tombstone 行的 native 崩溃，超时后按已收集的内容上报
+    pub fn flush_stale(&mut self) -> Option<CrashReport> {
+        if self
+            .native
+            .as_ref()
+            .is_some_and(|dump| dump.last_line.elapsed() >= NATIVE_DUMP_TIMEOUT)
+        {
+            return self.native.take().map(|dump| dump.report);
+        }
+        None
+    }
+
+    /// 日志结束时上报未完成的转储
+    pub fn finish(&mut self) -> Option<CrashReport> {
+        self.native.take().map(|dump| dump.report)
+    }
+}
+
+/// `pid: 4321, tid: 4321, name: RenderThread  >>> com.example <<<`
+fn parse_native_header(message: &str) -> Option<(u32, String)> {
+    let pid = message
+        .strip_prefix("pid: ")?
+        .split(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+    let start = message.find(">>> ")? + 4;
+    let end = message[start..].find(" <<<")? + start;
+    Some((pid, message[start..end].to_string()))
+}
+
+fn find_faultlog_path(message: &str) -> Option<&str> {
+    let start = message.find(FAULTLOG_DIR)?;
+    let rest = &message[start..];
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == ',' || c == '"' || c == '\'')
+        .unwrap_or(rest.len());
+    let path = &rest[..end];
+    // 只认具体文件（形如 cppcrash-xxx），忽略 faultlogger、temp 等目录
+    let name = path.rsplit('/').next().unwrap_or_default();
+    name.contains('-').then_some(path)
+}
+
+/// faultlog 文件名形如 `cppcrash-com.example-20010039-20240101120000`
+fn parse_faultlog_name(name: &str) -> (CrashKind, Option<String>) {
+    let mut parts = name.splitn(3, '-');
+    let kind = match parts.next() {
+        Some("cppcrash") => CrashKind::CppCrash,
+        Some("jscrash") => CrashKind::JsCrash,
+        Some("appfreeze") => CrashKind::AppFreeze,
+        _ => CrashKind::Fault,
+    };
+    let package = parts.next().filter(|p| p.contains('.')).map(str::to_string);
+    (kind, package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::filter::LogLevel;
+
+    fn entry(tag: &str, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: "03-12 09:41:07.512".to_string(),
+            pid: 612,
+            tid: 612,
+            level: LogLevel::Error,
+            tag: tag.to_string(),
+            message: message.to_string(),
+            domain: None,
+        }
+    }
+
+    type Expected<'a> = (CrashKind, Option<&'a str>, Option<u32>, Option<&'a str>);
+    /// (说明, 依次输入的 (标签, 消息), 预期上报)
+    type Case<'a> = (&'a str, Vec<(&'a str, &'a str)>, Vec<Expected<'a>>);
+
+    fn summary(report: &CrashReport) -> (CrashKind, Option<&str>, Option<u32>, Option<&str>) {
+        (report.kind, report.package.as_deref(), report.pid, report.remote_file.as_deref())
+    }
+
+    const NATIVE_DUMP: [(&str, &str); 4] = [
+        ("DEBUG", "*** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***"),
+        ("DEBUG", "Build fingerprint: 'google/sdk_gphone64_x86_64/emu64xa:14/UE1A.230829.036/10848357:userdebug/dev-keys'"),
+        ("DEBUG", "pid: 4321, tid: 4338, name: RenderThread  >>> com.example.app <<<"),
+        ("DEBUG", "signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0"),
+    ];
+
+    #[test]
+    fn observes_crashes() {
+        let cppcrash = "/data/log/faultlog/faultlogger/cppcrash-com.example.app-20010039-20240101120000";
+        let written = format!("Write fault log to {}", cppcrash);
+        let forwarded = format!("report {} to app", cppcrash);
+        let cases: &[Case] = &[
+            (
+                "java crash",
+                vec![(
+                    "AndroidRuntime",
+                    "FATAL EXCEPTION: main\nProcess: com.example.app, PID: 8123\njava.lang.IllegalStateException: boom",
+                )],
+                vec![(CrashKind::JavaCrash, Some("com.example.app"), Some(8123), None)],
+            ),
+            (
+                "other AndroidRuntime output",
+                vec![("AndroidRuntime", "Shutting down VM")],
+                vec![],
+            ),
+            (
+                "native crash ended by tombstone",
+                NATIVE_DUMP
+                    .iter()
+                    .copied()
+                    .chain([("tombstoned", "Tombstone written to: /data/tombstones/tombstone_03")])
+                    .collect(),
+                vec![(CrashKind::NativeCrash, Some("com.example.app"), Some(4321), Some("/data/tombstones/tombstone_03"))],
+            ),
+            (
+                "new native dump ends the previous one",
+                NATIVE_DUMP.iter().chain(NATIVE_DUMP.iter()).copied().collect(),
+                vec![(CrashKind::NativeCrash, Some("com.example.app"), Some(4321), None)],
+            ),
+            (
+                "tombstone without dump",
+                vec![("tombstoned", "Tombstone written to: /data/tombstones/tombstone_04")],
+                vec![],
+            ),
+            (
+                "anr",
+                vec![(
+                    "ActivityManager",
+                    "ANR in com.example.app (com.example.app/.MainActivity)\nPID: 8123\nReason: Input dispatching timed out",
+                )],
+                vec![(CrashKind::Anr, Some("com.example.app"), Some(8123), None)],
+            ),
+            (
+                "cppcrash faultlog",
+                vec![("Faultlogger", &written)],
+                vec![(CrashKind::CppCrash, Some("com.example.app"), None, Some(cppcrash))],
+            ),
+            (
+                "jscrash faultlog",
+                vec![("Faultlogger", "save \"/data/log/faultlog/faultlogger/jscrash-com.example.app-20010039-20240101120001\", done")],
+                vec![(
+                    CrashKind::JsCrash,
+                    Some("com.example.app"),
+                    None,
+                    Some("/data/log/faultlog/faultlogger/jscrash-com.example.app-20010039-20240101120001"),
+                )],
+            ),
+            (
+                "appfreeze faultlog",
+                vec![("Faultlogger", "/data/log/faultlog/faultlogger/appfreeze-com.example.app-20010039-20240101120002")],
+                vec![(
+                    CrashKind::AppFreeze,
+                    Some("com.example.app"),
+                    None,
+                    Some("/data/log/faultlog/faultlogger/appfreeze-com.example.app-20010039-20240101120002"),
+                )],
+            ),
+            (
+                "other faultlog",
+                vec![("Faultlogger", "/data/log/faultlog/faultlogger/sysfreeze-foundation-5523-20240101120003")],
+                vec![(
+                    CrashKind::Fault,
+                    None,
+                    None,
+                    Some("/data/log/faultlog/faultlogger/sysfreeze-foundation-5523-20240101120003"),
+                )],
+            ),
+            (
+                "directory mentions",
+                vec![
+                    ("Faultlogger", "init dir /data/log/faultlog/faultlogger/ done"),
+                    ("Faultlogger", "clean /data/log/faultlog/temp"),
+                    ("Faultlogger", "scan /data/log/faultlog/"),
+                ],
+                vec![],
+            ),
+            (
+                "same faultlog mentioned twice",
+                vec![
+                    ("Faultlogger", &written),
+                    ("hiview", &forwarded),
+                ],
+                vec![(CrashKind::CppCrash, Some("com.example.app"), None, Some(cppcrash))],
+            ),
+        ];
+
+        for (description, lines, expected) in cases {
+            let mut detector = CrashDetector::default();
+            let found: Vec<CrashReport> = lines
+                .iter()
+                .flat_map(|(tag, message)| detector.observe(&entry(tag, message)))
+                .collect();
+            let actual: Vec<_> = found.iter().map(summary).collect();
+            assert_eq!(&actual, expected, "{}", description);
+        }
+    }
+
+    #[test]
+    fn stale_native_dump_is_flushed() {
+        let mut detector = CrashDetector::default();
+        for (tag, message) in NATIVE_DUMP {
+            assert!(detector.observe(&entry(tag, message)).is_empty());
+        }
+        // 还在超时时间内
+        assert!(detector.flush_stale().is_none());
+
+        detector.native.as_mut().unwrap().last_line = Instant::now() - NATIVE_DUMP_TIMEOUT;
+        let report = detector.flush_stale().unwrap();
+        assert_eq!(summary(&report), (CrashKind::NativeCrash, Some("com.example.app"), Some(4321), None));
+        assert_eq!(report.stack_trace.lines().count(), NATIVE_DUMP.len());
+        assert!(detector.flush_stale().is_none());
+        assert!(detector.finish().is_none());
+    }
+
+    #[test]
+    fn other_log_lines_flush_stale_dump() {
+        let mut detector = CrashDetector::default();
+        for (tag, message) in NATIVE_DUMP {
+            detector.observe(&entry(tag, message));
+        }
+        assert!(detector.observe(&entry("Demo", "unrelated")).is_empty());
+
+        detector.native.as_mut().unwrap().last_line = Instant::now() - NATIVE_DUMP_TIMEOUT;
+        let found = detector.observe(&entry("Demo", "unrelated"));
+        assert_eq!(found.iter().map(summary).collect::<Vec<_>>(), [(CrashKind::NativeCrash, Some("com.example.app"), Some(4321), None)]);
+    }
+
+    #[test]
+    fn reported_files_expire_after_window() {
+        let tombstone = "Tombstone written to: /data/tombstones/tombstone_03";
+        let mut detector = CrashDetector::default();
+        let crash = |detector: &mut CrashDetector| {
+            for (tag, message) in NATIVE_DUMP {
+                detector.observe(&entry(tag, message));
+            }
+            detector.observe(&entry("tombstoned", tombstone)).len()
+        };
+        assert_eq!(crash(&mut detector), 1);
+        assert_eq!(crash(&mut detector), 0);
+
+        // tombstone 文件名循环复用，窗口期过后同名文件再次上报
+        for at in detector.reported.values_mut() {
+            *at = Instant::now() - DUPLICATE_WINDOW;
+        }
+        assert_eq!(crash(&mut detector), 1);
+    }
+
+    #[test]
+    fn parses_native_headers() {
+        let cases = [
+            ("pid: 4321, tid: 4338, name: RenderThread  >>> com.example.app <<<", Some((4321, "com.example.app"))),
+            ("pid: 1234, tid: 1234, name: main  >>> /system/bin/surfaceflinger <<<", Some((1234, "/system/bin/surfaceflinger"))),
+            ("pid: abc, tid: 1, name: x  >>> com.example <<<", None),
+            ("pid: 4321, tid: 4338, name: RenderThread", None),
+            ("signal 11 (SIGSEGV)", None),
+        ];
+        for (message, expected) in cases {
+            let actual = parse_native_header(message);
+            assert_eq!(actual.as_ref().map(|(pid, name)| (*pid, name.as_str())), expected, "{}", message);
+        }
+    }
+
+    #[test]
+    fn finds_faultlog_paths() {
+        let cases = [
+            (
+                "Write fault log to /data/log/faultlog/faultlogger/cppcrash-com.example-1-20240101120000",
+                Some("/data/log/faultlog/faultlogger/cppcrash-com.example-1-20240101120000"),
+            ),
+            (
+                "path=\"/data/log/faultlog/faultlogger/jscrash-com.example-1-20240101120000\"",
+                Some("/data/log/faultlog/faultlogger/jscrash-com.example-1-20240101120000"),
+            ),
+            (
+                "'/data/log/faultlog/temp/cppcrash-1234-20240101120000', size 4096",
+                Some("/data/log/faultlog/temp/cppcrash-1234-20240101120000"),
+            ),
+            ("mkdir /data/log/faultlog/faultlogger/", None),
+            ("clean /data/log/faultlog/temp", None),
+            ("scan /data/log/faultlog/", None),
+            ("no fault here", None),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(find_faultlog_path(message), expected, "{}", message);
+        }
+    }
+
+    #[test]
+    fn parses_faultlog_names() {
+        let cases = [
+            ("cppcrash-com.example.app-20010039-20240101120000", CrashKind::CppCrash, Some("com.example.app")),
+            ("jscrash-com.example.app-20010039-20240101120000", CrashKind::JsCrash, Some("com.example.app")),
+            ("appfreeze-com.example.app-20010039-20240101120000", CrashKind::AppFreeze, Some("com.example.app")),
+            ("cppcrash-1234-20240101120000", CrashKind::CppCrash, None),
+            ("sysfreeze-foundation-5523-20240101120000", CrashKind::Fault, None),
+        ];
+        for (name, kind, package) in cases {
+            assert_eq!(parse_faultlog_name(name), (kind, package.map(str::to_string)), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! 结构化日志条目与服务端过滤
+
+use super::hilog::parse_domain;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 从日志中发现的进程号在该时间内不会被刷新清除，避免查询结果早于进程启动时误删
+const DISCOVERED_PID_GRACE: Duration = Duration::from_secs(10);
+
+/// 日志级别，按严重程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LogLevel {
+    #[serde(rename = "V")]
+    Verbose,
+    #[serde(rename = "D")]
+    Debug,
+    #[serde(rename = "I")]
+    Info,
+    #[serde(rename = "W")]
+    Warn,
+    #[serde(rename = "E")]
+    Error,
+    #[serde(rename = "F")]
+    Fatal,
+}
+
+impl LogLevel {
+    /// logcat / hilog 输出中的单字母级别，Assert 视为 Fatal
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        match letter {
+            "V" => Some(LogLevel::Verbose),
+            "D" => Some(LogLevel::Debug),
+            "I" => Some(LogLevel::Info),
+            "W" => Some(LogLevel::Warn),
+            "E" => Some(LogLevel::Error),
+            "F" | "A" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            LogLevel::Verbose => 'V',
+            LogLevel::Debug => 'D',
+            LogLevel::Info => 'I',
+            LogLevel::Warn => 'W',
+            LogLevel::Error => 'E',
+            LogLevel::Fatal => 'F',
+        }
+    }
+}
+
+/// 一条解析后的日志，多行消息已合并
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    /// 设备上的原始时间字符串
+    pub timestamp: String,
+    pub pid: u32,
+    pub tid: u32,
+    pub level: LogLevel,
+    pub tag: String,
+    pub message: String,
+    /// 仅 hilog 有，如 `A03d00`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+impl LogEntry {
+    /// 同一条多行日志的各行头部相同
+    pub fn same_header(&self, other: &LogEntry) -> bool {
+        self.timestamp == other.timestamp
+            && self.pid == other.pid
+            && self.tid == other.tid
+            && self.level == other.level
+            && self.tag == other.tag
+            && self.domain == other.domain
+    }
+}
+
+/// 前端传入的过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilterOptions {
+    pub min_level: Option<LogLevel>,
+    /// 标签正则
+    pub tag: Option<String>,
+    /// 消息正则
+    pub message: Option<String>,
+    /// 只保留该应用进程的日志
+    pub package: Option<String>,
+    /// 只保留该进程的日志
+    pub pid: Option<u32>,
+    /// hilog domain，如 `0x3d00` 或 `A03d00`
+    pub domain: Option<String>,
+}
+
+/// 从 `ps -A -o PID,NAME` 输出中取出进程名为包名或 `包名:子进程` 的进程号
+pub fn parse_package_pids(ps: &str, package: &str) -> HashSet<u32> {
+    let child_prefix = format!("{}:", package);
+    ps.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            (name == package || name.starts_with(&child_prefix)).then_some(pid)
+        })
+        .collect()
+}
+
+/// 编译后的过滤条件；按包名过滤时，进程号集合会随应用重启而更新
+pub struct LogFilter {
+    min_level: Option<LogLevel>,
+    tag: Option<Regex>,
+    message: Option<Regex>,
+    package: Option<String>,
+    pids: Mutex<PackagePids>,
+    pid: Option<u32>,
+    domain: Option<u32>,
+}
+
+#[derive(Default)]
+struct PackagePids {
+    /// 最近一次查询到的应用进程（主进程及 `:remote` 等子进程）
+    running: HashSet<u32>,
+    /// 从日志中发现的进程及发现时间
+    discovered: HashMap<u32, Instant>,
+}
+
+impl PackagePids {
+    fn contains(&self, pid: u32) -> bool {
+        self.running.contains(&pid) || self.discovered.contains_key(&pid)
+    }
+}
+
+fn compile(label: &str, pattern: &Option<String>) -> Result<Option<Regex>, String> {
+    match pattern.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) => Regex::new(p)
+            .map(Some)
+            .map_err(|e| format!("Invalid {} regex: {}", label, e)),
+        None => Ok(None),
+    }
+}
+
+impl LogFilter {
+    pub fn new(options: &LogFilterOptions) -> Result<Self, String> {
+        Ok(Self {
+            min_level: options.min_level,
+            tag: compile("tag", &options.tag)?,
+            message: compile("message", &options.message)?,
+            package: options
+                .package
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+            pids: Mutex::new(PackagePids::default()),
+            pid: options.pid,
+            domain: match options.domain.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+                Some(d) => Some(parse_domain(d).ok_or_else(|| format!("Invalid hilog domain: {}", d))?),
+                None => None,
+            },
+        })
+    }
+
+    pub fn package(&self) -> Option<&str> {
+        self.package.as_deref()
+    }
+
+    /// 用查询到的应用进程更新进程号；从日志中发现的进程只有确认已退出（不在查询结果中且已过宽限期）才移除
+    pub fn set_pids(&self, pids: HashSet<u32>) {
+        let mut current = self.pids.lock().unwrap();
+        current
+            .discovered
+            .retain(|pid, found| pids.contains(pid) || found.elapsed() < DISCOVERED_PID_GRACE);
+        current.running = pids;
+    }
+
+    /// 记录从日志中发现的应用进程
+    pub fn add_pid(&self, pid: u32) {
+        self.pids.lock().unwrap().discovered.insert(pid, Instant::now());
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if self.min_level.is_some_and(|min| entry.level < min) {
+            return false;
+        }
+        if self.pid.is_some_and(|pid| entry.pid != pid) {
+            return false;
+        }
+        if self.domain.is_some() && entry.domain.as_deref().and_then(parse_domain) != self.domain {
+            return false;
+        }
+        if self.package.is_some() && !self.pids.lock().unwrap().contains(entry.pid) {
+            return false;
+        }
+        if self.tag.as_ref().is_some_and(|re| !re.is_match(&entry.tag)) {
+            return false;
+        }
+        if self.message.as_ref().is_some_and(|re| !re.is_match(&entry.message)) {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, level: LogLevel, tag: &str, message: &str, domain: Option<&str>) -> LogEntry {
+        LogEntry {
+            timestamp: "08-05 12:00:00.123".to_string(),
+            pid,
+            tid: pid,
+            level,
+            tag: tag.to_string(),
+            message: message.to_string(),
+            domain: domain.map(str::to_string),
+        }
+    }
+
+    fn filter(options: LogFilterOptions) -> LogFilter {
+        LogFilter::new(&options).unwrap()
+    }
+
+    #[test]
+    fn matches_each_condition() {
+        let info = entry(100, LogLevel::Info, "OkHttp", "--> GET /api/user", None);
+        let error = entry(200, LogLevel::Error, "AndroidRuntime", "FATAL EXCEPTION: main", None);
+        let hilog = entry(300, LogLevel::Warn, "JSAPP", "retry", Some("A03d00"));
+
+        let cases: Vec<(LogFilterOptions, [bool; 3])> = vec![
+            (LogFilterOptions::default(), [true, true, true]),
+            (LogFilterOptions { min_level: Some(LogLevel::Warn), ..Default::default() }, [false, true, true]),
+            (LogFilterOptions { tag: Some("^Ok".to_string()), ..Default::default() }, [true, false, false]),
+            (LogFilterOptions { message: Some("(?i)fatal".to_string()), ..Default::default() }, [false, true, false]),
+            (LogFilterOptions { pid: Some(200), ..Default::default() }, [false, true, false]),
+            (LogFilterOptions { domain: Some("0xD003d00".to_string()), ..Default::default() }, [false, false, true]),
+            (LogFilterOptions { domain: Some("0x3d01".to_string()), ..Default::default() }, [false, false, false]),
+            // 空白条件视为未设置
+            (
+                LogFilterOptions {
+                    tag: Some("  ".to_string()),
+                    message: Some(String::new()),
+                    package: Some(" ".to_string()),
+                    domain: Some(String::new()),
+                    ..Default::default()
+                },
+                [true, true, true],
+            ),
+            (
+                LogFilterOptions {
+                    min_level: Some(LogLevel::Info),
+                    tag: Some("Runtime$".to_string()),
+                    message: Some("main".to_string()),
+                    ..Default::default()
+                },
+                [false, true, false],
+            ),
+        ];
+
+        for (options, expected) in cases {
+            let description = format!("{:?}", options);
+            let filter = filter(options);
+            let actual = [&info, &error, &hilog].map(|e| filter.matches(e));
+            assert_eq!(actual, expected, "{}", description);
+        }
+    }
+
+    #[test]
+    fn package_filter_follows_process_ids() {
+        let filter = filter(LogFilterOptions {
+            package: Some("com.example.app".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(filter.package(), Some("com.example.app"));
+
+        let main = entry(100, LogLevel::Info, "Demo", "main", None);
+        let remote = entry(101, LogLevel::Info, "Demo", "remote", None);
+        // 还不知道进程号时全部过滤掉
+        assert!(!filter.matches(&main));
+
+        filter.set_pids(HashSet::from([100]));
+        filter.add_pid(101);
+        assert!(filter.matches(&main));
+        assert!(filter.matches(&remote));
+
+        // 刚发现的进程不会因查询结果较旧而被移除
+        filter.set_pids(HashSet::from([100]));
+        assert!(filter.matches(&remote));
+
+        // 应用重启后，已退出的旧进程号失效
+        expire_discovered(&filter);
+        filter.set_pids(HashSet::from([150]));
+        assert!(!filter.matches(&main));
+        assert!(!filter.matches(&remote));
+        assert!(filter.matches(&entry(150, LogLevel::Info, "Demo", "restarted", None)));
+    }
+
+    /// 让已发现的进程号都超过宽限期
+    fn expire_discovered(filter: &LogFilter) {
+        let expired = Instant::now() - DISCOVERED_PID_GRACE;
+        for found in filter.pids.lock().unwrap().discovered.values_mut() {
+            *found = expired;
+        }
+    }
+
+    #[test]
+    fn restart_keeps_remote_process_found_before_refresh() {
+        let filter = filter(LogFilterOptions {
+            package: Some("com.example.app".to_string()),
+            ..Default::default()
+        });
+        filter.set_pids(HashSet::from([100]));
+
+        // 应用重启：日志中先出现新主进程和 :remote 的 Start proc，刷新结果还未包含它们
+        filter.add_pid(150);
+        filter.add_pid(151);
+        filter.set_pids(HashSet::from([100]));
+        assert!(filter.matches(&entry(151, LogLevel::Info, "Demo", "remote", None)));
+
+        // 之后的刷新查询到两个进程，超过宽限期也不会移除仍在运行的进程
+        expire_discovered(&filter);
+        filter.set_pids(HashSet::from([150, 151]));
+        assert!(filter.matches(&entry(150, LogLevel::Info, "Demo", "main", None)));
+        assert!(filter.matches(&entry(151, LogLevel::Info, "Demo", "remote", None)));
+        assert!(!filter.matches(&entry(100, LogLevel::Info, "Demo", "old", None)));
+
+        // :remote 退出后下一次刷新才移除
+        filter.set_pids(HashSet::from([150]));
+        assert!(!filter.matches(&entry(151, LogLevel::Info, "Demo", "remote", None)));
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        let invalid = [
+            LogFilterOptions { tag: Some("(".to_string()), ..Default::default() },
+            LogFilterOptions { message: Some("[a-".to_string()), ..Default::default() },
+            LogFilterOptions { domain: Some("JSAPP".to_string()), ..Default::default() },
+        ];
+        for options in invalid {
+            assert!(LogFilter::new(&options).is_err(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn parses_level_letters() {
+        let letters: Vec<Option<char>> = ["V", "D", "I", "W", "E", "F", "A", "S", ""]
+            .iter()
+            .map(|l| LogLevel::from_letter(l).map(|level| level.letter()))
+            .collect();
+        assert_eq!(
+            letters,
+            [Some('V'), Some('D'), Some('I'), Some('W'), Some('E'), Some('F'), Some('F'), None, None]
+        );
+    }
+
+    #[test]
+    fn finds_package_processes_in_ps_output() {
+        let ps = "  PID NAME\n\
+                  \x20 612 system_server\n\
+                  8123 com.example.app\n\
+                  8190 com.example.app:remote\n\
+                  8200 com.example.application\n\
+                  8201 com.example\n";
+        assert_eq!(parse_package_pids(ps, "com.example.app"), HashSet::from([8123, 8190]));
+        assert!(parse_package_pids(ps, "com.example.missing").is_empty());
+        assert!(parse_package_pids("", "com.example.app").is_empty());
+    }
+}
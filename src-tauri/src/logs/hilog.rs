@@ -0,0 +1,111 @@
+//! HarmonyOS hilog 解析
+//!
+//! 行格式与 logcat threadtime 相同，只是标签前多了日志类型和 domain：
+//! `MM-DD HH:MM:SS.mmm  PID  TID LEVEL A03d00/JSAPP: MESSAGE`
+
+use super::filter::LogEntry;
+use super::logcat::parse_threadtime;
+use super::LineParser;
+
+/// domain 只取低 20 位：`A03d00`、`0xD003d00`、`0x3d00` 都表示同一个 domain
+pub fn parse_domain(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    // hilog 输出中首位是大写的日志类型（A 应用 / C 系统核心 / ...），后面是小写十六进制
+    let hex = match hex.chars().next() {
+        Some(c) if hex.len() == 6 && c.is_ascii_uppercase() => &hex[1..],
+        _ => hex,
+    };
+    u32::from_str_radix(hex, 16).ok().map(|d| d & 0xFFFFF)
+}
+
+pub fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut entry = parse_threadtime(line)?;
+    if let Some((domain, tag)) = entry.tag.split_once('/') {
+        if parse_domain(domain).is_some() {
+            entry.domain = Some(domain.to_string());
+            entry.tag = tag.to_string();
+        }
+    }
+    Some(entry)
+}
+
+pub struct HilogParser;
+
+impl LineParser for HilogParser {
+    fn parse(&mut self, line: &str) -> Option<LogEntry> {
+        parse_line(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::filter::LogLevel;
+
+    type Expected<'a> = (LogLevel, Option<&'a str>, &'a str, &'a str);
+
+    #[test]
+    fn parses_hilog_lines() {
+        let cases: &[(&str, Option<Expected>)] = &[
+            (
+                "08-05 12:00:00.123  1588  1600 I A03d00/JSAPP: [Demo] onCreate",
+                Some((LogLevel::Info, Some("A03d00"), "JSAPP", "[Demo] onCreate")),
+            ),
+            (
+                "08-05 12:00:00.124   612   640 E C01800/AccountMgr: query failed: -1",
+                Some((LogLevel::Error, Some("C01800"), "AccountMgr", "query failed: -1")),
+            ),
+            // 标签本身含 '/'
+            (
+                "08-05 12:00:00.125   701   733 W C02d11/DeviceManager/Sub: retry",
+                Some((LogLevel::Warn, Some("C02d11"), "DeviceManager/Sub", "retry")),
+            ),
+            // '/' 前不是 domain 时保留原标签
+            (
+                "08-05 12:00:00.126   701   733 D Foo/Bar: not a domain",
+                Some((LogLevel::Debug, None, "Foo/Bar", "not a domain")),
+            ),
+            (
+                "08-05 12:00:00.127   701   733 F C03f00/MUSL-SIGCHAIN: signal_chain_handler call 2 rd sigchain action for signal: 11",
+                Some((
+                    LogLevel::Fatal,
+                    Some("C03f00"),
+                    "MUSL-SIGCHAIN",
+                    "signal_chain_handler call 2 rd sigchain action for signal: 11",
+                )),
+            ),
+            ("Hilog buffer size 262144", None),
+            ("08-05 12:00:00.128   701", None),
+            ("", None),
+        ];
+
+        for (line, expected) in cases {
+            let actual = parse_line(line).map(|e| (e.level, e.domain, e.tag, e.message));
+            let expected = expected.map(|(level, domain, tag, message)| {
+                (level, domain.map(str::to_string), tag.to_string(), message.to_string())
+            });
+            assert_eq!(actual, expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn parses_domains() {
+        let cases = [
+            ("A03d00", Some(0x3d00)),
+            ("C01800", Some(0x1800)),
+            ("0xD003d00", Some(0x3d00)),
+            ("0x3d00", Some(0x3d00)),
+            (" 3d00 ", Some(0x3d00)),
+            ("JSAPP", None),
+            ("0x", None),
+            ("", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_domain(value), expected, "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Android `logcat -v threadtime` 解析
+
+use super::filter::{LogEntry, LogFilter, LogLevel};
+use super::LineParser;
+use std::sync::Arc;
+
+/// 解析一行 threadtime 输出：
+/// `MM-DD HH:MM:SS.mmm  PID  TID LEVEL TAG     : MESSAGE`
+pub fn parse_threadtime(line: &str) -> Option<LogEntry> {
+    let mut rest = line.trim_end_matches(['\r', '\n']);
+    let mut fields = Vec::with_capacity(5);
+    for _ in 0..5 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+
+    let level = LogLevel::from_letter(fields[4])?;
+    let pid = fields[2].parse().ok()?;
+    let tid = fields[3].parse().ok()?;
+    if !fields[0].contains('-') || !fields[1].contains(':') {
+        return None;
+    }
+
+    // 标签会被补齐空格，以 ": " 与消息分隔；消息为空时行尾只有 ":"
+    let (tag, message) = match rest.find(": ") {
+        Some(pos) => (&rest[..pos], &rest[pos + 2..]),
+        None => (rest.strip_suffix(':').unwrap_or(rest), ""),
+    };
+
+    Some(LogEntry {
+        timestamp: format!("{} {}", fields[0], fields[1]),
+        pid,
+        tid,
+        level,
+        tag: tag.trim_end().to_string(),
+        message: message.to_string(),
+        domain: None,
+    })
+}
+
+/// ActivityManager 在应用进程启动时输出 `Start proc 1234:com.example/u0a123 ...`
+fn started_pid(entry: &LogEntry, package: &str) -> Option<u32> {
+    if entry.tag != "ActivityManager" {
+        return None;
+    }
+    let rest = entry.message.strip_prefix("Start proc ")?;
+    let (pid, process) = rest.split_once(':')?;
+    let process = process.split(['/', ' ']).next()?;
+    // 应用的子进程名形如 com.example:remote
+    if process == package || process.starts_with(&format!("{}:", package)) {
+        pid.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// logcat 行解析器，同时从 ActivityManager 日志中发现被过滤应用的新进程
+pub struct LogcatParser {
+    filter: Arc<LogFilter>,
+}
+
+impl LogcatParser {
+    pub fn new(filter: Arc<LogFilter>) -> Self {
+        Self { filter }
+    }
+}
+
+impl LineParser for LogcatParser {
+    fn parse(&mut self, line: &str) -> Option<LogEntry> {
+        let entry = parse_threadtime(line)?;
+        if let Some(pid) = self.filter.package().and_then(|p| started_pid(&entry, p)) {
+            self.filter.add_pid(pid);
+        }
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Expected<'a> = (&'a str, u32, u32, LogLevel, &'a str, &'a str);
+
+    #[test]
+    fn parses_threadtime_lines() {
+        let cases: &[(&str, Option<Expected>)] = &[
+            (
+                "03-12 09:41:07.512  8123  8123 E AndroidRuntime: FATAL EXCEPTION: main",
+                Some(("03-12 09:41:07.512", 8123, 8123, LogLevel::Error, "AndroidRuntime", "FATAL EXCEPTION: main")),
+            ),
+            (
+                "03-12 09:41:07.530   612  1420 I ActivityManager: Start proc 8123:com.example.app/u0a231 for pre-top-activity {com.example.app/com.example.app.MainActivity}",
+                Some((
+                    "03-12 09:41:07.530",
+                    612,
+                    1420,
+                    LogLevel::Info,
+                    "ActivityManager",
+                    "Start proc 8123:com.example.app/u0a231 for pre-top-activity {com.example.app/com.example.app.MainActivity}",
+                )),
+            ),
+            // 标签补齐空格
+            (
+                "03-12 09:41:08.100  2211  2260 D OkHttp  : --> GET https://example.com/api",
+                Some(("03-12 09:41:08.100", 2211, 2260, LogLevel::Debug, "OkHttp", "--> GET https://example.com/api")),
+            ),
+            // 空消息
+            (
+                "03-12 09:41:08.101  2211  2260 W Choreographer:",
+                Some(("03-12 09:41:08.101", 2211, 2260, LogLevel::Warn, "Choreographer", "")),
+            ),
+            // Assert 视为 Fatal，Windows 换行
+            (
+                "03-12 09:41:08.102   330   330 A libc    : Fatal signal 11 (SIGSEGV)\r",
+                Some(("03-12 09:41:08.102", 330, 330, LogLevel::Fatal, "libc", "Fatal signal 11 (SIGSEGV)")),
+            ),
+            (
+                "03-12 09:41:08.103  1000  1000 V         : empty tag",
+                Some(("03-12 09:41:08.103", 1000, 1000, LogLevel::Verbose, "", "empty tag")),
+            ),
+            ("--------- beginning of main", None),
+            ("\tat com.example.app.MainActivity.onCreate(MainActivity.kt:21)", None),
+            ("", None),
+            ("03-12 09:41:08.104  abc  1000 I Tag: bad pid", None),
+            ("03-12 09:41:08.105  1000  1000 X Tag: bad level", None),
+            ("03-12 09:41:08.106  1000", None),
+            ("waiting for device ... adb server version (41) doesn't match", None),
+        ];
+
+        for (line, expected) in cases {
+            let actual = parse_threadtime(line).map(|e| {
+                assert_eq!(e.domain, None);
+                (e.timestamp, e.pid, e.tid, e.level, e.tag, e.message)
+            });
+            let expected = expected.map(|(ts, pid, tid, level, tag, message)| {
+                (ts.to_string(), pid, tid, level, tag.to_string(), message.to_string())
+            });
+            assert_eq!(actual, expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn detects_started_package_processes() {
+        let cases = [
+            ("ActivityManager", "Start proc 8123:com.example.app/u0a231 for activity", Some(8123)),
+            ("ActivityManager", "Start proc 8190:com.example.app:remote/u0a231 for service", Some(8190)),
+            ("ActivityManager", "Start proc 8200:com.example.application/u0a232 for activity", None),
+            ("ActivityManager", "Killing 8123:com.example.app/u0a231 (adj 900): remove task", None),
+            ("ActivityTaskManager", "Start proc 8123:com.example.app/u0a231 for activity", None),
+        ];
+        for (tag, message, expected) in cases {
+            let line = format!("03-12 09:41:07.530   612  1420 I {}: {}", tag, message);
+            let entry = parse_threadtime(&line).unwrap();
+            assert_eq!(started_pid(&entry, "com.example.app"), expected, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,673 @@
+//! 设备日志会话
+//!
+//! 每次开始抓取日志都会创建一个 [`LogSession`]，按会话 id 注册在全局表中，
+//! 同一时间可以有多个设备（或同一设备的多个）会话独立运行、读取和停止。
+//!
+//! 读取到的日志先进入会话的有界队列，再由 [`spawn_emitter`] 按批次通过 `log-lines` 事件推送给前端。
+//! 前端处理完每一批后调用 [`ack`] 确认，未确认的批次达到 [`MAX_IN_FLIGHT_BATCHES`] 时暂停推送，
+//! 新的行留在队列中；前端处理不过来导致队列写满时，最旧的行被丢弃并计数，随下一批一起上报。
+//!
+//! 每行输出先由 [`LineParser`] 解析为 [`LogEntry`]，多行消息合并为一条，再按 [`LogFilter`] 过滤后入队。
+//!
+//! 启用崩溃捕获时，每条日志（不受过滤条件影响）还会交给 [`crash::CrashDetector`]，识别到崩溃后推送 `device-crash` 事件。
+//!
+//! 会话可以同时通过 [`recorder::LogRecorder`] 写入磁盘。
+//!
+//! 会话持有日志进程，停止、设备断开或应用退出时会结束该进程，
+//! 进程退出后推送 `log-session-ended` 事件报告原因和退出码。
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Child;
+
+use crate::commands::device::Platform;
+
+pub mod crash;
+mod filter;
+pub mod hilog;
+pub mod logcat;
+pub mod recorder;
+
+pub use filter::{parse_package_pids, LogEntry, LogFilter, LogFilterOptions};
+
+pub type SessionId = u64;
+
+/// 每个会话最多排队的行数，超出后丢弃最旧的行
+const MAX_QUEUED_LINES: usize = 10_000;
+/// 单个事件最多携带的行数
+const MAX_BATCH_LINES: usize = 500;
+/// 最多允许多少批已推送但前端尚未确认
+pub const MAX_IN_FLIGHT_BATCHES: usize = 2;
+/// 超过该时间没有收到确认时，认为事件已丢失（如页面刷新），恢复推送
+const ACK_TIMEOUT: Duration = Duration::from_secs(5);
+/// 推送间隔
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// 按包名过滤时刷新进程号的间隔
+pub const PID_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// 超过该时间没有新行时，认为当前多行消息已结束
+const FOLD_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// 把日志进程输出的一行解析为结构化条目；无法解析的行返回 None，按上一条的续行处理
+pub trait LineParser: Send {
+    fn parse(&mut self, line: &str) -> Option<LogEntry>;
+}
+
+/// 把连续的行合并为完整的日志条目：头部相同的行和无法解析的续行都并入上一条
+#[derive(Default)]
+struct LineFolder {
+    pending: Option<LogEntry>,
+}
+
+impl LineFolder {
+    /// 处理一行及其解析结果，返回因此而完整的上一条
+    fn push(&mut self, parsed: Option<LogEntry>, line: &str) -> Option<LogEntry> {
+        match parsed {
+            Some(entry) => match self.pending.as_mut() {
+                Some(current) if current.same_header(&entry) => {
+                    current.message.push('\n');
+                    current.message.push_str(&entry.message);
+                    None
+                }
+                _ => self.pending.replace(entry),
+            },
+            // 缓冲区分隔行（--------- beginning of main）直接忽略，其余视为续行
+            None if line.starts_with("---------") => None,
+            None => {
+                if let Some(current) = self.pending.as_mut() {
+                    current.message.push('\n');
+                    current.message.push_str(line.trim_end());
+                }
+                None
+            }
+        }
+    }
+
+    /// 取出尚未完整的当前条目
+    fn flush(&mut self) -> Option<LogEntry> {
+        self.pending.take()
+    }
+}
+
+#[derive(Default)]
+struct LogQueue {
+    lines: VecDeque<LogEntry>,
+    /// 上次推送后丢弃的行数
+    dropped: u64,
+    total_dropped: u64,
+    /// 已推送但前端尚未确认的批次数
+    in_flight: usize,
+    /// 最近一次推送或确认的时间
+    last_progress: Option<Instant>,
+}
+
+/// 会话结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopReason {
+    /// 用户主动停止
+    Stopped,
+    /// 设备已断开或模拟器已关闭
+    Disconnected,
+    /// 应用退出
+    AppExit,
+    /// 日志进程自行退出
+    Exited,
+}
+
+/// `log-session-ended` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEnded {
+    pub session_id: SessionId,
+    pub platform: Platform,
+    pub device_id: String,
+    pub reason: StopReason,
+    /// 被信号结束时为 None
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    /// 进程 stderr 的末尾部分
+    pub message: String,
+}
+
+pub struct LogSession {
+    pub id: SessionId,
+    pub platform: Platform,
+    pub device_id: String,
+    running: AtomicBool,
+    filter: Arc<LogFilter>,
+    queue: Mutex<LogQueue>,
+    child: Mutex<Option<Child>>,
+    recorder: Mutex<Option<recorder::LogRecorder>>,
+    stop_reason: Mutex<Option<StopReason>>,
+    ended: Mutex<Option<SessionEnded>>,
+}
+
+impl LogSession {
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 追加一条符合过滤条件的日志，队列已满时丢弃最旧的一条
+    fn push(&self, entry: LogEntry) {
+        if !self.filter.matches(&entry) {
+            return;
+        }
+        if let Some(recorder) = self.recorder.lock().unwrap().as_ref() {
+            recorder.record(&entry);
+        }
+        let mut queue = self.queue.lock().unwrap();
+        if queue.lines.len() >= MAX_QUEUED_LINES {
+            queue.lines.pop_front();
+            queue.dropped += 1;
+            queue.total_dropped += 1;
+        }
+        queue.lines.push_back(entry);
+    }
+
+    /// 取出下一批日志；没有新内容或前端尚未确认之前的批次时返回 None
+    fn next_batch(&self) -> Option<LogBatch> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.lines.is_empty() && queue.dropped == 0 {
+            return None;
+        }
+        if queue.in_flight >= MAX_IN_FLIGHT_BATCHES {
+            if queue.last_progress.is_some_and(|t| t.elapsed() < ACK_TIMEOUT) {
+                return None;
+            }
+            queue.in_flight = 0;
+        }
+        queue.in_flight += 1;
+        queue.last_progress = Some(Instant::now());
+        let count = queue.lines.len().min(MAX_BATCH_LINES);
+        let entries = queue.lines.drain(..count).collect();
+        let dropped = std::mem::take(&mut queue.dropped);
+        Some(LogBatch {
+            session_id: self.id,
+            platform: self.platform,
+            device_id: self.device_id.clone(),
+            entries,
+            dropped,
+            total_dropped: queue.total_dropped,
+        })
+    }
+
+    /// 前端确认处理完一批
+    fn ack(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.in_flight = queue.in_flight.saturating_sub(1);
+        queue.last_progress = Some(Instant::now());
+    }
+
+    /// 队列中是否还有未推送的内容
+    fn is_drained(&self) -> bool {
+        let queue = self.queue.lock().unwrap();
+        queue.lines.is_empty() && queue.dropped == 0
+    }
+
+    /// 请求结束会话：结束日志进程，读取任务随后会因 EOF 退出并回收进程
+    fn request_stop(&self, reason: StopReason) {
+        self.running.store(false, Ordering::SeqCst);
+        self.stop_reason.lock().unwrap().get_or_insert(reason);
+        if let Some(child) = self.child.lock().unwrap().as_mut() {
+            let _ = child.start_kill();
+        }
+    }
+
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            id: self.id,
+            platform: self.platform,
+            device_id: self.device_id.clone(),
+            running: self.is_running(),
+            recording_dir: self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|r| r.dir.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// `log-lines` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct LogBatch {
+    pub session_id: SessionId,
+    pub platform: Platform,
+    pub device_id: String,
+    pub entries: Vec<LogEntry>,
+    /// 自上一批以来因队列已满丢弃的条数
+    pub dropped: u64,
+    /// 会话开始以来累计丢弃的条数
+    pub total_dropped: u64,
+}
+
+/// `device-crash` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceCrash {
+    pub session_id: SessionId,
+    pub platform: Platform,
+    pub device_id: String,
+    #[serde(flatten)]
+    pub report: crash::CrashReport,
+}
+
+/// 返回给前端的会话信息
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: SessionId,
+    pub platform: Platform,
+    pub device_id: String,
+    pub running: bool,
+    /// 正在录制时为保存目录
+    pub recording_dir: Option<String>,
+}
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+lazy_static::lazy_static! {
+    static ref SESSIONS: Mutex<HashMap<SessionId, Arc<LogSession>>> = Mutex::new(HashMap::new());
+}
+
+/// 创建并注册新会话
+pub fn register(platform: Platform, device_id: &str, filter: Arc<LogFilter>) -> Arc<LogSession> {
+    let session = Arc::new(LogSession {
+        id: NEXT_SESSION_ID.fetch_add(1, Ordering::SeqCst),
+        platform,
+        device_id: device_id.to_string(),
+        running: AtomicBool::new(true),
+        filter,
+        queue: Mutex::new(LogQueue::default()),
+        child: Mutex::new(None),
+        recorder: Mutex::new(None),
+        stop_reason: Mutex::new(None),
+        ended: Mutex::new(None),
+    });
+    SESSIONS.lock().unwrap().insert(session.id, session.clone());
+    session
+}
+
+pub fn list() -> Vec<SessionInfo> {
+    let mut sessions: Vec<_> = SESSIONS.lock().unwrap().values().map(|s| s.info()).collect();
+    sessions.sort_by_key(|s| s.id);
+    sessions
+}
+
+/// 停止会话；进程退出且日志推送完毕后会话从注册表移除
+pub fn stop(id: SessionId, reason: StopReason) -> bool {
+    let session = SESSIONS.lock().unwrap().get(&id).cloned();
+    match session {
+        Some(session) => {
+            session.request_stop(reason);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 前端处理完一批 `log-lines` 后确认，返回会话是否存在
+pub fn ack(id: SessionId) -> bool {
+    let session = SESSIONS.lock().unwrap().get(&id).cloned();
+    match session {
+        Some(session) => {
+            session.ack();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 停止满足条件的全部会话，返回被停止的会话 id
+pub fn stop_where<F: Fn(&LogSession) -> bool>(reason: StopReason, predicate: F) -> Vec<SessionId> {
+    let sessions: Vec<Arc<LogSession>> = SESSIONS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|s| predicate(s))
+        .cloned()
+        .collect();
+    sessions
+        .iter()
+        .map(|session| {
+            session.request_stop(reason);
+            session.id
+        })
+        .collect()
+}
+
+/// 开始把会话写入磁盘，返回保存目录；已在录制时先结束之前的录制
+pub fn start_recording(id: SessionId, options: &recorder::RecordOptions) -> Result<String, String> {
+    let session = SESSIONS
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Log session {} not found", id))?;
+    let recorder = recorder::LogRecorder::start(&session.device_id, options)?;
+    let dir = recorder.dir.to_string_lossy().to_string();
+    *session.recorder.lock().unwrap() = Some(recorder);
+    Ok(dir)
+}
+
+/// 结束录制，返回会话是否存在
+pub fn stop_recording(id: SessionId) -> bool {
+    let session = SESSIONS.lock().unwrap().get(&id).cloned();
+    match session {
+        Some(session) => {
+            session.recorder.lock().unwrap().take();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 设备断开或模拟器关闭时结束该设备上的全部会话
+pub fn stop_device(device_id: &str) {
+    stop_where(StopReason::Disconnected, |s| s.device_id == device_id);
+}
+
+/// 应用退出时调用，同步结束所有日志进程
+pub fn stop_all() {
+    stop_where(StopReason::AppExit, |_| true);
+}
+
+/// 读取子进程 stdout 到会话中，并按批推送给前端。
+/// 子进程须已设置 stdout / stderr 为 piped；进程退出后推送 `log-session-ended`
+pub fn spawn_process<P: LineParser + 'static>(
+    session: Arc<LogSession>,
+    mut child: Child,
+    mut parser: P,
+    crash_capture: Option<crash::CrashCapture>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to capture stdout".to_string())?;
+    let stderr = child.stderr.take();
+    *session.child.lock().unwrap() = Some(child);
+
+    spawn_emitter(session.clone(), app.clone());
+
+    tokio::spawn(async move {
+        let mut detector = crash_capture.as_ref().map(|_| crash::CrashDetector::default());
+        // 完整的一条日志：先做崩溃识别，再按过滤条件入队
+        let accept = |entry: LogEntry, detector: &mut Option<crash::CrashDetector>| {
+            if let (Some(detector), Some(capture)) = (detector.as_mut(), &crash_capture) {
+                for report in detector.observe(&entry) {
+                    report_crash(&session, capture, report, &app);
+                }
+            }
+            session.push(entry);
+        };
+
+        // stderr 只保留末尾部分，用于说明进程退出原因（如 device not found）
+        let stderr_task = tokio::spawn(async move {
+            let mut text = String::new();
+            if let Some(mut stderr) = stderr {
+                let _ = stderr.read_to_string(&mut text).await;
+            }
+            let start = text.len().saturating_sub(4096);
+            let start = (start..text.len()).find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+            text[start..].trim().to_string()
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        let mut folder = LineFolder::default();
+        loop {
+            let line = match tokio::time::timeout(FOLD_TIMEOUT, lines.next_line()).await {
+                Ok(Ok(Some(line))) => line,
+                Ok(_) => break,
+                // 暂时没有新行，当前条目已完整
+                Err(_) => {
+                    if let Some(entry) = folder.flush() {
+                        accept(entry, &mut detector);
+                    }
+                    if let (Some(report), Some(capture)) =
+                        (detector.as_mut().and_then(|d| d.flush_stale()), &crash_capture)
+                    {
+                        report_crash(&session, capture, report, &app);
+                    }
+                    continue;
+                }
+            };
+
+            if let Some(entry) = folder.push(parser.parse(&line), &line) {
+                accept(entry, &mut detector);
+            }
+        }
+        if let Some(entry) = folder.flush() {
+            accept(entry, &mut detector);
+        }
+        if let (Some(report), Some(capture)) = (detector.as_mut().and_then(|d| d.finish()), &crash_capture) {
+            report_crash(&session, capture, report, &app);
+        }
+
+        let child = session.child.lock().unwrap().take();
+        let status = match child {
+            Some(mut child) => child.wait().await.ok(),
+            None => None,
+        };
+        let message = stderr_task.await.unwrap_or_default();
+        let reason = session
+            .stop_reason
+            .lock()
+            .unwrap()
+            .unwrap_or(StopReason::Exited);
+
+        session.running.store(false, Ordering::SeqCst);
+        session.recorder.lock().unwrap().take();
+        *session.ended.lock().unwrap() = Some(SessionEnded {
+            session_id: session.id,
+            platform: session.platform,
+            device_id: session.device_id.clone(),
+            reason,
+            exit_code: status.and_then(|s| s.code()),
+            success: status.is_some_and(|s| s.success()),
+            message,
+        });
+    });
+
+    Ok(())
+}
+
+/// 在后台拉取崩溃文件（如需要）后推送 `device-crash`
+fn report_crash(
+    session: &LogSession,
+    capture: &crash::CrashCapture,
+    mut report: crash::CrashReport,
+    app: &tauri::AppHandle,
+) {
+    let capture = capture.clone();
+    let app = app.clone();
+    let session_id = session.id;
+    let platform = session.platform;
+    let device_id = session.device_id.clone();
+    tokio::task::spawn_blocking(move || {
+        capture.collect(&device_id, &mut report);
+        let _ = app.emit(
+            "device-crash",
+            DeviceCrash {
+                session_id,
+                platform,
+                device_id,
+                report,
+            },
+        );
+    });
+}
+
+/// 定时把会话中的日志批量推送给前端，进程退出且队列清空后推送结束事件并退出。
+/// 推送受前端确认限制，前端处理不过来时日志留在队列中，由队列上限决定丢弃
+fn spawn_emitter(session: Arc<LogSession>, app: tauri::AppHandle) {
+    tokio::spawn(async move {
+        loop {
+            // 先读状态再取数据，保证退出前写入的行都能推送出去
+            let ended = session.ended.lock().unwrap().clone();
+            while let Some(batch) = session.next_batch() {
+                let _ = app.emit("log-lines", &batch);
+                if batch.entries.len() < MAX_BATCH_LINES {
+                    break;
+                }
+            }
+            // 主动停止后前端不再接收，剩余的行直接丢弃
+            let abandoned = matches!(ended.as_ref().map(|e| e.reason), Some(StopReason::Stopped | StopReason::AppExit));
+            if let Some(ended) = ended.filter(|_| abandoned || session.is_drained()) {
+                let _ = app.emit("log-session-ended", &ended);
+                // 结束事件发出后才移除，保证最后几批的确认仍能找到会话
+                SESSIONS.lock().unwrap().remove(&session.id);
+                break;
+            }
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 依次送入各行，返回合并后的 (标签, 消息)
+    fn fold(lines: &[&str]) -> Vec<(String, String)> {
+        let mut folder = LineFolder::default();
+        let mut entries = Vec::new();
+        for line in lines {
+            entries.extend(folder.push(logcat::parse_threadtime(line), line));
+        }
+        entries.extend(folder.flush());
+        entries.into_iter().map(|e| (e.tag, e.message)).collect()
+    }
+
+    fn owned(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(t, m)| (t.to_string(), m.to_string())).collect()
+    }
+
+    #[test]
+    fn folds_lines_with_same_header() {
+        let lines = [
+            "--------- beginning of crash",
+            "03-12 09:41:07.512  8123  8123 E AndroidRuntime: FATAL EXCEPTION: main",
+            "03-12 09:41:07.512  8123  8123 E AndroidRuntime: Process: com.example.app, PID: 8123",
+            "03-12 09:41:07.512  8123  8123 E AndroidRuntime: java.lang.IllegalStateException: boom",
+            "03-12 09:41:07.512  8123  8123 E AndroidRuntime: \tat com.example.app.MainActivity.onCreate(MainActivity.kt:21)",
+            "03-12 09:41:07.530   612  1420 I ActivityManager: Process com.example.app (pid 8123) has died",
+        ];
+        assert_eq!(
+            fold(&lines),
+            owned(&[
+                (
+                    "AndroidRuntime",
+                    "FATAL EXCEPTION: main\nProcess: com.example.app, PID: 8123\n\
+                     java.lang.IllegalStateException: boom\n\
+                     \tat com.example.app.MainActivity.onCreate(MainActivity.kt:21)",
+                ),
+                ("ActivityManager", "Process com.example.app (pid 8123) has died"),
+            ])
+        );
+    }
+
+    #[test]
+    fn same_header_in_other_thread_is_a_new_entry() {
+        let lines = [
+            "03-12 09:41:08.001  8200  8200 D Demo: first",
+            "03-12 09:41:08.001  8200  8201 D Demo: second",
+            "03-12 09:41:08.001  8200  8200 D Demo: third",
+        ];
+        assert_eq!(fold(&lines), owned(&[("Demo", "first"), ("Demo", "second"), ("Demo", "third")]));
+    }
+
+    #[test]
+    fn unparsed_lines_are_continuations() {
+        let lines = [
+            "stray line before any entry",
+            "08-05 12:00:00.123  1588  1600 I A03d00/JSAPP: response body:",
+            "{\"code\": 0,   ",
+            "  \"data\": []}",
+            "--------- beginning of main",
+            "08-05 12:00:00.200  1588  1600 I A03d00/JSAPP: done",
+        ];
+        assert_eq!(
+            fold(&lines),
+            owned(&[
+                ("A03d00/JSAPP", "response body:\n{\"code\": 0,\n  \"data\": []}"),
+                ("A03d00/JSAPP", "done"),
+            ])
+        );
+    }
+
+    fn session() -> LogSession {
+        LogSession {
+            id: 1,
+            platform: Platform::Android,
+            device_id: "emulator-5554".to_string(),
+            running: AtomicBool::new(true),
+            filter: Arc::new(LogFilter::new(&LogFilterOptions::default()).unwrap()),
+            queue: Mutex::new(LogQueue::default()),
+            child: Mutex::new(None),
+            recorder: Mutex::new(None),
+            stop_reason: Mutex::new(None),
+            ended: Mutex::new(None),
+        }
+    }
+
+    fn push_lines(session: &LogSession, count: usize) {
+        for i in 0..count {
+            let line = format!("03-12 09:41:08.001  8200  8200 D Demo: line {}", i);
+            session.push(logcat::parse_threadtime(&line).unwrap());
+        }
+    }
+
+    #[test]
+    fn emission_waits_for_acks() {
+        let session = session();
+        push_lines(&session, MAX_BATCH_LINES * 4);
+        for _ in 0..MAX_IN_FLIGHT_BATCHES {
+            assert_eq!(session.next_batch().unwrap().entries.len(), MAX_BATCH_LINES);
+        }
+        // 未确认前不再推送，行留在队列中
+        assert!(session.next_batch().is_none());
+        assert!(!session.is_drained());
+
+        session.ack();
+        assert!(session.next_batch().is_some());
+        assert!(session.next_batch().is_none());
+    }
+
+    #[test]
+    fn unacked_batches_expire() {
+        let session = session();
+        push_lines(&session, MAX_BATCH_LINES * 3);
+        for _ in 0..MAX_IN_FLIGHT_BATCHES {
+            session.next_batch().unwrap();
+        }
+        assert!(session.next_batch().is_none());
+        session.queue.lock().unwrap().last_progress = Some(Instant::now() - ACK_TIMEOUT);
+        assert!(session.next_batch().is_some());
+    }
+
+    #[test]
+    fn full_queue_drops_oldest_while_waiting() {
+        let session = session();
+        push_lines(&session, 1);
+        session.next_batch().unwrap();
+        session.ack();
+        push_lines(&session, MAX_QUEUED_LINES + 5);
+        let batch = session.next_batch().unwrap();
+        assert_eq!(batch.dropped, 5);
+        assert_eq!(batch.total_dropped, 5);
+        assert_eq!(batch.entries[0].message, "line 5");
+    }
+
+    #[test]
+    fn flush_returns_pending_entry_once() {
+        let mut folder = LineFolder::default();
+        let line = "03-12 09:41:08.001  8200  8200 D Demo: only";
+        assert!(folder.push(logcat::parse_threadtime(line), line).is_none());
+        assert_eq!(folder.flush().map(|e| e.message).as_deref(), Some("only"));
+        assert!(folder.flush().is_none());
+    }
+}
@@ -0,0 +1,459 @@
+//! 把日志会话写入磁盘
+//!
+//! 每个录制在独立线程中写文件，按大小或时长切分；切分出的旧文件可选 gzip 压缩。
+//! 文件名为 `{设备}_{开始时间}_{序号}.log`，压缩后追加 `.gz`；压缩过程中写入 `.gz.tmp`，完成后才改名。
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::filter::LogEntry;
+
+/// 写入线程来不及处理时最多排队的条数
+const MAX_PENDING_ENTRIES: usize = 10_000;
+
+/// 前端传入的录制选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RecordOptions {
+    /// 保存目录，默认为截图目录下的 logs
+    pub dir: Option<String>,
+    /// 单个文件达到该大小 (MB) 后切分
+    pub max_size_mb: Option<u64>,
+    /// 单个文件写入超过该时长 (分钟) 后切分
+    pub max_minutes: Option<u64>,
+    /// 压缩切分出的旧文件
+    pub gzip: bool,
+}
+
+/// 录制文件的保存目录
+pub fn recording_dir(dir: Option<&str>) -> Result<PathBuf, String> {
+    match dir.filter(|d| !d.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => crate::commands::settings::get_screenshot_dir()
+            .map(|dir| PathBuf::from(dir).join("logs"))
+            .ok_or_else(|| "Cannot find log recording directory".to_string()),
+    }
+}
+
+fn format_entry(entry: &LogEntry) -> String {
+    let tag = match &entry.domain {
+        Some(domain) => format!("{}/{}", domain, entry.tag),
+        None => entry.tag.clone(),
+    };
+    let level = entry.level.letter();
+    entry
+        .message
+        .lines()
+        .map(|line| format!("{} {:5} {:5} {} {}: {}\n", entry.timestamp, entry.pid, entry.tid, level, tag, line))
+        .collect()
+}
+
+/// 正在进行的录制，drop 时关闭发送端，写入线程写完剩余内容后退出
+pub struct LogRecorder {
+    sender: SyncSender<LogEntry>,
+    dropped: Arc<AtomicU64>,
+    pub dir: PathBuf,
+}
+
+impl LogRecorder {
+    pub fn start(device_id: &str, options: &RecordOptions) -> Result<Self, String> {
+        if options.max_size_mb == Some(0) || options.max_minutes == Some(0) {
+            return Err("Rotation size and interval must be greater than zero".to_string());
+        }
+
+        let dir = recording_dir(options.dir.as_deref())?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create log recording directory: {}", e))?;
+
+        let device: String = device_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let mut writer = RotatingWriter {
+            dir: dir.clone(),
+            prefix: format!("{}_{}", device, chrono::Local::now().format("%Y%m%d_%H%M%S")),
+            max_size: options.max_size_mb.map(|mb| mb * 1024 * 1024),
+            max_age: options.max_minutes.map(|m| Duration::from_secs(m * 60)),
+            gzip: options.gzip,
+            part: 0,
+            current: None,
+            compressing: Vec::new(),
+        };
+        writer.open_next()?;
+
+        let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_ENTRIES);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = dropped.clone();
+        std::thread::spawn(move || run(writer, receiver, thread_dropped));
+
+        Ok(Self { sender, dropped, dir })
+    }
+
+    /// 写入一条日志；队列已满时丢弃并计数，不阻塞日志读取
+    pub fn record(&self, entry: &LogEntry) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(entry.clone()) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn run(mut writer: RotatingWriter, receiver: Receiver<LogEntry>, dropped: Arc<AtomicU64>) {
+    let mut reported = 0;
+    for entry in receiver {
+        let lost = dropped.load(Ordering::Relaxed);
+        if lost > reported {
+            let _ = writer.write(&format!("--- {} lines dropped ---\n", lost - reported));
+            reported = lost;
+        }
+        if writer.write(&format_entry(&entry)).is_err() {
+            break;
+        }
+    }
+    writer.close();
+}
+
+struct OpenFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+    opened_at: Instant,
+}
+
+struct RotatingWriter {
+    dir: PathBuf,
+    prefix: String,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    gzip: bool,
+    part: u32,
+    current: Option<OpenFile>,
+    /// 正在后台压缩的文件
+    compressing: Vec<JoinHandle<()>>,
+}
+
+impl RotatingWriter {
+    fn open_next(&mut self) -> Result<(), String> {
+        self.part += 1;
+        let path = self.dir.join(format!("{}_{:03}.log", self.prefix, self.part));
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create log file {:?}: {}", path, e))?;
+        self.current = Some(OpenFile {
+            path,
+            writer: BufWriter::new(file),
+            size: 0,
+            opened_at: Instant::now(),
+        });
+        Ok(())
+    }
+
+    fn needs_rotation(&self) -> bool {
+        let Some(file) = &self.current else {
+            return false;
+        };
+        self.max_size.is_some_and(|max| file.size >= max)
+            || self.max_age.is_some_and(|max| file.opened_at.elapsed() >= max)
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        if self.needs_rotation() {
+            if let Some(file) = self.current.take() {
+                self.finish_file(file, self.gzip);
+            }
+            self.open_next()?;
+        }
+
+        let file = self.current.as_mut().ok_or("Log file is closed")?;
+        file.writer
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write log file: {}", e))?;
+        file.size += text.len() as u64;
+        Ok(())
+    }
+
+    /// 关闭文件；需要压缩时在后台完成，不阻塞后续写入
+    fn finish_file(&mut self, mut file: OpenFile, gzip: bool) {
+        let _ = file.writer.flush();
+        drop(file.writer);
+        if gzip {
+            let path = file.path;
+            self.compressing.retain(|handle| !handle.is_finished());
+            self.compressing.push(std::thread::spawn(move || {
+                let _ = compress(&path);
+            }));
+        }
+    }
+
+    /// 最后一个文件保持未压缩，方便直接查看；等待进行中的压缩完成
+    fn close(&mut self) {
+        if let Some(file) = self.current.take() {
+            self.finish_file(file, false);
+        }
+        for handle in self.compressing.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 压缩为 `.gz`：先写入临时文件，完成后改名并删除原文件，列表中不会出现不完整的压缩文件
+fn compress(path: &Path) -> std::io::Result<()> {
+    let mut gz_name = path.as_os_str().to_os_string();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+    let mut tmp_name = gz_path.as_os_str().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let result = (|| {
+        let mut input = File::open(path)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(&tmp_path)?), Compression::default());
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?.flush()
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    std::fs::rename(&tmp_path, &gz_path)?;
+    std::fs::remove_file(path)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    /// 最后修改时间 (RFC 3339)
+    pub modified: String,
+    pub compressed: bool,
+}
+
+/// 目录下的全部录制文件，最新的在前
+pub fn list_recordings(dir: &Path) -> Result<Vec<RecordingInfo>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read log recording directory: {}", e))?;
+
+    let mut recordings: Vec<(std::time::SystemTime, RecordingInfo)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let compressed = name.ends_with(".log.gz");
+            if !compressed && !name.ends_with(".log") {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            Some((
+                modified,
+                RecordingInfo {
+                    path: entry.path().to_string_lossy().to_string(),
+                    name,
+                    size: metadata.len(),
+                    modified: chrono::DateTime::<chrono::Local>::from(modified).to_rfc3339(),
+                    compressed,
+                },
+            ))
+        })
+        .collect();
+
+    recordings.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(recordings.into_iter().map(|(_, info)| info).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::filter::LogLevel;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+
+    /// 临时录制目录，drop 时删除
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "simhub_recorder_{}_{}",
+                std::process::id(),
+                SEQ.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn writer(&self, max_size: Option<u64>, max_age: Option<Duration>, gzip: bool) -> RotatingWriter {
+            let mut writer = RotatingWriter {
+                dir: self.0.clone(),
+                prefix: "emulator-5554_20240101_120000".to_string(),
+                max_size,
+                max_age,
+                gzip,
+                part: 0,
+                current: None,
+                compressing: Vec::new(),
+            };
+            writer.open_next().unwrap();
+            writer
+        }
+
+        /// 按文件名排序的 (文件名, 解压后的内容)
+        fn files(&self) -> Vec<(String, String)> {
+            let mut files: Vec<_> = std::fs::read_dir(&self.0)
+                .unwrap()
+                .flatten()
+                .map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let bytes = std::fs::read(entry.path()).unwrap();
+                    let content = if name.ends_with(".gz") {
+                        let mut text = String::new();
+                        GzDecoder::new(&bytes[..]).read_to_string(&mut text).unwrap();
+                        text
+                    } else {
+                        String::from_utf8(bytes).unwrap()
+                    };
+                    (name, content)
+                })
+                .collect();
+            files.sort();
+            files
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: "01-01 12:00:00.000".to_string(),
+            pid: 100,
+            tid: 101,
+            level: LogLevel::Info,
+            tag: "Demo".to_string(),
+            message: message.to_string(),
+            domain: None,
+        }
+    }
+
+    fn owned(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(n, c)| (n.to_string(), c.to_string())).collect()
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let dir = TestDir::new();
+        let mut writer = dir.writer(Some(10), None, false);
+        for line in ["aaaaaa\n", "bbbbbb\n", "cc\n", "dddddddddddd\n", "e\n"] {
+            writer.write(line).unwrap();
+        }
+        writer.close();
+        assert_eq!(
+            dir.files(),
+            owned(&[
+                ("emulator-5554_20240101_120000_001.log", "aaaaaa\nbbbbbb\n"),
+                ("emulator-5554_20240101_120000_002.log", "cc\ndddddddddddd\n"),
+                ("emulator-5554_20240101_120000_003.log", "e\n"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let dir = TestDir::new();
+        let max_age = Duration::from_secs(60);
+        let mut writer = dir.writer(None, Some(max_age), false);
+        writer.write("first\n").unwrap();
+        writer.write("second\n").unwrap();
+        writer.current.as_mut().unwrap().opened_at = Instant::now() - max_age;
+        writer.write("third\n").unwrap();
+        writer.close();
+        assert_eq!(
+            dir.files(),
+            owned(&[
+                ("emulator-5554_20240101_120000_001.log", "first\nsecond\n"),
+                ("emulator-5554_20240101_120000_002.log", "third\n"),
+            ])
+        );
+    }
+
+    #[test]
+    fn compresses_rotated_files_but_not_the_last() {
+        let dir = TestDir::new();
+        let mut writer = dir.writer(Some(4), None, true);
+        for line in ["one\n", "two\n", "three\n"] {
+            writer.write(line).unwrap();
+        }
+        writer.close();
+        assert_eq!(
+            dir.files(),
+            owned(&[
+                ("emulator-5554_20240101_120000_001.log.gz", "one\n"),
+                ("emulator-5554_20240101_120000_002.log.gz", "two\n"),
+                ("emulator-5554_20240101_120000_003.log", "three\n"),
+            ])
+        );
+
+        let recordings = list_recordings(&dir.0).unwrap();
+        let mut names: Vec<_> = recordings.iter().map(|r| (r.name.as_str(), r.compressed)).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                ("emulator-5554_20240101_120000_001.log.gz", true),
+                ("emulator-5554_20240101_120000_002.log.gz", true),
+                ("emulator-5554_20240101_120000_003.log", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_dropped_line_marker() {
+        let dir = TestDir::new();
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let dropped = Arc::new(AtomicU64::new(3));
+        sender.send(entry("first")).unwrap();
+        sender.send(entry("second\ncontinued")).unwrap();
+        drop(sender);
+        run(dir.writer(None, None, false), receiver, dropped);
+
+        let files = dir.files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].1,
+            "--- 3 lines dropped ---\n\
+             01-01 12:00:00.000   100   101 I Demo: first\n\
+             01-01 12:00:00.000   100   101 I Demo: second\n\
+             01-01 12:00:00.000   100   101 I Demo: continued\n"
+        );
+    }
+
+    #[test]
+    fn lists_only_recordings_newest_first() {
+        let dir = TestDir::new();
+        std::fs::write(dir.0.join("a_001.log"), "old").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(dir.0.join("a_002.log.gz"), "new").unwrap();
+        // 压缩中的临时文件和其他文件不列出
+        std::fs::write(dir.0.join("a_003.log.gz.tmp"), "partial").unwrap();
+        std::fs::write(dir.0.join("notes.txt"), "other").unwrap();
+
+        let names: Vec<_> = list_recordings(&dir.0).unwrap().into_iter().map(|r| r.name).collect();
+        assert_eq!(names, ["a_002.log.gz", "a_001.log"]);
+        assert!(list_recordings(&dir.0.join("missing")).unwrap().is_empty());
+    }
+}
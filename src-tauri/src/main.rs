@@ -3,9 +3,14 @@
 
 mod commands;
 mod db;
+mod inspect;
+mod logs;
+mod screenrecord;
+mod toolchain;
+mod usb;
 mod utils;
 
-use commands::{android, ios, harmony, settings, usb_device};
+use commands::{android, device, ios, harmony, settings, usb_device};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{TrayIconBuilder, TrayIconEvent},
@@ -17,6 +22,8 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
+            utils::app_log::init(app.handle());
+
             // 创建托盘菜单
             let show_item = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
             let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
@@ -76,6 +83,21 @@ fn main() {
             }
         })
         .invoke_handler(tauri::generate_handler![
+            // Cross-platform device commands
+            device::list_devices,
+            device::start_device,
+            device::stop_device,
+            device::delete_device,
+            device::wipe_device,
+            device::screenshot_device,
+            device::get_device_logs,
+            device::install_packages,
+            device::list_device_packages,
+            device::get_package_details,
+            device::package_action,
+            commands::packages::inspect_package,
+            device::list_screen_recordings,
+            device::batch_screenshot,
             // Android commands
             android::list_android_emulators,
             android::start_android_emulator,
@@ -83,8 +105,9 @@ fn main() {
             android::delete_android_emulator,
             android::wipe_android_data,
             android::screenshot_android,
+            android::start_recording_android,
+            android::stop_recording_android,
             android::start_logcat,
-            android::get_logcat_logs,
             android::stop_logcat,
             android::copy_image_to_clipboard,
             android::write_log_file,
@@ -101,6 +124,19 @@ fn main() {
             harmony::start_harmony_emulator,
             harmony::stop_harmony_emulator,
             harmony::screenshot_harmony,
+            harmony::start_recording_harmony,
+            harmony::stop_recording_harmony,
+            harmony::start_hilog,
+            harmony::stop_hilog,
+            commands::logs::list_log_sessions,
+            commands::logs::ack_log_lines,
+            commands::logs::start_log_recording,
+            commands::logs::stop_log_recording,
+            commands::logs::list_log_recordings,
+            commands::captures::list_captures,
+            commands::captures::tag_capture,
+            commands::captures::delete_capture,
+            commands::captures::reveal_capture,
             // Settings commands
             settings::get_settings,
             settings::save_settings,
@@ -108,12 +144,21 @@ fn main() {
             settings::check_path_exists,
             settings::get_emulator_launch_params,
             settings::save_emulator_launch_params,
+            settings::diagnose_environment,
             // USB device commands
             usb_device::list_usb_devices,
+            usb_device::start_usb_watcher,
+            usb_device::stop_usb_watcher,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|_app, _event| {
+            // 退出前结束所有日志进程和录屏，避免残留 adb logcat / screenrecord
+            if let tauri::RunEvent::Exit = _event {
+                logs::stop_all();
+                screenrecord::stop_all();
+            }
+
             // macOS: 点击 Dock 图标时显示窗口
             #[cfg(target_os = "macos")]
             if let tauri::RunEvent::Reopen { .. } = _event {
@@ -0,0 +1,529 @@
+//! 设备录屏
+//!
+//! 每台设备同一时间只有一个录制，在独立线程中分段运行设备端的录屏工具：
+//! 单段达到平台上限（Android `screenrecord` 为 3 分钟）后自动开始下一段，直到停止或达到总时长。
+//! HarmonyOS 的录屏服务没有常驻进程，由本地计时进程表示一段的时长，结束时再通知设备停止录制。
+//! 结束后把各段拉取到本地、写入截图历史并删除设备上的文件，推送 `screen-recording-finished` 事件。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::commands::captures::{self, CaptureDevice};
+use crate::commands::device::Platform;
+use crate::db::captures::CaptureKind;
+
+/// 请求停止后等待设备端写完文件的时间，超时后强制结束
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+/// 单段在该时间内失败退出视为录制无法进行
+const STARTUP_FAILURE_WINDOW: Duration = Duration::from_secs(2);
+
+/// 前端传入的录屏选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RecordingOptions {
+    /// 码率 (bit/s)
+    pub bit_rate: Option<u32>,
+    /// 分辨率，如 `1280x720`
+    pub size: Option<String>,
+    /// 总时长 (秒)，不设置时录制到手动停止
+    pub time_limit_secs: Option<u64>,
+    /// 保存目录，默认为截图目录
+    pub dir: Option<String>,
+}
+
+impl RecordingOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(bit_rate) = self.bit_rate {
+            if !(100_000..=200_000_000).contains(&bit_rate) {
+                return Err(format!("Invalid bit rate: {}", bit_rate));
+            }
+        }
+        if let Some(size) = self.size.as_deref() {
+            parse_size(size).ok_or_else(|| format!("Invalid size: {}", size))?;
+        }
+        if self.time_limit_secs == Some(0) {
+            return Err("Time limit must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    fn local_dir(&self) -> Result<PathBuf, String> {
+        match self.dir.as_deref().filter(|d| !d.is_empty()) {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => crate::commands::settings::get_screenshot_dir()
+                .map(PathBuf::from)
+                .ok_or_else(|| "Cannot find screenshot directory".to_string()),
+        }
+    }
+}
+
+/// `1280x720` -> (1280, 720)
+pub fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    let width: u32 = width.trim().parse().ok()?;
+    let height: u32 = height.trim().parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// 平台相关的录屏操作
+pub trait ScreenRecorder: Send + Sync + 'static {
+    fn platform(&self) -> Platform;
+
+    /// 单段录制的最长时间 (秒)
+    fn max_segment_secs(&self) -> u64;
+
+    /// 设备上保存录制文件的路径
+    fn remote_path(&self, name: &str) -> String;
+
+    /// 开始录制一段，返回的进程退出即表示该段结束；stderr 须设置为 piped
+    fn spawn(&self, device_id: &str, remote: &str, options: &RecordingOptions, secs: u64) -> Result<Child, String>;
+
+    /// 收到停止请求时调用，通知当前段尽快结束
+    fn interrupt(&self, device_id: &str, child: &mut Child);
+
+    /// 每段的进程退出后调用，用于结束设备端的录制
+    fn end_segment(&self, _device_id: &str, _remote: &str) {}
+
+    fn pull(&self, device_id: &str, remote: &str, local: &Path) -> Result<(), String>;
+
+    fn remove(&self, device_id: &str, remote: &str);
+
+    /// 写入录制历史时记录的设备信息
+    fn describe(&self, device_id: &str) -> CaptureDevice;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingResult {
+    pub platform: Platform,
+    pub device_id: String,
+    /// 拉取到本地的文件，按录制顺序排列
+    pub files: Vec<String>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub platform: Platform,
+    pub device_id: String,
+    /// 开始时间 (RFC 3339)
+    pub started_at: String,
+    pub segments: usize,
+}
+
+struct Recording {
+    platform: Platform,
+    device_id: String,
+    started_at: String,
+    stop: AtomicBool,
+    segments: Mutex<usize>,
+    thread: Mutex<Option<JoinHandle<RecordingResult>>>,
+}
+
+lazy_static::lazy_static! {
+    /// 设备 -> 正在进行的录制
+    static ref RECORDINGS: Mutex<HashMap<String, Arc<Recording>>> = Mutex::new(HashMap::new());
+}
+
+/// 开始录制，设备已在录制时返回错误
+pub fn start<R: ScreenRecorder>(
+    recorder: R,
+    device_id: &str,
+    options: RecordingOptions,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    options.validate()?;
+    let dir = options.local_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create recording directory: {}", e))?;
+
+    let mut recordings = RECORDINGS.lock().unwrap();
+    if recordings.contains_key(device_id) {
+        return Err(format!("Device {} is already recording", device_id));
+    }
+
+    let recording = Arc::new(Recording {
+        platform: recorder.platform(),
+        device_id: device_id.to_string(),
+        started_at: chrono::Local::now().to_rfc3339(),
+        stop: AtomicBool::new(false),
+        segments: Mutex::new(0),
+        thread: Mutex::new(None),
+    });
+
+    let worker = recording.clone();
+    let handle = std::thread::spawn(move || {
+        let result = run(&recorder, &worker, &options, &dir);
+        let finished_by_itself = {
+            let mut recordings = RECORDINGS.lock().unwrap();
+            match recordings.get(&worker.device_id) {
+                Some(current) if Arc::ptr_eq(current, &worker) => recordings.remove(&worker.device_id).is_some(),
+                _ => false,
+            }
+        };
+        // 手动停止时由 stop 返回结果，这里只处理达到总时长或出错自行结束的情况
+        if finished_by_itself {
+            let _ = app.emit("screen-recording-finished", &result);
+        }
+        result
+    });
+    *recording.thread.lock().unwrap() = Some(handle);
+    recordings.insert(device_id.to_string(), recording);
+    Ok(())
+}
+
+/// 停止录制并等待文件拉取完成
+pub fn stop(device_id: &str) -> Result<RecordingResult, String> {
+    let recording = RECORDINGS
+        .lock()
+        .unwrap()
+        .remove(device_id)
+        .ok_or_else(|| format!("Device {} is not recording", device_id))?;
+    recording.stop.store(true, Ordering::SeqCst);
+
+    let handle = recording.thread.lock().unwrap().take();
+    let handle = handle.ok_or_else(|| format!("Device {} is not recording", device_id))?;
+    let result = handle
+        .join()
+        .map_err(|_| "Screen recording thread panicked".to_string())?;
+    match (&result.error, result.files.is_empty()) {
+        (Some(error), true) => Err(error.clone()),
+        _ => Ok(result),
+    }
+}
+
+pub fn list() -> Vec<RecordingInfo> {
+    RECORDINGS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|r| RecordingInfo {
+            platform: r.platform,
+            device_id: r.device_id.clone(),
+            started_at: r.started_at.clone(),
+            segments: *r.segments.lock().unwrap(),
+        })
+        .collect()
+}
+
+/// 应用退出时调用，结束所有录制并等待文件拉取完成
+pub fn stop_all() {
+    let recordings: Vec<_> = RECORDINGS.lock().unwrap().drain().map(|(_, r)| r).collect();
+    for recording in &recordings {
+        recording.stop.store(true, Ordering::SeqCst);
+    }
+    for recording in recordings {
+        if let Some(handle) = recording.thread.lock().unwrap().take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn sanitize(device_id: &str) -> String {
+    device_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+fn run<R: ScreenRecorder>(recorder: &R, recording: &Recording, options: &RecordingOptions, dir: &Path) -> RecordingResult {
+    let started = Instant::now();
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let (remotes, mut error) = record_segments(recorder, recording, options, &timestamp);
+
+    let device = sanitize(&recording.device_id);
+    let mut details = recorder.describe(&recording.device_id);
+    if let Some(size) = options.size.as_deref().and_then(parse_size) {
+        details.screen_size = Some(size);
+    }
+    let mut files = Vec::new();
+    for (index, remote) in remotes.iter().enumerate() {
+        let name = if index == 0 {
+            format!("recording_{}_{}.mp4", device, timestamp)
+        } else {
+            format!("recording_{}_{}_part{}.mp4", device, timestamp, index + 1)
+        };
+        let local = dir.join(name);
+        match recorder.pull(&recording.device_id, remote, &local) {
+            Ok(()) => {
+                let path = local.to_string_lossy().to_string();
+                captures::record(CaptureKind::Recording, recording.platform, &recording.device_id, &details, &path);
+                files.push(path);
+            }
+            Err(e) => error = Some(e),
+        }
+        recorder.remove(&recording.device_id, remote);
+    }
+
+    RecordingResult {
+        platform: recording.platform,
+        device_id: recording.device_id.clone(),
+        files,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+/// 分段录制直到停止、达到总时长或出错，返回设备上已录好的各段文件
+fn record_segments<R: ScreenRecorder>(
+    recorder: &R,
+    recording: &Recording,
+    options: &RecordingOptions,
+    timestamp: &str,
+) -> (Vec<String>, Option<String>) {
+    let started = Instant::now();
+    let mut remotes = Vec::new();
+    let mut error = None;
+
+    while !recording.stop.load(Ordering::SeqCst) {
+        let elapsed = started.elapsed().as_secs();
+        let secs = match options.time_limit_secs {
+            Some(limit) if elapsed >= limit => break,
+            Some(limit) => (limit - elapsed).min(recorder.max_segment_secs()),
+            None => recorder.max_segment_secs(),
+        };
+
+        let remote = recorder.remote_path(&format!("simhub_{}_{:03}.mp4", timestamp, remotes.len() + 1));
+        let mut child = match recorder.spawn(&recording.device_id, &remote, options, secs) {
+            Ok(child) => child,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+        let segment_started = Instant::now();
+        remotes.push(remote);
+        *recording.segments.lock().unwrap() = remotes.len();
+
+        let status = wait_segment(recorder, recording, &mut child);
+        if let Some(remote) = remotes.last() {
+            recorder.end_segment(&recording.device_id, remote);
+        }
+        if !status.is_some_and(|s| s.success())
+            && !recording.stop.load(Ordering::SeqCst)
+            && segment_started.elapsed() < STARTUP_FAILURE_WINDOW
+        {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            error = Some(format!("Screen recording failed: {}", stderr.trim()));
+            // 失败的段没有可用文件
+            if let Some(remote) = remotes.pop() {
+                recorder.remove(&recording.device_id, &remote);
+            }
+            break;
+        }
+    }
+    (remotes, error)
+}
+
+/// 等待当前段结束；收到停止请求后通知设备端收尾，超时则强制结束
+fn wait_segment<R: ScreenRecorder>(
+    recorder: &R,
+    recording: &Recording,
+    child: &mut Child,
+) -> Option<std::process::ExitStatus> {
+    let mut stop_requested: Option<Instant> = None;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => {}
+            Err(_) => return None,
+        }
+
+        match stop_requested {
+            None if recording.stop.load(Ordering::SeqCst) => {
+                recorder.interrupt(&recording.device_id, child);
+                stop_requested = Some(Instant::now());
+            }
+            Some(at) if at.elapsed() >= STOP_TIMEOUT => {
+                let _ = child.kill();
+                return child.wait().ok();
+            }
+            _ => {}
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    /// 用本地 shell 进程模拟设备端录屏，`{secs}` 替换为本段时长
+    struct FakeRecorder {
+        max_segment_secs: u64,
+        script: &'static str,
+        spawn_error: Option<&'static str>,
+        spawned: Mutex<Vec<(String, u64)>>,
+        ended: Mutex<Vec<String>>,
+        removed: Mutex<Vec<String>>,
+        interrupts: Mutex<usize>,
+    }
+
+    impl FakeRecorder {
+        fn new(max_segment_secs: u64, script: &'static str) -> Self {
+            Self {
+                max_segment_secs,
+                script,
+                spawn_error: None,
+                spawned: Mutex::new(Vec::new()),
+                ended: Mutex::new(Vec::new()),
+                removed: Mutex::new(Vec::new()),
+                interrupts: Mutex::new(0),
+            }
+        }
+
+        fn spawned_secs(&self) -> Vec<u64> {
+            self.spawned.lock().unwrap().iter().map(|(_, secs)| *secs).collect()
+        }
+    }
+
+    impl ScreenRecorder for FakeRecorder {
+        fn platform(&self) -> Platform {
+            Platform::Android
+        }
+
+        fn max_segment_secs(&self) -> u64 {
+            self.max_segment_secs
+        }
+
+        fn remote_path(&self, name: &str) -> String {
+            format!("/sdcard/{}", name)
+        }
+
+        fn spawn(&self, _device_id: &str, remote: &str, _options: &RecordingOptions, secs: u64) -> Result<Child, String> {
+            if let Some(error) = self.spawn_error {
+                return Err(error.to_string());
+            }
+            self.spawned.lock().unwrap().push((remote.to_string(), secs));
+            Command::new("sh")
+                .args(["-c", &self.script.replace("{secs}", &secs.to_string())])
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| e.to_string())
+        }
+
+        fn interrupt(&self, _device_id: &str, child: &mut Child) {
+            *self.interrupts.lock().unwrap() += 1;
+            let _ = child.kill();
+        }
+
+        fn end_segment(&self, _device_id: &str, remote: &str) {
+            self.ended.lock().unwrap().push(remote.to_string());
+        }
+
+        fn pull(&self, _device_id: &str, _remote: &str, _local: &Path) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn remove(&self, _device_id: &str, remote: &str) {
+            self.removed.lock().unwrap().push(remote.to_string());
+        }
+
+        fn describe(&self, _device_id: &str) -> CaptureDevice {
+            CaptureDevice::default()
+        }
+    }
+
+    fn recording() -> Recording {
+        Recording {
+            platform: Platform::Android,
+            device_id: "emulator-5554".to_string(),
+            started_at: String::new(),
+            stop: AtomicBool::new(false),
+            segments: Mutex::new(0),
+            thread: Mutex::new(None),
+        }
+    }
+
+    fn time_limit(secs: u64) -> RecordingOptions {
+        RecordingOptions {
+            time_limit_secs: Some(secs),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn chains_segments_up_to_time_limit() {
+        let recorder = FakeRecorder::new(2, "sleep {secs}");
+        let recording = recording();
+        let (remotes, error) = record_segments(&recorder, &recording, &time_limit(3), "20240101_120000");
+
+        assert_eq!(error, None);
+        assert_eq!(recorder.spawned_secs(), [2, 1]);
+        assert_eq!(
+            remotes,
+            ["/sdcard/simhub_20240101_120000_001.mp4", "/sdcard/simhub_20240101_120000_002.mp4"]
+        );
+        assert_eq!(*recorder.ended.lock().unwrap(), remotes);
+        assert_eq!(*recording.segments.lock().unwrap(), 2);
+        assert!(recorder.removed.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn short_time_limit_uses_single_segment() {
+        let recorder = FakeRecorder::new(180, "sleep {secs}");
+        let (remotes, error) = record_segments(&recorder, &recording(), &time_limit(1), "20240101_120000");
+        assert_eq!(error, None);
+        assert_eq!(recorder.spawned_secs(), [1]);
+        assert_eq!(remotes.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_interrupts_current_segment() {
+        let recorder = FakeRecorder::new(30, "sleep {secs}");
+        let recording = recording();
+        let (remotes, error) = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(300));
+                recording.stop.store(true, Ordering::SeqCst);
+            });
+            record_segments(&recorder, &recording, &RecordingOptions::default(), "20240101_120000")
+        });
+
+        // 停止后被中断的段仍然保留
+        assert_eq!(error, None);
+        assert_eq!(remotes.len(), 1);
+        assert_eq!(*recorder.interrupts.lock().unwrap(), 1);
+        assert_eq!(*recorder.ended.lock().unwrap(), remotes);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn startup_failure_reports_stderr() {
+        let recorder = FakeRecorder::new(180, "echo 'Unable to open output file' >&2; exit 1");
+        let (remotes, error) = record_segments(&recorder, &recording(), &RecordingOptions::default(), "20240101_120000");
+
+        assert_eq!(error.as_deref(), Some("Screen recording failed: Unable to open output file"));
+        assert!(remotes.is_empty());
+        assert_eq!(recorder.spawned_secs(), [180]);
+        // 失败段的文件被删除
+        assert_eq!(*recorder.removed.lock().unwrap(), ["/sdcard/simhub_20240101_120000_001.mp4"]);
+    }
+
+    #[test]
+    fn spawn_error_stops_recording() {
+        let recorder = FakeRecorder {
+            spawn_error: Some("Failed to start screen recording: adb not found"),
+            ..FakeRecorder::new(180, "sleep {secs}")
+        };
+        let (remotes, error) = record_segments(&recorder, &recording(), &RecordingOptions::default(), "20240101_120000");
+        assert_eq!(error.as_deref(), Some("Failed to start screen recording: adb not found"));
+        assert!(remotes.is_empty());
+        assert!(recorder.spawned.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+//! SDK 工具定位
+//!
+//! 统一解析 adb / emulator / avdmanager / sdkmanager / hdc / Emulator / xcrun 的路径，
+//! 解析结果会被缓存，设置保存后通过 [`invalidate`] 清空。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::settings;
+use crate::utils::new_command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tool {
+    Adb,
+    Emulator,
+    Avdmanager,
+    Sdkmanager,
+    Hdc,
+    HarmonyEmulator,
+    Xcrun,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 7] = [
+        Tool::Adb,
+        Tool::Emulator,
+        Tool::Avdmanager,
+        Tool::Sdkmanager,
+        Tool::Hdc,
+        Tool::HarmonyEmulator,
+        Tool::Xcrun,
+    ];
+
+    /// 界面和错误信息中使用的名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Tool::Adb => "adb",
+            Tool::Emulator => "emulator",
+            Tool::Avdmanager => "avdmanager",
+            Tool::Sdkmanager => "sdkmanager",
+            Tool::Hdc => "hdc",
+            Tool::HarmonyEmulator => "Emulator",
+            Tool::Xcrun => "xcrun",
+        }
+    }
+
+    /// 当前系统上是否需要该工具（xcrun 仅 macOS）
+    pub fn applicable(&self) -> bool {
+        match self {
+            Tool::Xcrun => cfg!(target_os = "macos"),
+            _ => true,
+        }
+    }
+
+    /// 查询版本号的参数；None 表示该工具没有可靠的版本参数
+    fn version_args(&self) -> Option<&'static [&'static str]> {
+        match self {
+            Tool::Adb => Some(&["version"]),
+            Tool::Emulator => Some(&["-version"]),
+            Tool::Sdkmanager => Some(&["--version"]),
+            Tool::Hdc => Some(&["-v"]),
+            Tool::Xcrun => Some(&["--version"]),
+            Tool::Avdmanager | Tool::HarmonyEmulator => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedTool {
+    path: PathBuf,
+    version: Option<Option<String>>,
+}
+
+lazy_static::lazy_static! {
+    static ref TOOL_CACHE: Mutex<HashMap<Tool, CachedTool>> = Mutex::new(HashMap::new());
+}
+
+/// 清空缓存，设置变更后调用
+pub fn invalidate() {
+    TOOL_CACHE.lock().unwrap().clear();
+}
+
+fn exe(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn script(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.bat", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Android SDK 根目录
+pub fn android_home() -> Result<String, String> {
+    settings::get_android_home()
+        .ok_or_else(|| "Android SDK path not configured. Please set it in Settings.".to_string())
+}
+
+/// DevEco Studio 中工具所在位置；macOS 上是 .app 包，多一层 Contents
+fn deveco_tool_path(deveco_home: &str, relative: &str) -> PathBuf {
+    let root = Path::new(deveco_home);
+    if cfg!(target_os = "macos") {
+        root.join("Contents").join(relative)
+    } else {
+        root.join(relative)
+    }
+}
+
+/// cmdline-tools 优先使用 latest，其次是任意已安装版本，最后是旧版 tools/bin
+fn cmdline_tool_path(android_home: &str, name: &str) -> PathBuf {
+    let root = Path::new(android_home);
+    let file = script(name);
+
+    let latest = root.join("cmdline-tools").join("latest").join("bin").join(&file);
+    if latest.exists() {
+        return latest;
+    }
+
+    if let Ok(entries) = std::fs::read_dir(root.join("cmdline-tools")) {
+        let mut candidates: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path().join("bin").join(&file))
+            .filter(|path| path.exists())
+            .collect();
+        candidates.sort();
+        if let Some(path) = candidates.pop() {
+            return path;
+        }
+    }
+
+    let legacy = root.join("tools").join("bin").join(&file);
+    if legacy.exists() {
+        return legacy;
+    }
+
+    latest
+}
+
+/// 在 PATH 中查找可执行文件
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// 根据当前设置计算工具应在的位置（不检查是否存在）
+fn locate(tool: Tool) -> Result<PathBuf, String> {
+    match tool {
+        Tool::Adb => Ok(Path::new(&android_home()?).join("platform-tools").join(exe("adb"))),
+        Tool::Emulator => Ok(Path::new(&android_home()?).join("emulator").join(exe("emulator"))),
+        Tool::Avdmanager => Ok(cmdline_tool_path(&android_home()?, "avdmanager")),
+        Tool::Sdkmanager => Ok(cmdline_tool_path(&android_home()?, "sdkmanager")),
+        Tool::Hdc => {
+            let configured = settings::load_settings()
+                .map(|s| s.harmony_hdc_path)
+                .unwrap_or_default();
+            if !configured.is_empty() {
+                return Ok(PathBuf::from(configured));
+            }
+            settings::get_deveco_home()
+                .map(|home| deveco_tool_path(&home, &format!("sdk/default/openharmony/toolchains/{}", exe("hdc"))))
+                .ok_or_else(|| "hdc path not configured. Please set DevEco Studio path or hdc path in Settings.".to_string())
+        }
+        Tool::HarmonyEmulator => {
+            let configured = settings::load_settings()
+                .map(|s| s.harmony_emulator_path)
+                .unwrap_or_default();
+            if !configured.is_empty() {
+                return Ok(PathBuf::from(configured));
+            }
+            settings::get_deveco_home()
+                .map(|home| deveco_tool_path(&home, &format!("tools/emulator/{}", exe("Emulator"))))
+                .ok_or_else(|| "Emulator path not configured. Please set DevEco Studio path or Emulator path in Settings.".to_string())
+        }
+        Tool::Xcrun => {
+            if !tool.applicable() {
+                return Err("xcrun is only available on macOS".to_string());
+            }
+            Ok(find_in_path("xcrun").unwrap_or_else(|| PathBuf::from("/usr/bin/xcrun")))
+        }
+    }
+}
+
+/// 解析工具路径，工具不存在时返回说明原因的错误
+pub fn resolve(tool: Tool) -> Result<PathBuf, String> {
+    if let Some(cached) = TOOL_CACHE.lock().unwrap().get(&tool) {
+        if cached.path.exists() {
+            return Ok(cached.path.clone());
+        }
+    }
+
+    let path = locate(tool)?;
+    if !path.exists() {
+        return Err(format!(
+            "{} not found at: {:?}. Please check your settings.",
+            tool.display_name(),
+            path
+        ));
+    }
+
+    TOOL_CACHE.lock().unwrap().insert(
+        tool,
+        CachedTool {
+            path: path.clone(),
+            version: None,
+        },
+    );
+    Ok(path)
+}
+
+fn query_version(tool: Tool, path: &Path) -> Option<String> {
+    let args = tool.version_args()?;
+    let output = new_command(path).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // 部分工具把版本信息写到 stderr
+    let text = if output.stdout.is_empty() {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    } else {
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// 工具版本（首行输出），结果与路径一起缓存
+pub fn version(tool: Tool) -> Result<Option<String>, String> {
+    let path = resolve(tool)?;
+
+    if let Some(Some(version)) = TOOL_CACHE.lock().unwrap().get(&tool).map(|c| c.version.clone()) {
+        return Ok(version);
+    }
+
+    let version = query_version(tool, &path);
+    if let Some(cached) = TOOL_CACHE.lock().unwrap().get_mut(&tool) {
+        cached.version = Some(version.clone());
+    }
+    Ok(version)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport {
+    pub tool: Tool,
+    pub name: String,
+    pub applicable: bool,
+    pub found: bool,
+    pub path: Option<String>,
+    pub version: Option<String>,
+    /// 缺失或配置错误的原因
+    pub problem: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentReport {
+    pub android_home: Option<String>,
+    pub deveco_home: Option<String>,
+    pub tools: Vec<ToolReport>,
+    /// 与具体工具无关的配置问题
+    pub problems: Vec<String>,
+}
+
+fn check_dir(label: &str, value: Option<String>, problems: &mut Vec<String>) -> Option<String> {
+    match &value {
+        Some(dir) if !Path::new(dir).is_dir() => {
+            problems.push(format!("{} does not exist or is not a directory: {}", label, dir));
+        }
+        None => problems.push(format!("{} is not configured", label)),
+        _ => {}
+    }
+    value
+}
+
+fn diagnose_tool(tool: Tool) -> ToolReport {
+    let mut report = ToolReport {
+        tool,
+        name: tool.display_name().to_string(),
+        applicable: tool.applicable(),
+        found: false,
+        path: None,
+        version: None,
+        problem: None,
+    };
+
+    if !report.applicable {
+        return report;
+    }
+
+    match locate(tool) {
+        Err(e) => report.problem = Some(e),
+        Ok(path) => {
+            report.path = Some(path.to_string_lossy().to_string());
+            if !path.exists() {
+                report.problem = Some(format!("{} not found at: {:?}", tool.display_name(), path));
+            } else if path.is_dir() {
+                report.problem = Some(format!("{:?} is a directory, expected the {} executable", path, tool.display_name()));
+            } else {
+                report.found = true;
+                match version(tool) {
+                    Ok(Some(v)) => report.version = Some(v),
+                    Ok(None) if tool.version_args().is_some() => {
+                        report.problem = Some(format!("{} exists but failed to run; check file permissions", tool.display_name()));
+                    }
+                    Ok(None) => {}
+                    Err(e) => report.problem = Some(e),
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// 检查所有工具及相关目录配置
+pub fn diagnose() -> EnvironmentReport {
+    let mut problems = Vec::new();
+
+    let android_home = check_dir("Android SDK path", settings::get_android_home(), &mut problems);
+    let deveco_home = check_dir("DevEco Studio path", settings::get_deveco_home(), &mut problems);
+    check_dir("HarmonyOS emulator location", settings::get_harmony_emulator_location(), &mut problems);
+    check_dir("HarmonyOS image location", settings::get_harmony_image_location(), &mut problems);
+
+    EnvironmentReport {
+        android_home,
+        deveco_home,
+        tools: Tool::ALL.iter().map(|&tool| diagnose_tool(tool)).collect(),
+        problems,
+    }
+}
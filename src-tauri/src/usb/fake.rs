@@ -0,0 +1,31 @@
+//! 固定设备列表的扫描器，用于测试和无真机时调试界面
+
+use super::{RawUsbDevice, UsbScanner};
+use std::path::Path;
+
+#[derive(Default)]
+pub struct FakeUsbScanner {
+    devices: Vec<RawUsbDevice>,
+}
+
+impl FakeUsbScanner {
+    pub fn new(devices: Vec<RawUsbDevice>) -> Self {
+        Self { devices }
+    }
+
+    /// 从 JSON 数组文件加载设备列表，格式同 [`RawUsbDevice`]
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read USB fixture {:?}: {}", path, e))?;
+        let devices: Vec<RawUsbDevice> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse USB fixture {:?}: {}", path, e))?;
+        Ok(Self::new(devices))
+    }
+}
+
+impl UsbScanner for FakeUsbScanner {
+    fn scan(&self) -> Result<Vec<RawUsbDevice>, String> {
+        Ok(self.devices.clone())
+    }
+}
@@ -0,0 +1,293 @@
+//! Linux 实现：遍历 sysfs（/sys/bus/usb/devices）
+
+use super::{RawUsbDevice, UsbInterface, UsbScanner};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SYSFS_USB_DEVICES: &str = "/sys/bus/usb/devices";
+
+pub struct LinuxUsbScanner {
+    root: PathBuf,
+}
+
+impl LinuxUsbScanner {
+    /// 以指定目录代替 /sys/bus/usb/devices，便于用伪造的 sysfs 目录树测试
+    pub fn with_root<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for LinuxUsbScanner {
+    fn default() -> Self {
+        Self::with_root(SYSFS_USB_DEVICES)
+    }
+}
+
+/// 读取 sysfs 属性文件，去掉结尾换行，空值视为不存在
+fn read_attr(dir: &Path, attr: &str) -> Option<String> {
+    let value = fs::read_to_string(dir.join(attr)).ok()?;
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn read_hex_u16(dir: &Path, attr: &str) -> Option<u16> {
+    u16::from_str_radix(&read_attr(dir, attr)?, 16).ok()
+}
+
+fn read_hex_u8(dir: &Path, attr: &str) -> Option<u8> {
+    u8::from_str_radix(&read_attr(dir, attr)?, 16).ok()
+}
+
+/// 设备目录下形如 `1-2:1.0` 的子目录即为该设备的接口
+fn read_interfaces(device_dir: &Path, device_name: &str) -> Vec<UsbInterface> {
+    let prefix = format!("{}:", device_name);
+    let Ok(entries) = fs::read_dir(device_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|entry| {
+            let dir = entry.path();
+            Some(UsbInterface {
+                class: read_hex_u8(&dir, "bInterfaceClass")?,
+                subclass: read_hex_u8(&dir, "bInterfaceSubClass")?,
+                protocol: read_hex_u8(&dir, "bInterfaceProtocol")?,
+                name: read_attr(&dir, "interface"),
+            })
+        })
+        .collect()
+}
+
+fn read_device(device_dir: &Path, device_name: &str) -> Option<RawUsbDevice> {
+    Some(RawUsbDevice {
+        vendor_id: read_hex_u16(device_dir, "idVendor")?,
+        product_id: read_hex_u16(device_dir, "idProduct")?,
+        name: read_attr(device_dir, "product"),
+        brand: read_attr(device_dir, "manufacturer"),
+        serial: read_attr(device_dir, "serial"),
+        interfaces: read_interfaces(device_dir, device_name),
+    })
+}
+
+impl UsbScanner for LinuxUsbScanner {
+    fn scan(&self) -> Result<Vec<RawUsbDevice>, String> {
+        let entries = fs::read_dir(&self.root)
+            .map_err(|e| format!("Failed to read {:?}: {}", self.root, e))?;
+
+        let mut names: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            // 接口目录（含 ':'）与设备目录并列出现在此处，只保留设备本身
+            .filter(|name| !name.contains(':'))
+            .collect();
+        names.sort();
+
+        Ok(names
+            .iter()
+            .filter_map(|name| read_device(&self.root.join(name), name))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usb::{scan_mobile_devices, UsbDevice};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SEQ: AtomicUsize = AtomicUsize::new(0);
+
+    /// 临时的 /sys/bus/usb/devices 目录树，drop 时删除
+    struct FakeSysfs(PathBuf);
+
+    impl FakeSysfs {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "simhub_sysfs_{}_{}",
+                std::process::id(),
+                SEQ.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        fn write(dir: &Path, attrs: &[(&str, &str)]) {
+            fs::create_dir_all(dir).unwrap();
+            for (name, value) in attrs {
+                fs::write(dir.join(name), format!("{}\n", value)).unwrap();
+            }
+        }
+
+        /// 添加设备，`interfaces` 为 (class, subclass, protocol, 接口名)
+        fn device(&self, name: &str, attrs: &[(&str, &str)], interfaces: &[(&str, &str, &str, &str)]) {
+            let dir = self.0.join(name);
+            Self::write(&dir, attrs);
+            for (i, (class, subclass, protocol, interface)) in interfaces.iter().enumerate() {
+                let interface_name = format!("{}:1.{}", name, i);
+                let mut attrs = vec![
+                    ("bInterfaceClass", *class),
+                    ("bInterfaceSubClass", *subclass),
+                    ("bInterfaceProtocol", *protocol),
+                ];
+                if !interface.is_empty() {
+                    attrs.push(("interface", *interface));
+                }
+                Self::write(&dir.join(&interface_name), &attrs);
+                // 真实 sysfs 中接口也会与设备并列出现在根目录
+                Self::write(&self.0.join(&interface_name), &attrs);
+            }
+        }
+
+        fn scanner(&self) -> LinuxUsbScanner {
+            LinuxUsbScanner::with_root(&self.0)
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sample_tree() -> FakeSysfs {
+        let sysfs = FakeSysfs::new();
+        sysfs.device(
+            "usb1",
+            &[("idVendor", "1d6b"), ("idProduct", "0002"), ("product", "xHCI Host Controller")],
+            &[("09", "00", "00", "")],
+        );
+        sysfs.device(
+            "1-1",
+            &[
+                ("idVendor", "18d1"),
+                ("idProduct", "4ee7"),
+                ("product", "Pixel 7"),
+                ("manufacturer", "Google"),
+                ("serial", "28031FDH2004ZE"),
+            ],
+            &[("06", "01", "01", "MTP"), ("ff", "42", "01", "ADB Interface")],
+        );
+        sysfs.device(
+            "1-2",
+            &[
+                ("idVendor", "12d1"),
+                ("idProduct", "5000"),
+                ("product", "Mate 60"),
+                ("manufacturer", "HUAWEI"),
+                ("serial", "FMR0223B08000459"),
+            ],
+            &[("ff", "50", "01", "HDC Interface")],
+        );
+        sysfs.device(
+            "1-3",
+            &[
+                ("idVendor", "05ac"),
+                ("idProduct", "12a8"),
+                ("product", "iPhone"),
+                ("manufacturer", "Apple Inc."),
+                ("serial", "00008030001A2D3E0C41802E"),
+            ],
+            &[("06", "01", "01", "PTP"), ("ff", "fe", "02", "")],
+        );
+        sysfs.device(
+            "1-4",
+            &[("idVendor", "05e3"), ("idProduct", "0610"), ("product", "USB2.1 Hub")],
+            &[("09", "00", "00", "")],
+        );
+        sysfs.device(
+            "1-4.1",
+            &[("idVendor", "046d"), ("idProduct", "c31c"), ("product", "USB Keyboard")],
+            &[("03", "01", "01", "")],
+        );
+        sysfs
+    }
+
+    #[test]
+    fn scan_reads_devices_and_interfaces() {
+        let sysfs = sample_tree();
+        let devices = sysfs.scanner().scan().unwrap();
+
+        // 根目录下的接口目录不算设备
+        assert_eq!(devices.len(), 6);
+        let pixel = devices.iter().find(|d| d.vendor_id == 0x18D1).unwrap();
+        assert_eq!(pixel.product_id, 0x4EE7);
+        assert_eq!(pixel.name.as_deref(), Some("Pixel 7"));
+        assert_eq!(pixel.brand.as_deref(), Some("Google"));
+        assert_eq!(pixel.serial.as_deref(), Some("28031FDH2004ZE"));
+        assert_eq!(pixel.interfaces.len(), 2);
+        assert!(pixel
+            .interfaces
+            .iter()
+            .any(|i| (i.class, i.subclass, i.protocol) == (0xFF, 0x42, 0x01)
+                && i.name.as_deref() == Some("ADB Interface")));
+
+        let hub = devices.iter().find(|d| d.vendor_id == 0x1D6B).unwrap();
+        assert_eq!(hub.serial, None);
+        assert_eq!(hub.interfaces[0].name, None);
+    }
+
+    #[test]
+    fn scan_classifies_mobile_devices() {
+        let sysfs = sample_tree();
+        let devices = scan_mobile_devices(&sysfs.scanner()).unwrap();
+
+        let summary: Vec<(&str, &str)> = devices
+            .iter()
+            .map(|d| (d.device_type.as_str(), d.serial.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("android", "28031FDH2004ZE"),
+                ("harmony", "FMR0223B08000459"),
+                ("ios", "00008030001A2D3E0C41802E"),
+            ]
+        );
+
+        assert_eq!(
+            devices[0],
+            UsbDevice {
+                device_type: "android".to_string(),
+                name: "Pixel 7".to_string(),
+                serial: "28031FDH2004ZE".to_string(),
+                brand: "Google".to_string(),
+                vendor_id: "0x18D1".to_string(),
+                product_id: "0x4EE7".to_string(),
+                usb_debugging: Some(true),
+                trusted: None,
+            }
+        );
+        assert_eq!(devices[1].usb_debugging, Some(true));
+        assert_eq!(devices[2].brand, "Apple");
+        assert_eq!(devices[2].trusted, Some(true));
+    }
+
+    #[test]
+    fn android_without_adb_interface_has_debugging_off() {
+        let sysfs = FakeSysfs::new();
+        sysfs.device(
+            "2-1",
+            &[("idVendor", "04e8"), ("idProduct", "6860"), ("serial", "R58M123ABC")],
+            &[("06", "01", "01", "MTP")],
+        );
+        let devices = scan_mobile_devices(&sysfs.scanner()).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].device_type, "android");
+        assert_eq!(devices[0].name, "Unknown");
+        assert_eq!(devices[0].usb_debugging, Some(false));
+    }
+
+    #[test]
+    fn missing_ids_are_skipped() {
+        let sysfs = FakeSysfs::new();
+        FakeSysfs::write(&sysfs.0.join("3-1"), &[("product", "Broken")]);
+        assert!(sysfs.scanner().scan().unwrap().is_empty());
+        assert!(LinuxUsbScanner::with_root(sysfs.0.join("missing")).scan().is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! macOS 实现：通过 IOKit 枚举 IOUSBDevice 及其下属接口
+
+use super::{RawUsbDevice, UsbInterface, UsbScanner};
+use core_foundation::base::{kCFAllocatorDefault, CFType, TCFType};
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
+use io_kit_sys::keys::kIOServicePlane;
+use io_kit_sys::ret::kIOReturnSuccess;
+use io_kit_sys::types::{io_iterator_t, io_object_t, io_registry_entry_t, IO_OBJECT_NULL};
+use io_kit_sys::usb::lib::kIOUSBDeviceClassName;
+use io_kit_sys::{
+    kIOMasterPortDefault, kIORegistryIterateRecursively, IOIteratorNext, IOObjectRelease,
+    IORegistryEntryCreateCFProperty, IORegistryEntryCreateIterator, IOServiceGetMatchingServices,
+    IOServiceMatching,
+};
+
+pub struct MacUsbScanner;
+
+/// IOKit 对象，离开作用域时释放
+struct IoObject(io_object_t);
+
+impl Drop for IoObject {
+    fn drop(&mut self) {
+        unsafe {
+            IOObjectRelease(self.0);
+        }
+    }
+}
+
+/// 迭代器中的下一个对象，迭代结束返回 None
+fn next_object(iterator: &IoObject) -> Option<IoObject> {
+    let object = unsafe { IOIteratorNext(iterator.0) };
+    (object != IO_OBJECT_NULL).then_some(IoObject(object))
+}
+
+fn property(entry: io_registry_entry_t, key: &'static str) -> Option<CFType> {
+    let key = CFString::from_static_string(key);
+    let value = unsafe {
+        IORegistryEntryCreateCFProperty(entry, key.as_concrete_TypeRef(), kCFAllocatorDefault, 0)
+    };
+    if value.is_null() {
+        None
+    } else {
+        Some(unsafe { CFType::wrap_under_create_rule(value) })
+    }
+}
+
+fn string_property(entry: io_registry_entry_t, key: &'static str) -> Option<String> {
+    property(entry, key)?.downcast::<CFString>().map(|s| s.to_string())
+}
+
+fn int_property(entry: io_registry_entry_t, key: &'static str) -> Option<i64> {
+    property(entry, key)?.downcast::<CFNumber>()?.to_i64()
+}
+
+/// 递归遍历设备在 IOService 平面下的子节点，收集接口信息
+fn read_interfaces(device: &IoObject) -> Vec<UsbInterface> {
+    let mut iterator: io_iterator_t = IO_OBJECT_NULL;
+    let kr = unsafe {
+        IORegistryEntryCreateIterator(device.0, kIOServicePlane, kIORegistryIterateRecursively, &mut iterator)
+    };
+    if kr != kIOReturnSuccess {
+        return Vec::new();
+    }
+    let iterator = IoObject(iterator);
+
+    let mut interfaces = Vec::new();
+    while let Some(entry) = next_object(&iterator) {
+        let class = int_property(entry.0, "bInterfaceClass");
+        let subclass = int_property(entry.0, "bInterfaceSubClass");
+        let protocol = int_property(entry.0, "bInterfaceProtocol");
+
+        if let (Some(class), Some(subclass), Some(protocol)) = (class, subclass, protocol) {
+            interfaces.push(UsbInterface {
+                class: class as u8,
+                subclass: subclass as u8,
+                protocol: protocol as u8,
+                name: string_property(entry.0, "USB Interface Name"),
+            });
+        }
+    }
+
+    interfaces
+}
+
+impl UsbScanner for MacUsbScanner {
+    fn scan(&self) -> Result<Vec<RawUsbDevice>, String> {
+        let matching = unsafe { IOServiceMatching(kIOUSBDeviceClassName) };
+        if matching.is_null() {
+            return Err("USB scan failed".to_string());
+        }
+
+        // IOServiceGetMatchingServices 会消耗 matching 的引用，无需手动释放
+        let mut iterator: io_iterator_t = IO_OBJECT_NULL;
+        let kr = unsafe { IOServiceGetMatchingServices(kIOMasterPortDefault, matching as _, &mut iterator) };
+        if kr != kIOReturnSuccess {
+            return Err(format!("USB scan failed: IOKit error {}", kr));
+        }
+        let iterator = IoObject(iterator);
+
+        let mut devices = Vec::new();
+        while let Some(device) = next_object(&iterator) {
+            let (Some(vendor_id), Some(product_id)) = (
+                int_property(device.0, "idVendor"),
+                int_property(device.0, "idProduct"),
+            ) else {
+                continue;
+            };
+
+            devices.push(RawUsbDevice {
+                vendor_id: vendor_id as u16,
+                product_id: product_id as u16,
+                name: string_property(device.0, "USB Product Name"),
+                brand: string_property(device.0, "USB Vendor Name"),
+                serial: string_property(device.0, "USB Serial Number"),
+                interfaces: read_interfaces(&device),
+            });
+        }
+
+        Ok(devices)
+    }
+}
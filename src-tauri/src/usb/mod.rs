@@ -0,0 +1,318 @@
+//! USB 设备枚举
+//!
+//! 各平台只负责实现 [`UsbScanner`]，把系统中的 USB 设备原样报告为 [`RawUsbDevice`]；
+//! 识别 Android / iOS / 鸿蒙设备的逻辑统一在 [`classify`] 中完成。
+
+use serde::{Deserialize, Serialize};
+
+mod fake;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+pub mod watcher;
+
+pub use fake::FakeUsbScanner;
+#[cfg(target_os = "linux")]
+pub use linux::LinuxUsbScanner;
+#[cfg(target_os = "macos")]
+pub use macos::MacUsbScanner;
+#[cfg(target_os = "windows")]
+pub use windows::WindowsUsbScanner;
+
+/// 设置后从该 JSON 文件读取设备列表，无需连接真机即可调试界面
+pub const FIXTURE_ENV: &str = "SIMHUB_USB_FIXTURE";
+
+// Android 厂商 VID
+const ANDROID_VENDORS: [u16; 5] = [
+    0x18D1, // Google
+    0x04E8, // Samsung
+    0x12D1, // Huawei
+    0x2717, // Xiaomi
+    0x2A70, // OnePlus
+];
+
+// Apple 厂商 VID
+const APPLE_VENDOR_ID: u16 = 0x05AC;
+
+// 华为 VID（Android 和鸿蒙共用）
+const HUAWEI_VENDOR_ID: u16 = 0x12D1;
+
+// iOS 真机 PID
+const IOS_PRODUCT_IDS: [u16; 4] = [
+    0x12A8, // Normal USB mux
+    0x12A7, // Recovery
+    0x12AB, // DFU
+    0x12AD, // Diagnostics
+];
+
+/// 接口 (class, subclass, protocol)
+const ADB_INTERFACE: (u8, u8, u8) = (0xFF, 0x42, 0x01);
+const HDC_INTERFACE: (u8, u8, u8) = (0xFF, 0x50, 0x01);
+const IOS_TRUSTED_INTERFACE: (u8, u8, u8) = (0x06, 0x01, 0x01);
+
+/// 设备下的一个 USB 接口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsbInterface {
+    pub class: u8,
+    pub subclass: u8,
+    pub protocol: u8,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl UsbInterface {
+    fn matches(&self, triple: (u8, u8, u8)) -> bool {
+        (self.class, self.subclass, self.protocol) == triple
+    }
+}
+
+/// 平台扫描器报告的原始设备信息，未做任何过滤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawUsbDevice {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub brand: Option<String>,
+    #[serde(default)]
+    pub serial: Option<String>,
+    #[serde(default)]
+    pub interfaces: Vec<UsbInterface>,
+}
+
+impl RawUsbDevice {
+    fn has_interface(&self, triple: (u8, u8, u8)) -> bool {
+        self.interfaces.iter().any(|i| i.matches(triple))
+    }
+
+    fn has_hdc_interface(&self) -> bool {
+        self.interfaces.iter().any(|i| {
+            i.matches(HDC_INTERFACE)
+                || i.name.as_deref().is_some_and(|n| n.to_lowercase().contains("hdc"))
+        })
+    }
+}
+
+/// 返回给前端的移动设备信息
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UsbDevice {
+    #[serde(rename = "type")]
+    pub device_type: String,
+    pub name: String,
+    pub serial: String,
+    pub brand: String,
+    pub vendor_id: String,
+    pub product_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usb_debugging: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted: Option<bool>,
+}
+
+/// 平台 USB 枚举接口
+pub trait UsbScanner: Send + Sync {
+    /// 枚举当前连接的全部 USB 设备
+    fn scan(&self) -> Result<Vec<RawUsbDevice>, String>;
+}
+
+/// 识别移动设备，非 Android / iOS / 鸿蒙设备返回 None
+pub fn classify(raw: &RawUsbDevice) -> Option<UsbDevice> {
+    let android = ANDROID_VENDORS.contains(&raw.vendor_id);
+    let ios = raw.vendor_id == APPLE_VENDOR_ID && IOS_PRODUCT_IDS.contains(&raw.product_id);
+    if !android && !ios {
+        return None;
+    }
+
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .unwrap_or("Unknown")
+            .to_string()
+    };
+    let name = text(&raw.name);
+    let serial = text(&raw.serial);
+    let vendor_id = format!("0x{:04X}", raw.vendor_id);
+    let product_id = format!("0x{:04X}", raw.product_id);
+
+    if ios {
+        return Some(UsbDevice {
+            device_type: "ios".to_string(),
+            name,
+            serial,
+            brand: "Apple".to_string(),
+            vendor_id,
+            product_id,
+            usb_debugging: None,
+            trusted: Some(raw.has_interface(IOS_TRUSTED_INTERFACE)),
+        });
+    }
+
+    let brand = text(&raw.brand);
+    let hdc = raw.has_hdc_interface();
+    let harmony = hdc
+        || (raw.vendor_id == HUAWEI_VENDOR_ID
+            && (brand.to_lowercase().contains("hisilicon")
+                || name.to_lowercase().contains("hdc")));
+
+    Some(UsbDevice {
+        device_type: if harmony { "harmony" } else { "android" }.to_string(),
+        name,
+        serial,
+        brand,
+        vendor_id,
+        product_id,
+        usb_debugging: Some(hdc || raw.has_interface(ADB_INTERFACE)),
+        trusted: None,
+    })
+}
+
+/// 扫描并筛选出移动设备
+pub fn scan_mobile_devices(scanner: &dyn UsbScanner) -> Result<Vec<UsbDevice>, String> {
+    Ok(scanner.scan()?.iter().filter_map(classify).collect())
+}
+
+/// 当前平台的扫描器；设置了 [`FIXTURE_ENV`] 时改用 [`FakeUsbScanner`]
+pub fn default_scanner() -> Result<Box<dyn UsbScanner>, String> {
+    if let Ok(path) = std::env::var(FIXTURE_ENV) {
+        return Ok(Box::new(FakeUsbScanner::from_json_file(path)?));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(LinuxUsbScanner::default()))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Box::new(MacUsbScanner))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Ok(Box::new(WindowsUsbScanner))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        Err("USB scanning is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(class: u8, subclass: u8, protocol: u8, name: Option<&str>) -> UsbInterface {
+        UsbInterface {
+            class,
+            subclass,
+            protocol,
+            name: name.map(str::to_string),
+        }
+    }
+
+    fn raw(vendor_id: u16, product_id: u16, serial: &str, interfaces: Vec<UsbInterface>) -> RawUsbDevice {
+        RawUsbDevice {
+            vendor_id,
+            product_id,
+            name: None,
+            brand: None,
+            serial: Some(serial.to_string()),
+            interfaces,
+        }
+    }
+
+    fn scan(devices: Vec<RawUsbDevice>) -> Vec<UsbDevice> {
+        scan_mobile_devices(&FakeUsbScanner::new(devices)).unwrap()
+    }
+
+    #[test]
+    fn filters_non_mobile_devices() {
+        let devices = scan(vec![
+            raw(0x1D6B, 0x0002, "0000:00:14.0", vec![interface(0x09, 0, 0, None)]),
+            raw(0x046D, 0xC31C, "KB1", vec![interface(0x03, 0x01, 0x01, None)]),
+            // Apple 键盘不是 iOS 设备
+            raw(APPLE_VENDOR_ID, 0x024F, "AK1", vec![interface(0x03, 0x01, 0x01, None)]),
+            raw(0x18D1, 0x4EE7, "PIXEL", vec![interface(0xFF, 0x42, 0x01, None)]),
+        ]);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].serial, "PIXEL");
+    }
+
+    #[test]
+    fn classifies_android_debugging_state() {
+        let devices = scan(vec![
+            raw(0x04E8, 0x6860, "ADB", vec![interface(0x06, 0x01, 0x01, None), interface(0xFF, 0x42, 0x01, None)]),
+            raw(0x2717, 0xFF48, "MTP", vec![interface(0x06, 0x01, 0x01, None)]),
+        ]);
+        assert_eq!(devices[0].device_type, "android");
+        assert_eq!(devices[0].usb_debugging, Some(true));
+        assert_eq!(devices[0].trusted, None);
+        assert_eq!(devices[1].device_type, "android");
+        assert_eq!(devices[1].usb_debugging, Some(false));
+        assert_eq!(devices[1].name, "Unknown");
+    }
+
+    #[test]
+    fn classifies_harmony_devices() {
+        let mut by_brand = raw(HUAWEI_VENDOR_ID, 0x107E, "BRAND", Vec::new());
+        by_brand.brand = Some("HiSilicon".to_string());
+        let devices = scan(vec![
+            raw(HUAWEI_VENDOR_ID, 0x5000, "HDC", vec![interface(0xFF, 0x50, 0x01, None)]),
+            raw(HUAWEI_VENDOR_ID, 0x5001, "NAMED", vec![interface(0xFF, 0xFF, 0xFF, Some("HDC Interface"))]),
+            by_brand,
+            // 华为的 Android 设备
+            raw(HUAWEI_VENDOR_ID, 0x107E, "ANDROID", vec![interface(0xFF, 0x42, 0x01, None)]),
+        ]);
+        let types: Vec<&str> = devices.iter().map(|d| d.device_type.as_str()).collect();
+        assert_eq!(types, ["harmony", "harmony", "harmony", "android"]);
+        assert_eq!(devices[0].usb_debugging, Some(true));
+        assert_eq!(devices[1].usb_debugging, Some(true));
+        assert_eq!(devices[2].usb_debugging, Some(false));
+    }
+
+    #[test]
+    fn classifies_ios_trust_state() {
+        let devices = scan(vec![
+            raw(APPLE_VENDOR_ID, 0x12A8, "TRUSTED", vec![interface(0x06, 0x01, 0x01, None)]),
+            raw(APPLE_VENDOR_ID, 0x12A8, "UNTRUSTED", vec![interface(0xFF, 0xFE, 0x02, None)]),
+        ]);
+        assert_eq!(devices[0].device_type, "ios");
+        assert_eq!(devices[0].brand, "Apple");
+        assert_eq!(devices[0].vendor_id, "0x05AC");
+        assert_eq!(devices[0].product_id, "0x12A8");
+        assert_eq!(devices[0].trusted, Some(true));
+        assert_eq!(devices[0].usb_debugging, None);
+        assert_eq!(devices[1].trusted, Some(false));
+    }
+
+    #[test]
+    fn loads_fixture_file() {
+        let path = std::env::temp_dir().join(format!("simhub_usb_fixture_{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[
+                {"vendor_id": 6353, "product_id": 20199, "name": "Pixel 7", "serial": "PIXEL",
+                 "interfaces": [{"class": 255, "subclass": 66, "protocol": 1}]},
+                {"vendor_id": 1452, "product_id": 4776, "serial": "IPHONE"}
+            ]"#,
+        )
+        .unwrap();
+        let scanner = FakeUsbScanner::from_json_file(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let devices = scan_mobile_devices(&scanner.unwrap()).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "Pixel 7");
+        assert_eq!(devices[0].usb_debugging, Some(true));
+        assert_eq!(devices[1].device_type, "ios");
+        assert_eq!(devices[1].trusted, Some(false));
+
+        assert!(FakeUsbScanner::from_json_file(std::env::temp_dir().join("simhub_missing.json")).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! USB 热插拔监听
+//!
+//! Linux 上监听内核 netlink uevent，收到 USB 事件后立即重新扫描；
+//! 其他平台（或 netlink 不可用时）按固定间隔扫描并比较前后两次结果。
+
+use super::{scan_mobile_devices, UsbDevice, UsbScanner};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 未指定时的轮询间隔
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub enum UsbEvent {
+    Attached(UsbDevice),
+    Detached(UsbDevice),
+    /// 设备仍在，但属性发生了变化（如 USB 调试授权、iOS 信任状态）
+    Changed(UsbDevice),
+}
+
+/// 比较两次扫描结果，以序列号区分设备
+pub fn diff(old: &[UsbDevice], new: &[UsbDevice]) -> Vec<UsbEvent> {
+    let find = |devices: &[UsbDevice], serial: &str| devices.iter().find(|d| d.serial == serial).cloned();
+
+    let detached = old
+        .iter()
+        .filter(|d| find(new, &d.serial).is_none())
+        .map(|d| UsbEvent::Detached(d.clone()));
+    let changed_or_attached = new.iter().filter_map(|d| match find(old, &d.serial) {
+        None => Some(UsbEvent::Attached(d.clone())),
+        Some(previous) if previous != *d => Some(UsbEvent::Changed(d.clone())),
+        Some(_) => None,
+    });
+    detached.chain(changed_or_attached).collect()
+}
+
+/// 后台监听线程，drop 时自动停止
+pub struct UsbWatcher {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl UsbWatcher {
+    /// 以当前设备列表为基线启动监听，之后的变化通过 `on_event` 回调
+    pub fn start<F>(scanner: Box<dyn UsbScanner>, interval: Duration, mut on_event: F) -> Self
+    where
+        F: FnMut(UsbEvent) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+
+        let thread = std::thread::spawn(move || {
+            let mut waiter = ChangeWaiter::new(interval);
+            let mut known = scan_mobile_devices(scanner.as_ref()).unwrap_or_default();
+
+            while flag.load(Ordering::SeqCst) {
+                waiter.wait();
+                if !flag.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                // 扫描失败时保留上一次结果，避免误报所有设备被拔出
+                let Ok(current) = scan_mobile_devices(scanner.as_ref()) else {
+                    continue;
+                };
+                for event in diff(&known, &current) {
+                    on_event(event);
+                }
+                known = current;
+            }
+        });
+
+        Self {
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 通知线程退出并等待其结束（最多一个等待周期）
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for UsbWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 等待下一次需要重新扫描的时机
+struct ChangeWaiter {
+    interval: Duration,
+    #[cfg(target_os = "linux")]
+    uevents: Option<uevent::UeventSocket>,
+}
+
+impl ChangeWaiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            #[cfg(target_os = "linux")]
+            uevents: uevent::UeventSocket::open(interval).ok(),
+        }
+    }
+
+    fn wait(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(socket) = &self.uevents {
+            // 收到 USB 事件后稍等片刻，让 sysfs 中的接口目录创建完毕，并合并同一设备的多条事件
+            if socket.wait_usb_event() {
+                std::thread::sleep(Duration::from_millis(300));
+                socket.drain();
+            }
+            return;
+        }
+
+        std::thread::sleep(self.interval);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod uevent {
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::time::Duration;
+
+    /// 订阅内核 kobject uevent 广播的 netlink 套接字
+    pub struct UeventSocket {
+        fd: OwnedFd,
+    }
+
+    impl UeventSocket {
+        /// `timeout` 为单次接收的超时时间，保证监听线程能及时响应停止请求
+        pub fn open(timeout: Duration) -> io::Result<Self> {
+            unsafe {
+                let raw = libc::socket(
+                    libc::AF_NETLINK,
+                    libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                    libc::NETLINK_KOBJECT_UEVENT,
+                );
+                if raw < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let fd = OwnedFd::from_raw_fd(raw);
+
+                let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+                addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+                addr.nl_groups = 1;
+                if libc::bind(
+                    fd.as_raw_fd(),
+                    &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                    std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+                ) < 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let tv = libc::timeval {
+                    tv_sec: timeout.as_secs() as libc::time_t,
+                    tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+                };
+                if libc::setsockopt(
+                    fd.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_RCVTIMEO,
+                    &tv as *const libc::timeval as *const libc::c_void,
+                    std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+                ) < 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+
+                Ok(Self { fd })
+            }
+        }
+
+        fn recv(&self, flags: libc::c_int) -> Option<Vec<u8>> {
+            let mut buf = vec![0u8; 8192];
+            let len = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    flags,
+                )
+            };
+            if len <= 0 {
+                return None;
+            }
+            buf.truncate(len as usize);
+            Some(buf)
+        }
+
+        /// 阻塞到收到 USB 子系统事件或超时，返回是否收到事件
+        pub fn wait_usb_event(&self) -> bool {
+            while let Some(message) = self.recv(0) {
+                if is_usb_event(&message) {
+                    return true;
+                }
+            }
+            false
+        }
+
+        /// 丢弃已排队的事件
+        pub fn drain(&self) {
+            while self.recv(libc::MSG_DONTWAIT).is_some() {}
+        }
+    }
+
+    /// uevent 消息是以 \0 分隔的 `KEY=VALUE` 列表，首项为 `action@devpath`
+    fn is_usb_event(message: &[u8]) -> bool {
+        message
+            .split(|&b| b == 0)
+            .any(|field| field == b"SUBSYSTEM=usb")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usb::{FakeUsbScanner, RawUsbDevice, UsbInterface};
+
+    fn android(serial: &str, debugging: bool) -> RawUsbDevice {
+        RawUsbDevice {
+            vendor_id: 0x18D1,
+            product_id: 0x4EE7,
+            name: Some("Pixel 7".to_string()),
+            brand: Some("Google".to_string()),
+            serial: Some(serial.to_string()),
+            interfaces: if debugging {
+                vec![UsbInterface { class: 0xFF, subclass: 0x42, protocol: 0x01, name: None }]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    fn scan(devices: Vec<RawUsbDevice>) -> Vec<UsbDevice> {
+        scan_mobile_devices(&FakeUsbScanner::new(devices)).unwrap()
+    }
+
+    fn summary(events: &[UsbEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                UsbEvent::Attached(d) => format!("+{}", d.serial),
+                UsbEvent::Detached(d) => format!("-{}", d.serial),
+                UsbEvent::Changed(d) => format!("~{}", d.serial),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_devices_produce_no_events() {
+        let devices = scan(vec![android("A", true), android("B", false)]);
+        assert!(diff(&devices, &devices).is_empty());
+    }
+
+    #[test]
+    fn reports_attached_and_detached() {
+        let old = scan(vec![android("A", true), android("B", true)]);
+        let new = scan(vec![android("B", true), android("C", true)]);
+        assert_eq!(summary(&diff(&old, &new)), ["-A", "+C"]);
+        assert_eq!(summary(&diff(&[], &old)), ["+A", "+B"]);
+        assert_eq!(summary(&diff(&old, &[])), ["-A", "-B"]);
+    }
+
+    #[test]
+    fn attribute_change_is_not_a_detach() {
+        let old = scan(vec![android("A", false), android("B", true)]);
+        let new = scan(vec![android("A", true), android("B", true)]);
+        let events = diff(&old, &new);
+        assert_eq!(summary(&events), ["~A"]);
+        let UsbEvent::Changed(device) = &events[0] else { unreachable!() };
+        assert_eq!(device.usb_debugging, Some(true));
+    }
+}
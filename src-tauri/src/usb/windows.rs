@@ -0,0 +1,215 @@
+//! Windows 实现：SetupAPI 枚举 USB 设备，CfgMgr32 遍历子节点获取接口信息
+//!
+//! 全部使用 W 系列 API，属性缓冲区按系统返回的实际长度分配，不再有 GBK 解码和截断问题。
+
+use super::{RawUsbDevice, UsbInterface, UsbScanner};
+use std::ptr::{null, null_mut};
+use windows_sys::core::GUID;
+use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_Get_Child, CM_Get_DevNode_Registry_PropertyW, CM_Get_Sibling, SetupDiDestroyDeviceInfoList,
+    SetupDiEnumDeviceInfo, SetupDiGetClassDevsW, SetupDiGetDeviceInstanceIdW,
+    SetupDiGetDeviceRegistryPropertyW, CM_DRP_COMPATIBLEIDS, CM_DRP_DEVICEDESC, CM_DRP_HARDWAREID,
+    CR_BUFFER_SMALL, CR_SUCCESS, DIGCF_DEVICEINTERFACE, DIGCF_PRESENT, HDEVINFO, SPDRP_DEVICEDESC,
+    SPDRP_FRIENDLYNAME, SPDRP_HARDWAREID, SPDRP_MFG, SP_DEVINFO_DATA,
+};
+use windows_sys::Win32::Devices::Usb::GUID_DEVINTERFACE_USB_DEVICE;
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+
+pub struct WindowsUsbScanner;
+
+/// 设备信息集合，离开作用域时释放
+struct DevInfoList(HDEVINFO);
+
+impl Drop for DevInfoList {
+    fn drop(&mut self) {
+        unsafe {
+            SetupDiDestroyDeviceInfoList(self.0);
+        }
+    }
+}
+
+/// UTF-16 字符串，遇到第一个 \0 截止
+fn wide_to_string(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
+/// REG_MULTI_SZ → 字符串列表
+fn split_multi_sz(buf: &[u16]) -> Vec<String> {
+    buf.split(|&c| c == 0)
+        .filter(|s| !s.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect()
+}
+
+/// 读取设备注册表属性：先查询所需字节数，再按实际长度分配缓冲区
+fn device_property(list: &DevInfoList, data: &SP_DEVINFO_DATA, property: u32) -> Option<Vec<u16>> {
+    let mut required: u32 = 0;
+    unsafe {
+        SetupDiGetDeviceRegistryPropertyW(list.0, data, property, null_mut(), null_mut(), 0, &mut required);
+    }
+    if required == 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u16; (required as usize).div_ceil(2)];
+    let ok = unsafe {
+        SetupDiGetDeviceRegistryPropertyW(
+            list.0,
+            data,
+            property,
+            null_mut(),
+            buf.as_mut_ptr().cast(),
+            (buf.len() * 2) as u32,
+            null_mut(),
+        )
+    };
+    (ok != 0).then_some(buf)
+}
+
+fn device_instance_id(list: &DevInfoList, data: &SP_DEVINFO_DATA) -> Option<String> {
+    let mut required: u32 = 0;
+    unsafe {
+        SetupDiGetDeviceInstanceIdW(list.0, data, null_mut(), 0, &mut required);
+    }
+    if required == 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u16; required as usize];
+    let ok = unsafe {
+        SetupDiGetDeviceInstanceIdW(list.0, data, buf.as_mut_ptr(), buf.len() as u32, null_mut())
+    };
+    (ok != 0).then(|| wide_to_string(&buf))
+}
+
+/// 读取设备节点注册表属性（用于接口子节点）
+fn devnode_property(dev_inst: u32, property: u32) -> Option<Vec<u16>> {
+    let mut len: u32 = 0;
+    let cr = unsafe {
+        CM_Get_DevNode_Registry_PropertyW(dev_inst, property, null_mut(), null_mut(), &mut len, 0)
+    };
+    if cr != CR_BUFFER_SMALL || len == 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u16; (len as usize).div_ceil(2)];
+    let mut len = (buf.len() * 2) as u32;
+    let cr = unsafe {
+        CM_Get_DevNode_Registry_PropertyW(dev_inst, property, null_mut(), buf.as_mut_ptr().cast(), &mut len, 0)
+    };
+    (cr == CR_SUCCESS).then_some(buf)
+}
+
+/// 取 `USB\VID_18D1&PID_4EE7&REV_0440` 或 `USB\Class_ff&SubClass_42&Prot_01` 中某个字段的十六进制值
+fn id_field(id: &str, key: &str) -> Option<u32> {
+    id.split(['\\', '&'])
+        .find_map(|token| {
+            let (name, value) = token.split_once('_')?;
+            name.eq_ignore_ascii_case(key).then_some(value)
+        })
+        .and_then(|value| u32::from_str_radix(value, 16).ok())
+}
+
+fn parse_vid_pid(hardware_ids: &[String]) -> Option<(u16, u16)> {
+    hardware_ids.iter().find_map(|id| {
+        Some((id_field(id, "VID")? as u16, id_field(id, "PID")? as u16))
+    })
+}
+
+fn parse_interface_class(ids: &[String]) -> Option<(u8, u8, u8)> {
+    ids.iter().find_map(|id| {
+        Some((
+            id_field(id, "Class")? as u8,
+            id_field(id, "SubClass")? as u8,
+            id_field(id, "Prot")? as u8,
+        ))
+    })
+}
+
+/// 复合设备的每个子节点对应一个接口
+fn read_interfaces(dev_inst: u32) -> Vec<UsbInterface> {
+    let mut interfaces = Vec::new();
+    let mut child: u32 = 0;
+    let mut cr = unsafe { CM_Get_Child(&mut child, dev_inst, 0) };
+
+    while cr == CR_SUCCESS {
+        let mut ids = Vec::new();
+        for property in [CM_DRP_COMPATIBLEIDS, CM_DRP_HARDWAREID] {
+            if let Some(buf) = devnode_property(child, property) {
+                ids.extend(split_multi_sz(&buf));
+            }
+        }
+
+        if let Some((class, subclass, protocol)) = parse_interface_class(&ids) {
+            interfaces.push(UsbInterface {
+                class,
+                subclass,
+                protocol,
+                name: devnode_property(child, CM_DRP_DEVICEDESC).map(|buf| wide_to_string(&buf)),
+            });
+        }
+
+        let current = child;
+        cr = unsafe { CM_Get_Sibling(&mut child, current, 0) };
+    }
+
+    interfaces
+}
+
+impl UsbScanner for WindowsUsbScanner {
+    fn scan(&self) -> Result<Vec<RawUsbDevice>, String> {
+        let handle = unsafe {
+            SetupDiGetClassDevsW(
+                &GUID_DEVINTERFACE_USB_DEVICE,
+                null(),
+                null_mut(),
+                DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE as HDEVINFO {
+            return Err("USB scan failed".to_string());
+        }
+        let list = DevInfoList(handle);
+
+        let mut devices = Vec::new();
+        for index in 0.. {
+            let mut data = SP_DEVINFO_DATA {
+                cbSize: std::mem::size_of::<SP_DEVINFO_DATA>() as u32,
+                ClassGuid: GUID::from_u128(0),
+                DevInst: 0,
+                Reserved: 0,
+            };
+            if unsafe { SetupDiEnumDeviceInfo(list.0, index, &mut data) } == 0 {
+                break;
+            }
+
+            let hardware_ids = device_property(&list, &data, SPDRP_HARDWAREID)
+                .map(|buf| split_multi_sz(&buf))
+                .unwrap_or_default();
+            let Some((vendor_id, product_id)) = parse_vid_pid(&hardware_ids) else {
+                continue;
+            };
+
+            let name = device_property(&list, &data, SPDRP_FRIENDLYNAME)
+                .or_else(|| device_property(&list, &data, SPDRP_DEVICEDESC))
+                .map(|buf| wide_to_string(&buf));
+            let brand = device_property(&list, &data, SPDRP_MFG).map(|buf| wide_to_string(&buf));
+            // 实例 ID 形如 USB\VID_xxxx&PID_xxxx\<serial>
+            let serial = device_instance_id(&list, &data)
+                .and_then(|id| id.rsplit('\\').next().map(str::to_string))
+                .filter(|s| !s.is_empty());
+
+            devices.push(RawUsbDevice {
+                vendor_id,
+                product_id,
+                name,
+                brand,
+                serial,
+                interfaces: read_interfaces(data.DevInst),
+            });
+        }
+
+        Ok(devices)
+    }
+}
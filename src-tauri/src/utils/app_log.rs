@@ -0,0 +1,32 @@
+//! 应用日志：通过 `add-log` 事件显示在前端的日志面板中
+//!
+//! 供拿不到 `AppHandle` 的后台代码使用，应用启动时由 [`init`] 记录句柄。
+
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+/// `kind` 与前端日志类型一致：command / info / success / error
+pub fn log(kind: &str, message: impl Into<String>) {
+    let message = message.into();
+    match APP.get() {
+        Some(app) => {
+            let _ = app.emit("add-log", serde_json::json!({
+                "type": kind,
+                "message": message,
+                "source": "app"
+            }));
+        }
+        // 窗口创建之前（或测试中）只能输出到终端
+        None => eprintln!("[{}] {}", kind, message),
+    }
+}
+
+pub fn error(message: impl Into<String>) {
+    log("error", message);
+}
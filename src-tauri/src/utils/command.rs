@@ -5,6 +5,7 @@
 use std::ffi::OsStr;
 use std::process::{Command, Output, Stdio};
 use std::io::Result;
+use std::sync::{Arc, Mutex};
 
 /// Create a new Command that won't show a console window on Windows
 pub fn new_command<S: AsRef<OsStr>>(program: S) -> Command {
@@ -68,3 +69,91 @@ pub fn spawn_command_with_output<S: AsRef<OsStr>>(program: S, args: &[&str]) ->
         .stderr(Stdio::piped())
         .spawn()
 }
+
+/// 在后台持续读取子进程 stderr，只保留末尾部分用于报错
+pub fn capture_stderr(child: &mut std::process::Child) -> Arc<Mutex<String>> {
+    use std::io::BufRead;
+
+    const MAX_LEN: usize = 8 * 1024;
+    let captured = Arc::new(Mutex::new(String::new()));
+    if let Some(stderr) = child.stderr.take() {
+        let captured = captured.clone();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(stderr).lines().map_while(Result::ok) {
+                let mut text = captured.lock().unwrap();
+                text.push_str(&line);
+                text.push('\n');
+                if text.len() > MAX_LEN {
+                    let cut = text.len() - MAX_LEN;
+                    let cut = (cut..text.len()).find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+                    text.drain(..cut);
+                }
+            }
+        });
+    }
+    captured
+}
+
+/// 按空白拆分参数，单引号或双引号内的空白保留
+pub fn split_args(input: &str) -> std::result::Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in arguments: {}", input));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_arguments() {
+        let cases: [(&str, &[&str]); 7] = [
+            ("", &[]),
+            ("   ", &[]),
+            ("-a  -b\tc", &["-a", "-b", "c"]),
+            (r#"-append "console=ttyS0 debug""#, &["-append", "console=ttyS0 debug"]),
+            ("-prop 'a=b c' d", &["-prop", "a=b c", "d"]),
+            (r#"--name="x y"z"#, &["--name=x yz"]),
+            (r#""" ''"#, &["", ""]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(split_args(input).unwrap(), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(split_args(r#"-prop "a=b"#).unwrap_err().starts_with("Unterminated quote"));
+        assert!(split_args("'").is_err());
+    }
+}
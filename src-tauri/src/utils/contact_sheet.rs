@@ -0,0 +1,149 @@
+//! 把多张截图拼成一张带设备标签的总览图
+//!
+//! 标签用系统字体绘制，优先选择带中文字形的字体，单个字符缺字时依次换用后面的字体。
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::{imageops, Rgba, RgbaImage};
+use std::path::Path;
+
+/// 每张截图缩放后的高度
+const TILE_HEIGHT: u32 = 800;
+const PADDING: u32 = 16;
+/// 标签字号 (px)
+const FONT_SIZE: f32 = 28.0;
+const LABEL_HEIGHT: u32 = FONT_SIZE as u32 + PADDING;
+
+const BACKGROUND: Rgba<u8> = Rgba([32, 33, 36, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// (标签, 截图路径)，按顺序从左到右、从上到下排列
+pub fn build(tiles: &[(String, String)], output: &Path) -> Result<(), String> {
+    if tiles.is_empty() {
+        return Err("No screenshots to combine".to_string());
+    }
+
+    let mut images = Vec::with_capacity(tiles.len());
+    for (label, path) in tiles {
+        let image = image::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?
+            .to_rgba8();
+        let width = (image.width() as u64 * TILE_HEIGHT as u64 / image.height().max(1) as u64).max(1) as u32;
+        let image = imageops::resize(&image, width, TILE_HEIGHT, imageops::FilterType::Triangle);
+        images.push((label.as_str(), image));
+    }
+
+    let columns = (images.len() as f64).sqrt().ceil() as u32;
+    let rows = (images.len() as u32).div_ceil(columns);
+    let cell_width = images.iter().map(|(_, image)| image.width()).max().unwrap_or(1) + PADDING;
+    let cell_height = TILE_HEIGHT + LABEL_HEIGHT + PADDING;
+
+    // 找不到任何字体时只拼图不画标签，设备信息仍可从各截图的文件名中看出
+    let fonts = load_fonts();
+    let mut sheet = RgbaImage::from_pixel(
+        columns * cell_width + PADDING,
+        rows * cell_height + PADDING,
+        BACKGROUND,
+    );
+    for (index, (label, image)) in images.iter().enumerate() {
+        let x = PADDING + (index as u32 % columns) * cell_width;
+        let y = PADDING + (index as u32 / columns) * cell_height;
+        draw_text(&mut sheet, &fonts, label, x, y, cell_width - PADDING);
+        // 窄于单元格的截图居中
+        let offset = (cell_width - PADDING - image.width()) / 2;
+        imageops::overlay(&mut sheet, image, (x + offset) as i64, (y + LABEL_HEIGHT) as i64);
+    }
+
+    sheet
+        .save(output)
+        .map_err(|e| format!("Failed to save contact sheet: {}", e))
+}
+
+/// 按顺序尝试的字体文件：先是各平台自带的中文字体，最后是只含西文的常见字体
+#[cfg(target_os = "windows")]
+fn font_candidates() -> Vec<std::path::PathBuf> {
+    let dir = std::path::PathBuf::from(std::env::var("WINDIR").unwrap_or_else(|_| "C:\\Windows".to_string())).join("Fonts");
+    ["msyh.ttc", "msyh.ttf", "simhei.ttf", "simsun.ttc", "segoeui.ttf", "arial.ttf"]
+        .iter()
+        .map(|name| dir.join(name))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn font_candidates() -> Vec<std::path::PathBuf> {
+    [
+        "/System/Library/Fonts/PingFang.ttc",
+        "/System/Library/Fonts/Hiragino Sans GB.ttc",
+        "/System/Library/Fonts/STHeiti Medium.ttc",
+        "/Library/Fonts/Arial Unicode.ttf",
+        "/System/Library/Fonts/Helvetica.ttc",
+    ]
+    .iter()
+    .map(std::path::PathBuf::from)
+    .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn font_candidates() -> Vec<std::path::PathBuf> {
+    [
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+        "/usr/share/fonts/wqy-microhei/wqy-microhei.ttc",
+        "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    ]
+    .iter()
+    .map(std::path::PathBuf::from)
+    .collect()
+}
+
+/// 加载存在的候选字体；.ttc 字体集合取第一个字体
+fn load_fonts() -> Vec<FontVec> {
+    font_candidates()
+        .into_iter()
+        .filter_map(|path| std::fs::read(path).ok())
+        .filter_map(|data| FontVec::try_from_vec_and_index(data, 0).ok())
+        .collect()
+}
+
+/// 绘制单行文字，超出宽度的部分截断
+fn draw_text(image: &mut RgbaImage, fonts: &[FontVec], text: &str, x: u32, y: u32, max_width: u32) {
+    let Some(primary) = fonts.first() else {
+        return;
+    };
+    let scale = PxScale::from(FONT_SIZE);
+    let baseline = y as f32 + primary.as_scaled(scale).ascent();
+    let right = (x + max_width) as f32;
+    let mut caret = x as f32;
+
+    for c in text.chars() {
+        // 所有字体都缺字时用第一个字体的缺字符号
+        let font = fonts.iter().find(|f| f.glyph_id(c).0 != 0).unwrap_or(primary);
+        let scaled = font.as_scaled(scale);
+        let mut glyph = scaled.scaled_glyph(c);
+        let advance = scaled.h_advance(glyph.id);
+        if caret + advance > right {
+            break;
+        }
+
+        glyph.position = point(caret, baseline);
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i64 + gx as i64;
+                let py = bounds.min.y as i64 + gy as i64;
+                if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                    return;
+                }
+                let pixel = image.get_pixel_mut(px as u32, py as u32);
+                for i in 0..3 {
+                    let blended = pixel[i] as f32 * (1.0 - coverage) + TEXT_COLOR[i] as f32 * coverage;
+                    pixel[i] = blended.round() as u8;
+                }
+            });
+        }
+        caret += advance;
+    }
+}
@@ -1,5 +1,7 @@
 //! Shared utilities for the emulator manager
 
+pub mod app_log;
 pub mod command;
+pub mod contact_sheet;
 
 pub use command::*;
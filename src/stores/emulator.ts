@@ -2,16 +2,93 @@ import { defineStore } from 'pinia'
 import { ref } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 
+export type DeviceStatus = 'stopped' | 'booting' | 'running' | 'shutting-down' | 'error'
+
 export interface Emulator {
   id: string
   type: 'ios' | 'android' | 'harmony'
   name: string
   deviceType: string
   osVersion: string
-  status: 'running' | 'stopped'
+  status: DeviceStatus
   lastUsedAt?: number
 }
 
+export type Platform = Emulator['type']
+
+export interface InstallOutcome {
+  paths: string[]
+  success: boolean
+  code?: string | null
+  error?: string | null
+}
+
+export interface InstallOptions {
+  reinstall?: boolean
+  allow_downgrade?: boolean
+  grant_permissions?: boolean
+  same_app?: boolean
+  allow_test_packages?: boolean
+}
+
+export interface PackageInfo {
+  name: string
+  is_system: boolean
+  version_name?: string | null
+  version_code?: number | null
+  first_install_time?: string | null
+  last_update_time?: string | null
+  min_sdk?: number | null
+  target_sdk?: number | null
+  apk_path?: string | null
+  uid?: number | null
+  data_size?: number | null
+  requested_permissions: string[]
+  granted_permissions: string[]
+}
+
+export type PackageAction =
+  | { type: 'uninstall', keep_data?: boolean }
+  | { type: 'clear-data' }
+  | { type: 'force-stop' }
+  | { type: 'launch' }
+  | { type: 'grant', permission: string }
+  | { type: 'revoke', permission: string }
+
+export interface PackageMetadata {
+  format: 'apk' | 'apks' | 'hap' | 'hsp'
+  package: string
+  version_name?: string | null
+  version_code?: number | null
+  min_sdk?: number | null
+  target_sdk?: number | null
+  permissions: string[]
+  abis: string[]
+  launchable?: string | null
+  module_name?: string | null
+  module_type?: string | null
+  signing: {
+    schemes: string[]
+    certificates: { subject: string, sha256: string }[]
+  }
+  file_size: number
+}
+
+export interface RecordingOptions {
+  bit_rate?: number
+  size?: string
+  time_limit_secs?: number
+  dir?: string
+}
+
+export interface RecordingResult {
+  platform: 'android' | 'harmony'
+  device_id: string
+  files: string[]
+  duration_ms: number
+  error?: string | null
+}
+
 export const useEmulatorStore = defineStore('emulator', () => {
   const emulators = ref<Emulator[]>([])
   const loading = ref(false)
@@ -36,7 +113,7 @@ export const useEmulatorStore = defineStore('emulator', () => {
         name: e.name,
         deviceType: e.device_type,
         osVersion: e.os_version,
-        status: e.status as 'running' | 'stopped'
+        status: e.status as DeviceStatus
       }))
     } finally {
       loading.value = false
@@ -95,6 +172,49 @@ export const useEmulatorStore = defineStore('emulator', () => {
     throw new Error('Unknown emulator type')
   }
 
+  async function startRecording(id: string, options?: RecordingOptions): Promise<void> {
+    const type = currentType.value
+    if (type === 'android') {
+      await invoke('start_recording_android', { id, options })
+      return
+    } else if (type === 'harmony') {
+      await invoke('start_recording_harmony', { id, options })
+      return
+    }
+    throw new Error('Screen recording is not supported for this emulator type')
+  }
+
+  async function stopRecording(id: string): Promise<RecordingResult> {
+    const type = currentType.value
+    if (type === 'android') {
+      return await invoke('stop_recording_android', { id })
+    } else if (type === 'harmony') {
+      return await invoke('stop_recording_harmony', { id })
+    }
+    throw new Error('Screen recording is not supported for this emulator type')
+  }
+
+  async function installPackages(id: string, paths: string[], options?: InstallOptions): Promise<InstallOutcome[]> {
+    const platform = currentType.value
+    return await invoke('install_packages', { platform, id, paths, options })
+  }
+
+  async function listPackages(platform: Platform, id: string): Promise<PackageInfo[]> {
+    return await invoke('list_device_packages', { platform, id })
+  }
+
+  async function getPackageDetails(platform: Platform, id: string, pkg: string): Promise<PackageInfo> {
+    return await invoke('get_package_details', { platform, id, package: pkg })
+  }
+
+  async function packageAction(platform: Platform, id: string, pkg: string, action: PackageAction): Promise<void> {
+    await invoke('package_action', { platform, id, package: pkg, action })
+  }
+
+  async function inspectPackage(path: string): Promise<PackageMetadata> {
+    return await invoke('inspect_package', { path })
+  }
+
   return {
     emulators,
     loading,
@@ -103,6 +223,13 @@ export const useEmulatorStore = defineStore('emulator', () => {
     stopEmulator,
     deleteEmulator,
     wipeData,
-    takeScreenshot
+    takeScreenshot,
+    startRecording,
+    stopRecording,
+    installPackages,
+    listPackages,
+    getPackageDetails,
+    packageAction,
+    inspectPackage
   }
 })